@@ -0,0 +1,875 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use sv_parser::{
+    parse_sv_pp, preprocess_str, Defines, Error, Locate, NodeEvent, NodeKind, RefNode, SyntaxTree,
+    WhiteSpace,
+};
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BeginStyle {
+    /// Keep `begin` and `else` on the line where they are written
+    Keep,
+    /// Move `begin` to the end of the previous line and `else` after `end`
+    SameLine,
+}
+
+#[derive(Clone, Debug)]
+pub struct FormatConfig {
+    /// Number of spaces per indentation level
+    pub indent_width: usize,
+    /// Align port lists, port connections and declarations
+    pub align: bool,
+    /// Placement of `begin` and `else`
+    pub begin_style: BeginStyle,
+    /// Maximum number of consecutive blank lines
+    pub max_blank_lines: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            indent_width: 2,
+            align: true,
+            begin_style: BeginStyle::Keep,
+            max_blank_lines: 1,
+        }
+    }
+}
+
+/// Format the specified file
+pub fn format<T: AsRef<Path>, U: AsRef<Path>>(
+    path: T,
+    pre_defines: &Defines,
+    include_paths: &[U],
+    config: &FormatConfig,
+) -> Result<String, Error> {
+    let f = File::open(path.as_ref()).map_err(|x| Error::File {
+        source: x,
+        path: PathBuf::from(path.as_ref()),
+    })?;
+    let mut reader = BufReader::new(f);
+    let mut s = String::new();
+    if reader.read_to_string(&mut s).is_err() {
+        return Err(Error::ReadUtf8(PathBuf::from(path.as_ref())));
+    }
+    format_str(&s, path, pre_defines, include_paths, config)
+}
+
+/// Format the specified source code
+///
+/// The output has the line ending of the first line of `s`. `include directives are resolved with `include_paths`, but the included
+/// text isn't formatted. Lines which can't be mapped to the syntax tree
+/// completely, for example lines including compiler directives, macro usages
+/// or code in inactive `ifdef` branches, are kept as is.
+pub fn format_str<T: AsRef<Path>, U: AsRef<Path>>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines,
+    include_paths: &[U],
+    config: &FormatConfig,
+) -> Result<String, Error> {
+    let (text, defines) = preprocess_str(
+        s,
+        path.as_ref(),
+        pre_defines,
+        include_paths,
+        false, // ignore_include
+        false, // strip_comments
+        0,     // resolve_depth
+        0,     // include_depth
+    )?;
+    let (syntax_tree, _) = parse_sv_pp(text, defines, false)?;
+    let source = Source::new(s, path.as_ref(), &syntax_tree);
+    let ret = source.format(config);
+
+    // The line ending of the first line is used for all lines.
+    if s.find('\n').is_some_and(|x| s[..x].ends_with('\r')) {
+        Ok(ret.replace("\r\n", "\n").replace('\n', "\r\n"))
+    } else {
+        Ok(ret)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+struct Node {
+    kind: NodeKind,
+    parent: Option<usize>,
+    first: Option<usize>,
+}
+
+struct Token {
+    /// Offset in the original source if the token is written in it
+    offset: Option<usize>,
+    len: usize,
+    line: usize,
+    node: Option<usize>,
+    comment: bool,
+    opener: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    Port,
+    Declaration,
+    Connection,
+}
+
+struct Source<'a> {
+    s: &'a str,
+    nodes: Vec<Node>,
+    tokens: Vec<Token>,
+    line_starts: Vec<usize>,
+    verbatim: Vec<bool>,
+    line_tokens: Vec<Vec<usize>>,
+}
+
+impl<'a> Source<'a> {
+    fn new(s: &'a str, path: &Path, syntax_tree: &SyntaxTree) -> Self {
+        let mut line_starts = vec![0];
+        for (i, c) in s.bytes().enumerate() {
+            if c == b'\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        let n_lines = line_starts.len();
+
+        let mut ret = Source {
+            s,
+            nodes: Vec::new(),
+            tokens: Vec::new(),
+            line_starts,
+            verbatim: vec![false; n_lines],
+            line_tokens: vec![Vec::new(); n_lines],
+        };
+
+        let mut covered = vec![false; s.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        let mut white_space: Vec<Option<&WhiteSpace>> = Vec::new();
+        let mut brackets: Vec<usize> = Vec::new();
+        let mut last_end = 0;
+        let mut last_line = 0;
+
+        for event in syntax_tree.into_iter().event() {
+            match event {
+                NodeEvent::Enter(RefNode::Locate(x)) => {
+                    let trivia = white_space.iter().rev().find_map(|x| *x);
+                    let comment = matches!(trivia, Some(WhiteSpace::Comment(_)));
+                    let directive = matches!(trivia, Some(WhiteSpace::CompilerDirective(_)));
+                    if trivia.is_some() && !comment && !directive {
+                        continue;
+                    }
+
+                    let text = syntax_tree.get_str(x).unwrap_or("");
+                    let offset = ret.original_offset(syntax_tree, path, x, text, last_end);
+                    let len = if comment {
+                        text.trim_end_matches(['\n', '\r']).len()
+                    } else {
+                        text.len()
+                    };
+
+                    if let Some(offset) = offset {
+                        last_end = offset + len;
+                        last_line = ret.line_of(offset);
+                        let end_line = ret.line_of(offset + len.max(1) - 1);
+                        if directive || end_line != last_line {
+                            for line in last_line..=end_line {
+                                ret.verbatim[line] = true;
+                            }
+                        }
+                        for c in covered.iter_mut().skip(offset).take(len) {
+                            *c = true;
+                        }
+                    }
+                    if directive {
+                        continue;
+                    }
+
+                    let id = ret.tokens.len();
+                    let node = stack.last().copied();
+                    let mut opener = None;
+                    if !comment {
+                        match text {
+                            "(" | "[" | "{" | "'{" => brackets.push(id),
+                            ")" | "]" | "}" => opener = brackets.pop(),
+                            _ => (),
+                        }
+                        for &n in &stack {
+                            if ret.nodes[n].first.is_none() {
+                                ret.nodes[n].first = Some(id);
+                            }
+                        }
+                    }
+                    if offset.is_some() {
+                        ret.line_tokens[last_line].push(id);
+                    }
+                    ret.tokens.push(Token {
+                        offset,
+                        len,
+                        line: last_line,
+                        node,
+                        comment,
+                        opener,
+                    });
+                }
+                NodeEvent::Enter(x) => {
+                    if let RefNode::WhiteSpace(w) = x {
+                        white_space.push(Some(w));
+                    } else {
+                        white_space.push(None);
+                    }
+                    let id = ret.nodes.len();
+                    ret.nodes.push(Node {
+                        kind: x.kind(),
+                        parent: stack.last().copied(),
+                        first: None,
+                    });
+                    stack.push(id);
+                }
+                NodeEvent::Leave(RefNode::Locate(_)) => (),
+                NodeEvent::Leave(_) => {
+                    white_space.pop();
+                    stack.pop();
+                }
+            }
+        }
+
+        for line in 0..n_lines {
+            let text = ret.line_str(line);
+            let begin = ret.line_starts[line];
+            if text.contains('`') {
+                ret.verbatim[line] = true;
+            }
+            for (i, c) in text.bytes().enumerate() {
+                if !c.is_ascii_whitespace() && !covered[begin + i] {
+                    ret.verbatim[line] = true;
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Get the offset in the original source of the token if the token is
+    /// written in it, not produced by macro expansion or included file.
+    fn original_offset(
+        &self,
+        syntax_tree: &SyntaxTree,
+        path: &Path,
+        locate: &Locate,
+        text: &str,
+        last_end: usize,
+    ) -> Option<usize> {
+        let (origin_path, offset) = syntax_tree.get_origin(locate)?;
+        if origin_path.as_path() != path || offset < last_end {
+            return None;
+        }
+        if self.s.get(offset..offset + text.len()) == Some(text) {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(x) => x,
+            Err(x) => x - 1,
+        }
+    }
+
+    fn line_str(&self, line: usize) -> &'a str {
+        let begin = self.line_starts[line];
+        let end = if line + 1 < self.line_starts.len() {
+            self.line_starts[line + 1] - 1
+        } else {
+            self.s.len()
+        };
+        self.s[begin..end].trim_end_matches('\r')
+    }
+
+    fn token_str(&self, id: usize) -> &'a str {
+        let token = &self.tokens[id];
+        if let Some(offset) = token.offset {
+            &self.s[offset..offset + token.len]
+        } else {
+            ""
+        }
+    }
+
+    fn has_space_before(&self, id: usize) -> bool {
+        if let Some(offset) = self.tokens[id].offset {
+            offset > 0 && self.s.as_bytes()[offset - 1].is_ascii_whitespace()
+        } else {
+            true
+        }
+    }
+
+    fn ancestors(&self, id: usize) -> Vec<usize> {
+        let mut ret = Vec::new();
+        let mut node = self.tokens[id].node;
+        while let Some(x) = node {
+            ret.push(x);
+            node = self.nodes[x].parent;
+        }
+        ret
+    }
+
+    fn parent_kind(&self, id: usize) -> Option<NodeKind> {
+        let node = self.tokens[id].node?;
+        let parent = self.nodes[node].parent?;
+        Some(self.nodes[parent].kind)
+    }
+
+    fn is_keyword(&self, id: usize) -> bool {
+        self.tokens[id]
+            .node
+            .map(|x| self.nodes[x].kind == NodeKind::Keyword)
+            .unwrap_or(false)
+    }
+
+    fn is_symbol(&self, id: usize, s: &str) -> bool {
+        self.tokens[id]
+            .node
+            .map(|x| self.nodes[x].kind == NodeKind::Symbol)
+            .unwrap_or(false)
+            && self.token_str(id) == s
+    }
+
+    /// Find the token which opens the construct closed by the specified token
+    fn closing_opener(&self, id: usize) -> Option<usize> {
+        let token = &self.tokens[id];
+        if token.comment {
+            return None;
+        }
+        if token.opener.is_some() {
+            return token.opener;
+        }
+        let text = self.token_str(id);
+        if self.is_keyword(id)
+            && (text.starts_with("end") || text.starts_with("join") || text == "else")
+        {
+            let keyword = token.node?;
+            let parent = self.nodes[keyword].parent?;
+            return self.nodes[parent].first;
+        }
+        None
+    }
+
+    fn format(&self, config: &FormatConfig) -> String {
+        let n_lines = self.line_starts.len();
+
+        // Assign virtual lines considering lines joined by BeginStyle::SameLine.
+        let mut vline = vec![0; n_lines];
+        let mut joined = vec![false; n_lines];
+        let mut next = 0;
+        for line in 0..n_lines {
+            if line > 0 && config.begin_style == BeginStyle::SameLine && self.joinable(line) {
+                vline[line] = vline[line - 1];
+                joined[line] = true;
+            } else {
+                vline[line] = next;
+                next += 1;
+            }
+        }
+        let token_vline = |id: usize| vline[self.tokens[id].line];
+
+        // Calculate indentation level of each virtual line.
+        let mut indent: Vec<usize> = vec![0; next];
+        for line in 0..n_lines {
+            if joined[line] {
+                continue;
+            }
+            let v = vline[line];
+            let first = if let Some(x) = self.line_tokens[line].first() {
+                *x
+            } else {
+                continue;
+            };
+            indent[v] = if let Some(opener) = self.closing_opener(first) {
+                let o = token_vline(opener);
+                if o < v {
+                    indent[o]
+                } else {
+                    0
+                }
+            } else {
+                let target = if self.tokens[first].comment {
+                    (first..self.tokens.len()).find(|x| !self.tokens[*x].comment)
+                } else {
+                    Some(first)
+                };
+                let mut outer = None;
+                if let Some(target) = target {
+                    for node in self.ancestors(target) {
+                        let node = &self.nodes[node];
+                        if node.kind == NodeKind::SourceText {
+                            continue;
+                        }
+                        if let Some(x) = node.first {
+                            let o = token_vline(x);
+                            // Items of a list starting at the head of line are kept at the
+                            // same level as the first item.
+                            let list = node.kind.name().starts_with("List")
+                                && self.line_tokens[self.tokens[x].line].first() == Some(&x);
+                            if o < v && outer.map(|(y, _)| o > y).unwrap_or(true) {
+                                outer = Some((o, list));
+                            } else if outer == Some((o, true)) && !list {
+                                outer = Some((o, false));
+                            }
+                        }
+                    }
+                }
+                match outer {
+                    Some((x, true)) => indent[x],
+                    Some((x, false)) => indent[x] + 1,
+                    None => 0,
+                }
+            };
+        }
+
+        // Render tokens of each virtual line.
+        let mut rendered: Vec<Option<Vec<(String, usize)>>> = vec![None; next];
+        for (line, tokens) in self.line_tokens.iter().enumerate() {
+            if self.verbatim[line] || tokens.is_empty() {
+                continue;
+            }
+            let v = vline[line];
+            let pieces = rendered[v].get_or_insert_with(Vec::new);
+            for (i, id) in tokens.iter().enumerate() {
+                let space = if i == 0 {
+                    if pieces.is_empty() {
+                        ""
+                    } else {
+                        " "
+                    }
+                } else {
+                    self.spacing(tokens[i - 1], *id)
+                };
+                pieces.push((format!("{}{}", space, self.token_str(*id)), *id));
+            }
+        }
+
+        if config.align {
+            self.align(&mut rendered, &indent, &vline, &joined);
+        }
+
+        // Emit lines.
+        let mut ret = String::new();
+        let mut blank = 0;
+        let mut v_done = vec![false; next];
+        for (line, &v) in vline.iter().enumerate() {
+            if v_done[v] {
+                continue;
+            }
+            let text = if self.verbatim[line] {
+                String::from(self.line_str(line))
+            } else if let Some(pieces) = &rendered[v] {
+                let mut text = " ".repeat(indent[v] * config.indent_width);
+                for (x, _) in pieces {
+                    text.push_str(x);
+                }
+                text
+            } else {
+                String::new()
+            };
+            v_done[v] = true;
+
+            if text.trim().is_empty() {
+                blank += 1;
+                if blank > config.max_blank_lines {
+                    continue;
+                }
+                ret.push('\n');
+            } else {
+                blank = 0;
+                ret.push_str(&text);
+                ret.push('\n');
+            }
+        }
+        while ret.ends_with("\n\n") {
+            ret.pop();
+        }
+        if ret.trim().is_empty() {
+            ret.clear();
+        }
+        ret
+    }
+
+    /// Check whether the specified line can be joined to the previous line
+    fn joinable(&self, line: usize) -> bool {
+        if self.verbatim[line] || self.verbatim[line - 1] {
+            return false;
+        }
+        let tokens = &self.line_tokens[line];
+        let prev = &self.line_tokens[line - 1];
+        let (first, last) = match (tokens.first(), prev.last()) {
+            (Some(x), Some(y)) => (*x, *y),
+            _ => return false,
+        };
+        if self.tokens[last].comment || tokens.iter().any(|x| self.tokens[*x].comment) {
+            return false;
+        }
+        let first_str = self.token_str(first);
+        let last_str = self.token_str(last);
+
+        // `begin` alone in the line, optionally with label.
+        let only_begin = self.is_keyword(first)
+            && first_str == "begin"
+            && (tokens.len() == 1 || (tokens.len() == 3 && self.token_str(tokens[1]) == ":"));
+        if only_begin {
+            return !matches!(last_str, ";" | "begin" | "end" | "endcase" | "join")
+                && !self.is_keyword(last)
+                || matches!(last_str, "else" | "do" | "forever" | "initial" | "final")
+                || last_str.starts_with("always");
+        }
+
+        // `else` just after `end`.
+        let prev_only_end = self.token_str(prev[0]) == "end"
+            && self.is_keyword(prev[0])
+            && (prev.len() == 1 || (prev.len() == 3 && self.token_str(prev[1]) == ":"));
+        first_str == "else" && self.is_keyword(first) && prev_only_end
+    }
+
+    fn spacing(&self, prev: usize, cur: usize) -> &'static str {
+        let had_space = self.has_space_before(cur);
+        let preserve = if had_space { " " } else { "" };
+        if self.tokens[cur].comment || self.tokens[prev].comment {
+            return preserve;
+        }
+        let prev_str = self.token_str(prev);
+        let cur_str = self.token_str(cur);
+
+        let no_space = self.is_symbol(cur, ",")
+            || self.is_symbol(cur, ";")
+            || self.is_symbol(cur, ")")
+            || self.is_symbol(cur, "]")
+            || self.is_symbol(prev, "(")
+            || self.is_symbol(prev, "[");
+        let space = self.is_symbol(prev, ",")
+            || self.is_assignment(prev)
+            || self.is_assignment(cur)
+            || prev_str.is_empty()
+            || cur_str.is_empty();
+
+        if no_space && !self.is_symbol(prev, ",") {
+            ""
+        } else if space {
+            " "
+        } else {
+            preserve
+        }
+    }
+
+    fn is_assignment(&self, id: usize) -> bool {
+        const OPS: &[&str] = &[
+            "=", "<=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", "<<<=", ">>>=",
+        ];
+        if !OPS.iter().any(|x| self.is_symbol(id, x)) {
+            return false;
+        }
+        // `<=` in expression is an operator, not an assignment.
+        !matches!(
+            self.parent_kind(id),
+            Some(NodeKind::BinaryOperator) | Some(NodeKind::EventExpression)
+        )
+    }
+
+    fn group(&self, first: usize) -> Option<(Group, usize)> {
+        let mut node = self.tokens[first].node;
+        while let Some(x) = node {
+            let n = &self.nodes[x];
+            if n.first != Some(first) {
+                break;
+            }
+            let group = match n.kind {
+                NodeKind::AnsiPortDeclaration | NodeKind::PortDeclaration => Some(Group::Port),
+                NodeKind::DataDeclaration | NodeKind::NetDeclaration => Some(Group::Declaration),
+                NodeKind::NamedPortConnection | NodeKind::NamedParameterAssignment => {
+                    Some(Group::Connection)
+                }
+                _ => None,
+            };
+            if let Some(group) = group {
+                return Some((group, x));
+            }
+            node = n.parent;
+        }
+        None
+    }
+
+    fn align_token(&self, group: Group, item: usize, pieces: &[(String, usize)]) -> Option<usize> {
+        for (i, (_, id)) in pieces.iter().enumerate().skip(1) {
+            let ancestors = self.ancestors(*id);
+            if !ancestors.contains(&item) {
+                continue;
+            }
+            let hit = match group {
+                Group::Port | Group::Declaration => ancestors.iter().any(|x| {
+                    let n = &self.nodes[*x];
+                    n.first == Some(*id)
+                        && matches!(
+                            n.kind,
+                            NodeKind::PortIdentifier
+                                | NodeKind::VariableIdentifier
+                                | NodeKind::VariableDeclAssignment
+                                | NodeKind::NetDeclAssignment
+                        )
+                }),
+                Group::Connection => {
+                    self.is_symbol(*id, "(")
+                        && self.tokens[*id].node.and_then(|x| self.nodes[x].parent)
+                            == Some(ancestors[1])
+                        && matches!(
+                            self.nodes[ancestors[1]].kind,
+                            NodeKind::NamedPortConnectionIdentifier
+                                | NodeKind::NamedParameterAssignment
+                        )
+                }
+            };
+            if hit {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    fn align(
+        &self,
+        rendered: &mut [Option<Vec<(String, usize)>>],
+        indent: &[usize],
+        vline: &[usize],
+        joined: &[bool],
+    ) {
+        // Candidates of alignment: (virtual line, group, index of piece)
+        let mut candidates: Vec<Option<(Group, usize)>> = vec![None; rendered.len()];
+        for (line, v) in vline.iter().enumerate() {
+            if joined[line] || self.verbatim[line] {
+                continue;
+            }
+            if let Some(pieces) = &rendered[*v] {
+                let first = pieces[0].1;
+                if self.tokens[first].comment {
+                    continue;
+                }
+                if let Some((group, item)) = self.group(first) {
+                    if let Some(i) = self.align_token(group, item, pieces) {
+                        candidates[*v] = Some((group, i));
+                    }
+                }
+            }
+        }
+
+        let mut v = 0;
+        while v < rendered.len() {
+            let (group, _) = if let Some(x) = candidates[v] {
+                x
+            } else {
+                v += 1;
+                continue;
+            };
+            let mut end = v + 1;
+            while end < rendered.len()
+                && candidates[end].map(|x| x.0) == Some(group)
+                && indent[end] == indent[v]
+            {
+                end += 1;
+            }
+
+            let base = |pieces: &[(String, usize)], i: usize| -> (usize, String) {
+                let text = pieces[i].0.trim_start().to_string();
+                let space = if group == Group::Connection { "" } else { " " };
+                let width: usize = pieces[..i].iter().map(|x| x.0.chars().count()).sum();
+                (width + space.len(), format!("{}{}", space, text))
+            };
+
+            let mut target = 0;
+            for x in v..end {
+                let i = candidates[x].unwrap().1;
+                let (width, _) = base(rendered[x].as_ref().unwrap(), i);
+                target = target.max(width);
+            }
+            for x in v..end {
+                let i = candidates[x].unwrap().1;
+                let pieces = rendered[x].as_mut().unwrap();
+                let (width, text) = base(pieces, i);
+                pieces[i].0 = format!("{}{}", " ".repeat(target - width), text);
+            }
+            v = end;
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::env;
+
+    fn fmt(s: &str, config: &FormatConfig) -> String {
+        let ret = format_str(s, "test.sv", &HashMap::new(), &[] as &[String], config).unwrap();
+        let again = format_str(&ret, "test.sv", &HashMap::new(), &[] as &[String], config).unwrap();
+        assert_eq!(ret, again, "not idempotent");
+        ret
+    }
+
+    #[test]
+    fn indent() {
+        let src = r##"module test(
+input a,
+      input logic [7:0] bb
+  );
+always_ff @(posedge clk) begin
+if (a) begin
+x <= 1;
+end
+else
+    x <= 0;
+  end
+       // comment
+endmodule
+"##;
+        let expected = r##"module test(
+  input             a,
+  input logic [7:0] bb
+);
+  always_ff @(posedge clk) begin
+    if (a) begin
+      x <= 1;
+    end
+    else
+      x <= 0;
+  end
+  // comment
+endmodule
+"##;
+        assert_eq!(fmt(src, &FormatConfig::default()), expected);
+    }
+
+    #[test]
+    fn spacing() {
+        let src = "module test;\nassign a=b ,c= d;\nfoo u_foo ( .a( a ), .bb(bb) );\nendmodule\n";
+        let expected =
+            "module test;\n  assign a = b, c = d;\n  foo u_foo (.a(a), .bb(bb));\nendmodule\n";
+        assert_eq!(fmt(src, &FormatConfig::default()), expected);
+    }
+
+    #[test]
+    fn align_connection() {
+        let src = r##"module test;
+foo u_foo (
+.a(a),
+.long_name(b)
+);
+wire a;
+logic [1:0] b;
+endmodule
+"##;
+        let expected = r##"module test;
+  foo u_foo (
+    .a        (a),
+    .long_name(b)
+  );
+  wire        a;
+  logic [1:0] b;
+endmodule
+"##;
+        assert_eq!(fmt(src, &FormatConfig::default()), expected);
+    }
+
+    #[test]
+    fn begin_same_line() {
+        let src = r##"module test;
+always_comb
+begin
+if (a)
+begin
+x = 1;
+end
+else
+begin
+x = 0;
+end
+end
+endmodule
+"##;
+        let expected = r##"module test;
+  always_comb begin
+    if (a) begin
+      x = 1;
+    end else begin
+      x = 0;
+    end
+  end
+endmodule
+"##;
+        let config = FormatConfig {
+            begin_style: BeginStyle::SameLine,
+            ..Default::default()
+        };
+        assert_eq!(fmt(src, &config), expected);
+    }
+
+    #[test]
+    fn macro_region() {
+        let src = r##"`define ASSIGN(a, b) assign a   =   b;
+module test;
+   `ASSIGN(x,
+       y)
+wire   z;
+`ifdef UNDEFINED
+      this   is   not   parsed
+`endif
+endmodule
+"##;
+        let expected = r##"`define ASSIGN(a, b) assign a   =   b;
+module test;
+   `ASSIGN(x,
+       y)
+  wire z;
+`ifdef UNDEFINED
+      this   is   not   parsed
+`endif
+endmodule
+"##;
+        assert_eq!(fmt(src, &FormatConfig::default()), expected);
+    }
+
+    #[test]
+    fn crlf() {
+        let src = "module m;\r\n  assign a = b;\r\nendmodule\r\n";
+        assert_eq!(fmt(src, &FormatConfig::default()), src);
+
+        let src = "module m;\r\nassign a=b;\r\n/* a\r\n b */\r\nendmodule\r\n";
+        let expected = "module m;\r\n  assign a = b;\r\n/* a\r\n b */\r\nendmodule\r\n";
+        assert_eq!(fmt(src, &FormatConfig::default()), expected);
+    }
+
+    #[test]
+    fn include() {
+        let src = r##"module test;
+`include "included.svh"
+   `ASSIGN(x, y)
+wire   z;
+endmodule
+"##;
+        let expected = r##"module test;
+`include "included.svh"
+   `ASSIGN(x, y)
+  wire z;
+endmodule
+"##;
+        let include_paths = [format!(
+            "{}/testcases",
+            env::var("CARGO_MANIFEST_DIR").unwrap()
+        )];
+        let config = FormatConfig::default();
+        let ret = format_str(src, "test.sv", &HashMap::new(), &include_paths, &config).unwrap();
+        assert_eq!(ret, expected);
+    }
+}
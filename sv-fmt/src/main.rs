@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+use sv_fmt::{format, BeginStyle, FormatConfig};
+use sv_parser::{Define, DefineText};
+
+#[derive(StructOpt)]
+struct Opt {
+    pub files: Vec<PathBuf>,
+
+    /// Include path
+    #[structopt(short = "i", long = "include", multiple = true, number_of_values = 1)]
+    pub includes: Vec<PathBuf>,
+
+    /// Define
+    #[structopt(short = "d", long = "define", multiple = true, number_of_values = 1)]
+    pub defines: Vec<String>,
+
+    /// Number of spaces per indentation level
+    #[structopt(long = "indent", default_value = "2")]
+    pub indent: usize,
+
+    /// Disable alignment
+    #[structopt(long = "no-align")]
+    pub no_align: bool,
+
+    /// Put `begin` at the end of the previous line
+    #[structopt(long = "begin-same-line")]
+    pub begin_same_line: bool,
+
+    /// Check whether files are formatted without modifying them
+    #[structopt(long = "check")]
+    pub check: bool,
+
+    /// Write the result to the source file instead of stdout
+    #[structopt(short = "w", long = "write")]
+    pub write: bool,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    let mut defines = HashMap::new();
+    for define in &opt.defines {
+        let mut define = define.splitn(2, '=');
+        let ident = String::from(define.next().unwrap());
+        let text = if let Some(x) = define.next() {
+            let x = enquote::unescape(x, None).unwrap();
+            Some(DefineText::new(x, None))
+        } else {
+            None
+        };
+        let define = Define::new(ident.clone(), vec![], text);
+        defines.insert(ident, Some(define));
+    }
+
+    let config = FormatConfig {
+        indent_width: opt.indent,
+        align: !opt.no_align,
+        begin_style: if opt.begin_same_line {
+            BeginStyle::SameLine
+        } else {
+            BeginStyle::Keep
+        },
+        ..Default::default()
+    };
+
+    let builder = std::thread::Builder::new().stack_size(20 * 1024 * 1024);
+
+    let child = builder
+        .spawn(move || {
+            let mut exit = 0;
+            for path in &opt.files {
+                let formatted = match format(path, &defines, &opt.includes, &config) {
+                    Ok(x) => x,
+                    Err(x) => {
+                        eprintln!("format failed: {:?} ({})", path, x);
+                        let mut err = x.source();
+                        while let Some(x) = err {
+                            eprintln!("  Caused by {}", x);
+                            err = x.source();
+                        }
+                        exit = 1;
+                        continue;
+                    }
+                };
+
+                if opt.check {
+                    let original = match fs::read_to_string(path) {
+                        Ok(x) => x,
+                        Err(x) => {
+                            eprintln!("read failed: {:?} ({})", path, x);
+                            exit = 1;
+                            continue;
+                        }
+                    };
+                    if original != formatted {
+                        println!("{}", path.to_string_lossy());
+                        exit = 1;
+                    }
+                } else if opt.write {
+                    if let Err(x) = fs::write(path, formatted) {
+                        eprintln!("write failed: {:?} ({})", path, x);
+                        exit = 1;
+                    }
+                } else {
+                    print!("{}", formatted);
+                }
+            }
+            process::exit(exit);
+        })
+        .expect("thread spawn failure");
+
+    let _ = child.join();
+}
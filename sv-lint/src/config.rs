@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Configuration of sv-lint
+///
+/// ```toml
+/// [rules]
+/// casex = "error"
+/// case_default = "off"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// Severity of each rule. Rules not listed use their default severity.
+    #[serde(default)]
+    pub rules: HashMap<String, Severity>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Load configuration from the specified TOML file
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, ConfigError> {
+        let mut f = File::open(path.as_ref())
+            .map_err(|x| ConfigError(format!("failed to open {:?}: {}", path.as_ref(), x)))?;
+        let mut s = String::new();
+        f.read_to_string(&mut s)
+            .map_err(|x| ConfigError(format!("failed to read {:?}: {}", path.as_ref(), x)))?;
+        Config::from_str(&s)
+    }
+
+    /// Get the severity of the specified rule
+    pub fn severity(&self, rule: &str, default: Severity) -> Severity {
+        self.rules.get(rule).copied().unwrap_or(default)
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|x| ConfigError(format!("failed to parse config: {}", x)))
+    }
+}
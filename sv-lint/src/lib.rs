@@ -0,0 +1,223 @@
+pub mod config;
+pub mod report;
+pub mod rule;
+pub mod rules;
+mod waiver;
+
+pub use config::*;
+pub use report::*;
+pub use rule::*;
+
+use std::path::Path;
+use sv_parser::{parse_sv, parse_sv_str, Defines, Error, SyntaxTree};
+use waiver::Waivers;
+
+// -----------------------------------------------------------------------------
+
+/// Apply the rules of `Registry` with severities configured by `Config`
+pub struct Linter {
+    registry: Registry,
+    config: Config,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter::new(Registry::builtin(), Config::default())
+    }
+}
+
+impl Linter {
+    pub fn new(registry: Registry, config: Config) -> Self {
+        Linter { registry, config }
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Parse and check the specified file
+    pub fn lint<T: AsRef<Path>, U: AsRef<Path>>(
+        &self,
+        path: T,
+        pre_defines: &Defines,
+        include_paths: &[U],
+    ) -> Result<(Vec<Diagnostic>, Defines), Error> {
+        let (syntax_tree, defines) = parse_sv(path, pre_defines, include_paths, false, false)?;
+        Ok((self.check(&syntax_tree), defines))
+    }
+
+    /// Parse and check the specified source code
+    pub fn lint_str<T: AsRef<Path>, U: AsRef<Path>>(
+        &self,
+        s: &str,
+        path: T,
+        pre_defines: &Defines,
+        include_paths: &[U],
+    ) -> Result<(Vec<Diagnostic>, Defines), Error> {
+        let (syntax_tree, defines) =
+            parse_sv_str(s, path.as_ref(), pre_defines, include_paths, false, false)?;
+        Ok((self.check(&syntax_tree), defines))
+    }
+
+    /// Check the specified syntax tree
+    pub fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Diagnostic> {
+        let waivers = Waivers::new(syntax_tree);
+
+        let mut ret = Vec::new();
+        for rule in self.registry.rules() {
+            let severity = self.config.severity(rule.name(), rule.default_severity());
+            if severity == Severity::Off {
+                continue;
+            }
+            for failure in rule.check(syntax_tree) {
+                let (path, line, column) =
+                    if let Some(x) = syntax_tree.get_location(&failure.locate) {
+                        (x.path, x.line, x.column)
+                    } else {
+                        continue;
+                    };
+                if waivers.is_waived(rule.name(), &path, line) {
+                    continue;
+                }
+                ret.push(Diagnostic {
+                    rule: String::from(rule.name()),
+                    severity,
+                    message: failure.message,
+                    path,
+                    line,
+                    column,
+                });
+            }
+        }
+        ret.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        ret
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    const SRC: &str = r##"module test;
+always_ff @(posedge clk) begin
+  a = 1; // sv-lint: disable-line blocking_in_always_ff
+  b = 1;
+  // sv-lint: disable-next-line
+  c = 1;
+end
+// sv-lint: disable casex
+always_comb begin
+  casex (s)
+    default: d = 0;
+  endcase
+end
+// sv-lint: enable casex
+always_comb begin
+  casex (s)
+    default: e = 0;
+  endcase
+end
+endmodule
+"##;
+
+    fn lint(config: Config) -> Vec<Diagnostic> {
+        let linter = Linter::new(Registry::builtin(), config);
+        let (ret, _) = linter
+            .lint_str(SRC, "test.sv", &HashMap::new(), &[] as &[String])
+            .unwrap();
+        ret
+    }
+
+    #[test]
+    fn waiver() {
+        let ret = lint(Config::default());
+        let ret: Vec<_> = ret
+            .iter()
+            .map(|x| (x.rule.as_str(), x.line, x.column))
+            .collect();
+        assert_eq!(ret, vec![("blocking_in_always_ff", 4, 3), ("casex", 16, 3)]);
+    }
+
+    #[test]
+    fn config() {
+        let config = Config::from_str(
+            r##"
+            [rules]
+            blocking_in_always_ff = "off"
+            casex = "error"
+            "##,
+        )
+        .unwrap();
+        let ret = lint(config);
+        assert_eq!(ret.len(), 1);
+        assert_eq!(ret[0].rule, "casex");
+        assert_eq!(ret[0].severity, Severity::Error);
+
+        assert!(Config::from_str("[rules]\ncasex = \"fatal\"").is_err());
+    }
+
+    #[test]
+    fn output() {
+        let ret = lint(Config::default());
+        let registry = Registry::builtin();
+
+        let text = report(&ret, &registry, Format::Text);
+        assert_eq!(
+            text,
+            "test.sv:4:3: warning[blocking_in_always_ff]: blocking assignment in always_ff\n\
+             test.sv:16:3: warning[casex]: casex is used\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report(&ret, &registry, Format::Json)).unwrap();
+        assert_eq!(json[1]["rule"], "casex");
+        assert_eq!(json[1]["severity"], "warning");
+        assert_eq!(json[1]["line"], 16);
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&report(&ret, &registry, Format::Sarif)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 7);
+        assert_eq!(run["results"][0]["ruleId"], "blocking_in_always_ff");
+        assert_eq!(
+            run["results"][0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            4
+        );
+    }
+
+    #[test]
+    fn position_after_macro() {
+        // The expansion of `W spans two lines of the preprocessed text
+        let src = r##"`define W wire a; \
+  wire b;
+module test;
+`W
+always_ff @(posedge clk) c = 1;
+endmodule
+"##;
+        let (syntax_tree, _) = parse_sv_str(
+            src,
+            "test.sv",
+            &HashMap::new(),
+            &[] as &[String],
+            false,
+            false,
+        )
+        .unwrap();
+        let ret = Linter::default().check(&syntax_tree);
+        let ret: Vec<_> = ret
+            .iter()
+            .map(|x| (x.rule.as_str(), x.line, x.column))
+            .collect();
+        assert_eq!(ret, vec![("blocking_in_always_ff", 5, 26)]);
+    }
+}
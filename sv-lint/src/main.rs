@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::path::PathBuf;
+use std::process;
+use structopt::StructOpt;
+use sv_lint::{report, Config, Format, Linter, Registry, Severity};
+use sv_parser::{Define, DefineText};
+
+#[derive(StructOpt)]
+struct Opt {
+    pub files: Vec<PathBuf>,
+
+    /// Include path
+    #[structopt(short = "i", long = "include", multiple = true, number_of_values = 1)]
+    pub includes: Vec<PathBuf>,
+
+    /// Define
+    #[structopt(short = "d", long = "define", multiple = true, number_of_values = 1)]
+    pub defines: Vec<String>,
+
+    /// Config file
+    #[structopt(short = "c", long = "config")]
+    pub config: Option<PathBuf>,
+
+    /// Output format: text, json or sarif
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    pub format: Format,
+
+    /// Show rules
+    #[structopt(long = "rules")]
+    pub rules: bool,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    let mut defines = HashMap::new();
+    for define in &opt.defines {
+        let mut define = define.splitn(2, '=');
+        let ident = String::from(define.next().unwrap());
+        let text = if let Some(x) = define.next() {
+            let x = enquote::unescape(x, None).unwrap();
+            Some(DefineText::new(x, None))
+        } else {
+            None
+        };
+        let define = Define::new(ident.clone(), vec![], text);
+        defines.insert(ident, Some(define));
+    }
+
+    let config = if let Some(path) = &opt.config {
+        match Config::load(path) {
+            Ok(x) => x,
+            Err(x) => {
+                eprintln!("{}", x);
+                process::exit(1);
+            }
+        }
+    } else {
+        Config::default()
+    };
+    let linter = Linter::new(Registry::builtin(), config);
+
+    if opt.rules {
+        for rule in linter.registry().rules() {
+            let severity = linter
+                .config()
+                .severity(rule.name(), rule.default_severity());
+            println!("{:<30} {:<8} {}", rule.name(), severity, rule.description());
+        }
+        return;
+    }
+
+    let builder = std::thread::Builder::new().stack_size(20 * 1024 * 1024);
+
+    let child = builder
+        .spawn(move || {
+            let mut exit = 0;
+            let mut diagnostics = Vec::new();
+            for path in &opt.files {
+                match linter.lint(path, &defines, &opt.includes) {
+                    Ok((mut x, new_defines)) => {
+                        diagnostics.append(&mut x);
+                        defines = new_defines;
+                    }
+                    Err(x) => {
+                        eprintln!("parse failed: {:?} ({})", path, x);
+                        let mut err = x.source();
+                        while let Some(x) = err {
+                            eprintln!("  Caused by {}", x);
+                            err = x.source();
+                        }
+                        exit = 1;
+                    }
+                }
+            }
+
+            if diagnostics.iter().any(|x| x.severity == Severity::Error) {
+                exit = 1;
+            }
+            let output = report(&diagnostics, linter.registry(), opt.format);
+            if !output.is_empty() {
+                print!("{}", output);
+                if !output.ends_with('\n') {
+                    println!();
+                }
+            }
+            process::exit(exit);
+        })
+        .expect("thread spawn failure");
+
+    let _ = child.join();
+}
@@ -0,0 +1,129 @@
+use crate::config::Severity;
+use crate::rule::Registry;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+// -----------------------------------------------------------------------------
+
+/// A violation reported by `Linter`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    pub path: PathBuf,
+    /// 1-origin line in the original source
+    pub line: usize,
+    /// 1-origin column in the original source
+    pub column: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            _ => Err(format!("unknown format: {}", s)),
+        }
+    }
+}
+
+/// Render diagnostics in the specified format
+pub fn report(diagnostics: &[Diagnostic], registry: &Registry, format: Format) -> String {
+    match format {
+        Format::Text => report_text(diagnostics),
+        Format::Json => report_json(diagnostics),
+        Format::Sarif => report_sarif(diagnostics, registry),
+    }
+}
+
+/// Render diagnostics as `path:line:column: severity[rule]: message`
+pub fn report_text(diagnostics: &[Diagnostic]) -> String {
+    let mut ret = String::new();
+    for x in diagnostics {
+        ret.push_str(&format!(
+            "{}:{}:{}: {}[{}]: {}\n",
+            x.path.to_string_lossy(),
+            x.line,
+            x.column,
+            x.severity,
+            x.rule,
+            x.message
+        ));
+    }
+    ret
+}
+
+/// Render diagnostics as JSON array
+pub fn report_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string_pretty(diagnostics).unwrap_or_default()
+}
+
+/// Render diagnostics as SARIF 2.1.0 log
+pub fn report_sarif(diagnostics: &[Diagnostic], registry: &Registry) -> String {
+    let rules: Vec<_> = registry
+        .rules()
+        .map(|x| {
+            json!({
+                "id": x.name(),
+                "shortDescription": { "text": x.description() },
+                "defaultConfiguration": { "level": sarif_level(x.default_severity()) },
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|x| {
+            json!({
+                "ruleId": x.rule,
+                "level": sarif_level(x.severity),
+                "message": { "text": x.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": x.path.to_string_lossy() },
+                        "region": { "startLine": x.line, "startColumn": x.column },
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "sv-lint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/dalance/sv-parser",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Off => "none",
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
@@ -0,0 +1,85 @@
+use crate::config::Severity;
+use crate::rules::*;
+use sv_parser::{Locate, SyntaxTree};
+
+// -----------------------------------------------------------------------------
+
+/// A violation found by a `Rule`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Failure {
+    pub locate: Locate,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new<T: Into<String>>(locate: Locate, message: T) -> Self {
+        Failure {
+            locate,
+            message: message.into(),
+        }
+    }
+}
+
+/// A lint rule
+pub trait Rule: Send + Sync {
+    /// Name used in configuration files and waivers
+    fn name(&self) -> &'static str;
+
+    /// Short description of the rule
+    fn description(&self) -> &'static str;
+
+    /// Severity used when the configuration doesn't specify it
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Check the whole syntax tree and return found violations
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure>;
+}
+
+// -----------------------------------------------------------------------------
+
+/// Set of rules applied by `Linter`
+#[derive(Default)]
+pub struct Registry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Create a registry including all built-in rules
+    pub fn builtin() -> Self {
+        let mut ret = Registry::new();
+        ret.register(BlockingInAlwaysFf);
+        ret.register(NonBlockingInAlwaysComb);
+        ret.register(CaseDefault);
+        ret.register(ImplicitNet);
+        ret.register(Casex);
+        ret.register(MultipleDrivers);
+        ret.register(LatchInAlwaysComb);
+        ret
+    }
+
+    /// Add a rule. A rule with the same name is replaced.
+    pub fn register<T: Rule + 'static>(&mut self, rule: T) {
+        self.rules.retain(|x| x.name() != rule.name());
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Get the rule of the specified name
+    pub fn get(&self, name: &str) -> Option<&dyn Rule> {
+        self.rules
+            .iter()
+            .find(|x| x.name() == name)
+            .map(|x| x.as_ref())
+    }
+
+    /// Get all rules in registration order
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|x| x.as_ref())
+    }
+}
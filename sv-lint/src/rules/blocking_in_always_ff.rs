@@ -0,0 +1,39 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::first_locate;
+use sv_parser::{AlwaysKeyword, RefNode, SyntaxTree};
+
+/// Blocking assignment in `always_ff`
+pub struct BlockingInAlwaysFf;
+
+impl Rule for BlockingInAlwaysFf {
+    fn name(&self) -> &'static str {
+        "blocking_in_always_ff"
+    }
+
+    fn description(&self) -> &'static str {
+        "blocking assignment must not be used in always_ff"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            if let RefNode::AlwaysConstruct(x) = node {
+                if let AlwaysKeyword::AlwaysFf(_) = x.nodes.0 {
+                    for node in RefNode::from(&x.nodes.1) {
+                        if let RefNode::BlockingAssignment(x) = node {
+                            if let Some(locate) = first_locate(x.into()) {
+                                ret.push(Failure::new(locate, "blocking assignment in always_ff"));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
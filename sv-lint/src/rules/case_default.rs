@@ -0,0 +1,50 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::first_locate;
+use sv_parser::{CaseInsideItem, CaseItem, RefNode, SyntaxTree};
+
+/// `case` statement without `default`
+pub struct CaseDefault;
+
+impl Rule for CaseDefault {
+    fn name(&self) -> &'static str {
+        "case_default"
+    }
+
+    fn description(&self) -> &'static str {
+        "case statement must have default item"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            let (has_default, keyword) = match node {
+                RefNode::CaseStatementNormal(x) => {
+                    let (_, ref keyword, _, ref first, ref items, _) = x.nodes;
+                    let has_default = Some(first)
+                        .into_iter()
+                        .chain(items.iter())
+                        .any(|x| matches!(x, CaseItem::Default(_)));
+                    (has_default, first_locate(keyword.into()))
+                }
+                RefNode::CaseStatementInside(x) => {
+                    let (_, ref keyword, _, _, ref first, ref items, _) = x.nodes;
+                    let has_default = Some(first)
+                        .into_iter()
+                        .chain(items.iter())
+                        .any(|x| matches!(x, CaseInsideItem::Default(_)));
+                    (has_default, first_locate(keyword.into()))
+                }
+                _ => continue,
+            };
+            if let (false, Some(locate)) = (has_default, keyword) {
+                ret.push(Failure::new(locate, "case statement without default"));
+            }
+        }
+        ret
+    }
+}
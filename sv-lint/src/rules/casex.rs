@@ -0,0 +1,33 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::first_locate;
+use sv_parser::{CaseKeyword, RefNode, SyntaxTree};
+
+/// Usage of `casex`
+pub struct Casex;
+
+impl Rule for Casex {
+    fn name(&self) -> &'static str {
+        "casex"
+    }
+
+    fn description(&self) -> &'static str {
+        "casex must not be used"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            if let RefNode::CaseKeyword(CaseKeyword::Casex(x)) = node {
+                if let Some(locate) = first_locate(x.as_ref().into()) {
+                    ret.push(Failure::new(locate, "casex is used"));
+                }
+            }
+        }
+        ret
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::{first_locate, is_simple_identifier};
+use std::collections::HashSet;
+use sv_parser::{
+    Description, NetLvalue, NodeEvent, PackageImportItem, RefNode, SyntaxTree, VariableLvalue,
+};
+
+/// Net declared implicitly by port connection or continuous assignment
+pub struct ImplicitNet;
+
+impl Rule for ImplicitNet {
+    fn name(&self) -> &'static str {
+        "implicit_net"
+    }
+
+    fn description(&self) -> &'static str {
+        "nets must be declared explicitly"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        // Declarations in compilation unit scope are visible from all modules.
+        let mut unit = Scope::default();
+        for node in syntax_tree {
+            if let RefNode::SourceText(x) = node {
+                for x in &x.nodes.2 {
+                    if let Description::PackageItem(x) = x {
+                        unit.collect(syntax_tree, x.as_ref().into());
+                    }
+                }
+            }
+        }
+        if unit.wildcard_import {
+            return vec![];
+        }
+
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            if let RefNode::ModuleDeclaration(x) = node {
+                let mut scope = Scope::default();
+                scope.collect(syntax_tree, x.into());
+                if scope.wildcard_import {
+                    continue;
+                }
+                for (name, locate) in scope.used {
+                    if !scope.declared.contains(&name) && !unit.declared.contains(&name) {
+                        ret.push(Failure::new(locate, format!("implicit net `{}`", name)));
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
+
+#[derive(Default)]
+struct Scope {
+    declared: HashSet<String>,
+    used: Vec<(String, sv_parser::Locate)>,
+    wildcard_import: bool,
+}
+
+impl Scope {
+    fn collect(&mut self, syntax_tree: &SyntaxTree, node: RefNode) {
+        // Identifiers in port connections, lvalues and expressions are not declarations.
+        let mut usage = 0;
+
+        for event in node.into_iter().event() {
+            let node = match event {
+                NodeEvent::Enter(x) => {
+                    if is_usage(&x) {
+                        usage += 1;
+                    }
+                    x
+                }
+                NodeEvent::Leave(x) => {
+                    if is_usage(&x) {
+                        usage -= 1;
+                    }
+                    continue;
+                }
+            };
+
+            match node {
+                RefNode::PortIdentifier(_)
+                | RefNode::VariableIdentifier(_)
+                | RefNode::NetIdentifier(_)
+                | RefNode::ParameterIdentifier(_)
+                | RefNode::GenvarIdentifier(_)
+                | RefNode::InstanceIdentifier(_)
+                | RefNode::EnumIdentifier(_)
+                | RefNode::SpecparamIdentifier(_)
+                | RefNode::FunctionIdentifier(_)
+                    if usage == 0 =>
+                {
+                    if let Some(x) = syntax_tree.get_str_trim(vec![node]) {
+                        self.declared.insert(String::from(x));
+                    }
+                }
+                RefNode::PackageImportItem(PackageImportItem::Asterisk(_)) => {
+                    self.wildcard_import = true;
+                }
+                RefNode::ContinuousAssign(x) => {
+                    for x in x {
+                        match x {
+                            RefNode::NetLvalue(NetLvalue::Identifier(x)) => {
+                                self.use_expression(syntax_tree, RefNode::from(&x.nodes.0));
+                            }
+                            RefNode::VariableLvalue(VariableLvalue::Identifier(x)) => {
+                                self.use_expression(syntax_tree, RefNode::from(&x.nodes.1));
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                RefNode::OrderedPortConnection(x) => {
+                    if let Some(x) = &x.nodes.1 {
+                        self.use_expression(syntax_tree, x.into());
+                    }
+                }
+                RefNode::NamedPortConnectionIdentifier(x) => {
+                    if let Some(x) = &x.nodes.3 {
+                        if let Some(x) = &x.nodes.1 {
+                            self.use_expression(syntax_tree, x.into());
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn use_expression(&mut self, syntax_tree: &SyntaxTree, node: RefNode) {
+        let name = syntax_tree.get_str_trim(vec![node.clone()]);
+        if let (Some(name), Some(locate)) = (name, first_locate(node)) {
+            if is_simple_identifier(name) && self.used.iter().all(|(x, _)| x != name) {
+                self.used.push((String::from(name), locate));
+            }
+        }
+    }
+}
+
+fn is_usage(node: &RefNode) -> bool {
+    matches!(
+        node,
+        RefNode::NamedPortConnection(_)
+            | RefNode::NetLvalue(_)
+            | RefNode::VariableLvalue(_)
+            | RefNode::Expression(_)
+            | RefNode::ConstantExpression(_)
+    )
+}
@@ -0,0 +1,159 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::assigned_variables;
+use std::collections::HashSet;
+use sv_parser::{
+    AlwaysKeyword, CaseInsideItem, CaseItem, CaseStatement, RefNode, Statement, StatementItem,
+    StatementOrNull, SyntaxTree, UniquePriority,
+};
+
+/// Latch inferred in `always_comb`
+pub struct LatchInAlwaysComb;
+
+impl Rule for LatchInAlwaysComb {
+    fn name(&self) -> &'static str {
+        "latch_in_always_comb"
+    }
+
+    fn description(&self) -> &'static str {
+        "variables assigned in always_comb must be assigned in all paths"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            if let RefNode::AlwaysConstruct(x) = node {
+                if let AlwaysKeyword::AlwaysComb(_) = x.nodes.0 {
+                    let statement = &x.nodes.1;
+                    let assigned = definitely_assigned(syntax_tree, statement);
+                    for (name, locate) in assigned_variables(syntax_tree, statement.into()) {
+                        if !assigned.contains(&name) {
+                            ret.push(Failure::new(
+                                locate,
+                                format!("latch is inferred for `{}` in always_comb", name),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Get variables assigned in all paths of the specified statement
+///
+/// Variables assigned in statements which are not analyzed, such as loops and
+/// subroutine calls, are treated as assigned because it is unknown.
+fn definitely_assigned(syntax_tree: &SyntaxTree, statement: &Statement) -> HashSet<String> {
+    let names = |x: RefNode| {
+        assigned_variables(syntax_tree, x)
+            .into_iter()
+            .map(|(x, _)| x)
+            .collect()
+    };
+    let intersection = |statements: Vec<&StatementOrNull>| {
+        let mut ret: Option<HashSet<String>> = None;
+        for x in statements {
+            let x = definitely_assigned_or_null(syntax_tree, x);
+            ret = Some(match ret {
+                Some(y) => y.intersection(&x).cloned().collect(),
+                None => x,
+            });
+        }
+        ret.unwrap_or_default()
+    };
+
+    match &statement.nodes.2 {
+        StatementItem::BlockingAssignment(x) => names((&x.0).into()),
+        StatementItem::NonblockingAssignment(x) => names((&x.0).into()),
+        StatementItem::SeqBlock(x) => {
+            let mut ret = HashSet::new();
+            for x in &x.nodes.3 {
+                ret.extend(definitely_assigned_or_null(syntax_tree, x));
+            }
+            ret
+        }
+        StatementItem::ProceduralTimingControlStatement(x) => {
+            definitely_assigned_or_null(syntax_tree, &x.nodes.1)
+        }
+        StatementItem::ConditionalStatement(x) => {
+            let (ref unique_priority, _, _, ref first, ref else_ifs, ref else_) = x.nodes;
+            let mut statements = vec![first];
+            statements.extend(else_ifs.iter().map(|x| &x.3));
+            if let Some((_, else_)) = else_ {
+                statements.push(else_);
+                intersection(statements)
+            } else if full(unique_priority) {
+                intersection(statements)
+            } else {
+                HashSet::new()
+            }
+        }
+        StatementItem::CaseStatement(x) => match x.as_ref() {
+            CaseStatement::Normal(x) => {
+                let (ref unique_priority, _, _, ref first, ref items, _) = x.nodes;
+                let items: Vec<_> = Some(first).into_iter().chain(items.iter()).collect();
+                if full(unique_priority) || items.iter().any(|x| matches!(x, CaseItem::Default(_)))
+                {
+                    intersection(
+                        items
+                            .into_iter()
+                            .map(|x| match x {
+                                CaseItem::NonDefault(x) => &x.nodes.2,
+                                CaseItem::Default(x) => &x.nodes.2,
+                            })
+                            .collect(),
+                    )
+                } else {
+                    HashSet::new()
+                }
+            }
+            CaseStatement::Inside(x) => {
+                let (ref unique_priority, _, _, _, ref first, ref items, _) = x.nodes;
+                let items: Vec<_> = Some(first).into_iter().chain(items.iter()).collect();
+                if full(unique_priority)
+                    || items
+                        .iter()
+                        .any(|x| matches!(x, CaseInsideItem::Default(_)))
+                {
+                    intersection(
+                        items
+                            .into_iter()
+                            .map(|x| match x {
+                                CaseInsideItem::NonDefault(x) => &x.nodes.2,
+                                CaseInsideItem::Default(x) => &x.nodes.2,
+                            })
+                            .collect(),
+                    )
+                } else {
+                    HashSet::new()
+                }
+            }
+            CaseStatement::Matches(_) => names(statement.into()),
+        },
+        _ => names(statement.into()),
+    }
+}
+
+// `unique` and `priority` assert that one of the branches is always taken
+fn full(unique_priority: &Option<UniquePriority>) -> bool {
+    matches!(
+        unique_priority,
+        Some(UniquePriority::Unique(_)) | Some(UniquePriority::Priority(_))
+    )
+}
+
+fn definitely_assigned_or_null(
+    syntax_tree: &SyntaxTree,
+    statement: &StatementOrNull,
+) -> HashSet<String> {
+    match statement {
+        StatementOrNull::Statement(x) => definitely_assigned(syntax_tree, x),
+        StatementOrNull::Attribute(_) => HashSet::new(),
+    }
+}
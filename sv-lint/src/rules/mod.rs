@@ -0,0 +1,399 @@
+pub mod blocking_in_always_ff;
+pub mod case_default;
+pub mod casex;
+pub mod implicit_net;
+pub mod latch_in_always_comb;
+pub mod multiple_drivers;
+pub mod non_blocking_in_always_comb;
+
+pub use blocking_in_always_ff::*;
+pub use case_default::*;
+pub use casex::*;
+pub use implicit_net::*;
+pub use latch_in_always_comb::*;
+pub use multiple_drivers::*;
+pub use non_blocking_in_always_comb::*;
+
+use std::collections::HashSet;
+use sv_parser::{unwrap_locate, Locate, NodeEvent, RefNode, SyntaxTree};
+
+// -----------------------------------------------------------------------------
+
+/// Get the first `Locate` of the specified node
+pub(crate) fn first_locate(node: RefNode) -> Option<Locate> {
+    unwrap_locate!(node).copied()
+}
+
+/// Get variables assigned in the specified node
+///
+/// Loop variables and variables declared in the node are excluded.
+/// Each variable is returned once with the position of its first assignment.
+pub(crate) fn assigned_variables(syntax_tree: &SyntaxTree, node: RefNode) -> Vec<(String, Locate)> {
+    let mut ret: Vec<(String, Locate)> = Vec::new();
+    for (name, _, locate) in assigned_selects(syntax_tree, node) {
+        if ret.iter().all(|(x, _)| *x != name) {
+            ret.push((name, locate));
+        }
+    }
+    ret
+}
+
+/// Get variables assigned in the specified node with their selects
+///
+/// The select is the text following the variable name such as `[3:0]` or
+/// `.member`, and empty if the whole variable is assigned.
+/// Each pair of a variable and a select is returned once with the position of
+/// its first assignment.
+pub(crate) fn assigned_selects(
+    syntax_tree: &SyntaxTree,
+    node: RefNode,
+) -> Vec<(String, String, Locate)> {
+    let mut locals = HashSet::new();
+    let mut ret: Vec<(String, String, Locate)> = Vec::new();
+    let mut skip = 0;
+    let mut select = None;
+
+    for event in node.into_iter().event() {
+        match event {
+            NodeEvent::Enter(RefNode::ForInitialization(_))
+            | NodeEvent::Enter(RefNode::ForStep(_)) => skip += 1,
+            NodeEvent::Leave(RefNode::ForInitialization(_))
+            | NodeEvent::Leave(RefNode::ForStep(_)) => skip -= 1,
+            NodeEvent::Enter(RefNode::BlockItemDeclaration(x)) => {
+                for x in x {
+                    if let RefNode::VariableIdentifier(x) = x {
+                        if let Some(x) = syntax_tree.get_str_trim(x) {
+                            locals.insert(String::from(x));
+                        }
+                    }
+                }
+            }
+            NodeEvent::Enter(RefNode::VariableLvalueIdentifier(x)) => {
+                select = syntax_tree.get_str_trim(&x.nodes.2);
+            }
+            NodeEvent::Enter(RefNode::NonrangeVariableLvalue(x)) => {
+                select = syntax_tree.get_str_trim(&x.nodes.2);
+            }
+            NodeEvent::Enter(RefNode::BlockingAssignmentHierarchicalVariable(x)) => {
+                select = syntax_tree.get_str_trim(&x.nodes.2);
+            }
+            NodeEvent::Enter(RefNode::NetLvalueIdentifier(x)) => {
+                select = syntax_tree.get_str_trim(&x.nodes.1);
+            }
+            NodeEvent::Enter(RefNode::HierarchicalVariableIdentifier(x)) if skip == 0 => {
+                push_name(syntax_tree, x.into(), select.take(), &mut ret);
+            }
+            NodeEvent::Enter(RefNode::PsOrHierarchicalNetIdentifier(x)) if skip == 0 => {
+                push_name(syntax_tree, x.into(), select.take(), &mut ret);
+            }
+            _ => (),
+        }
+    }
+
+    ret.retain(|(x, _, _)| !locals.contains(x));
+    ret
+}
+
+fn push_name(
+    syntax_tree: &SyntaxTree,
+    node: RefNode,
+    select: Option<&str>,
+    names: &mut Vec<(String, String, Locate)>,
+) {
+    let name = syntax_tree.get_str_trim(vec![node.clone()]);
+    let select: String = select
+        .unwrap_or("")
+        .chars()
+        .filter(|x| !x.is_whitespace())
+        .collect();
+    if let (Some(name), Some(locate)) = (name, first_locate(node)) {
+        if names.iter().all(|(x, y, _)| x != name || *y != select) {
+            names.push((String::from(name), select, locate));
+        }
+    }
+}
+
+/// Check whether the specified string is a simple identifier
+pub(crate) fn is_simple_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::rule::{Registry, Rule};
+    use std::collections::HashMap;
+    use sv_parser::parse_sv_str;
+
+    fn check(rule: &str, src: &str) -> Vec<String> {
+        let (syntax_tree, _) =
+            parse_sv_str(src, "test.sv", &HashMap::new(), &[""], false, false).unwrap();
+        let registry = Registry::builtin();
+        let rule: &dyn Rule = registry.get(rule).unwrap();
+        rule.check(&syntax_tree)
+            .into_iter()
+            .map(|x| format!("{}:{}", x.locate.line, x.message))
+            .collect()
+    }
+
+    #[test]
+    fn blocking_in_always_ff() {
+        let src = r##"module test;
+always_ff @(posedge clk) begin
+  a <= 1;
+  b = 1;
+  for (int i = 0; i < 4; i++) c[i] <= 0;
+end
+always_comb x = 1;
+endmodule
+"##;
+        assert_eq!(
+            check("blocking_in_always_ff", src),
+            vec!["4:blocking assignment in always_ff"]
+        );
+    }
+
+    #[test]
+    fn non_blocking_in_always_comb() {
+        let src = r##"module test;
+always_comb begin
+  a = 1;
+  b <= 1;
+end
+always_ff @(posedge clk) c <= 1;
+endmodule
+"##;
+        assert_eq!(
+            check("non_blocking_in_always_comb", src),
+            vec!["4:non-blocking assignment in always_comb"]
+        );
+    }
+
+    #[test]
+    fn case_default() {
+        let src = r##"module test;
+always_comb begin
+  case (a)
+    0: x = 0;
+    default: x = 1;
+  endcase
+  case (a)
+    0: y = 0;
+  endcase
+  case (a) inside
+    [0:1]: z = 0;
+  endcase
+end
+endmodule
+"##;
+        assert_eq!(
+            check("case_default", src),
+            vec![
+                "7:case statement without default",
+                "10:case statement without default"
+            ]
+        );
+    }
+
+    #[test]
+    fn casex() {
+        let src = r##"module test;
+always_comb begin
+  casez (a)
+    default: x = 0;
+  endcase
+  casex (a)
+    default: x = 1;
+  endcase
+end
+endmodule
+"##;
+        assert_eq!(check("casex", src), vec!["6:casex is used"]);
+    }
+
+    #[test]
+    fn implicit_net() {
+        let src = r##"parameter P = 1;
+module test(input a, output b);
+wire c;
+assign b = a;
+assign d = c;
+sub u_sub (.x(c), .y(e), .z(P), .w(a & c));
+sub u_sub2 (c, f);
+endmodule
+"##;
+        assert_eq!(
+            check("implicit_net", src),
+            vec![
+                "5:implicit net `d`",
+                "6:implicit net `e`",
+                "7:implicit net `f`"
+            ]
+        );
+    }
+
+    #[test]
+    fn multiple_drivers() {
+        let src = r##"module test;
+logic a, b, c, d, e;
+always_ff @(posedge clk) a <= 1;
+always_comb begin
+  a = 0;
+  b = 0;
+  b = 1;
+end
+assign c = 1;
+assign c = 0;
+always_comb begin
+  for (int i = 0; i < 4; i++) d[i] = 0;
+end
+always_comb begin
+  for (int i = 0; i < 4; i++) d[i] = 0;
+end
+if (P) begin
+  always_comb e = 1;
+end else begin
+  always_comb e = 0;
+end
+endmodule
+"##;
+        assert_eq!(
+            check("multiple_drivers", src),
+            vec![
+                "5:`a` is driven by multiple processes",
+                "10:`c` is driven by multiple processes",
+                "15:`d` is driven by multiple processes",
+            ]
+        );
+
+        let src = r##"module test;
+logic x, y, z;
+if (P) begin
+  assign x = 0;
+  assign x = 1;
+  assign y = 0;
+end else if (Q) begin
+  assign y = 1;
+end else begin
+  case (R)
+    0: assign z = 0;
+    default: assign z = 1;
+  endcase
+end
+assign y = 2;
+endmodule
+interface test_if;
+logic a;
+assign a = 0;
+assign a = 1;
+endinterface
+"##;
+        assert_eq!(
+            check("multiple_drivers", src),
+            vec![
+                "5:`x` is driven by multiple processes",
+                "15:`y` is driven by multiple processes",
+                "20:`a` is driven by multiple processes",
+            ]
+        );
+
+        let src = r##"module test;
+logic [7:0] c, d, e;
+logic [1:0][3:0] f;
+assign c[0] = 1;
+assign c[1] = 0;
+assign c[7:4] = 0;
+assign c[5] = 1;
+assign d[3:0] = 0;
+assign d[4 +: 4] = 0;
+assign d[i] = 1;
+assign e = 0;
+assign e[0] = 1;
+assign f[0][1] = 0;
+assign f[1] = 0;
+assign f[0] = 1;
+always_comb s.x = 0;
+always_comb s.y = 0;
+endmodule
+"##;
+        assert_eq!(
+            check("multiple_drivers", src),
+            vec![
+                "7:`c` is driven by multiple processes",
+                "10:`d` is driven by multiple processes",
+                "12:`e` is driven by multiple processes",
+                "15:`f` is driven by multiple processes",
+            ]
+        );
+
+        // Drivers in the same process are not compared
+        let src = r##"module test;
+logic [3:0] d;
+always_comb begin d = '0; d[s] = 1'b1; end
+endmodule
+"##;
+        assert!(check("multiple_drivers", src).is_empty());
+    }
+
+    #[test]
+    fn latch_in_always_comb() {
+        let src = r##"module test;
+always_comb begin
+  if (en) a = 1;
+  if (en) b = 1;
+  else b = 0;
+  c = 0;
+  if (en) c = 1;
+  case (s)
+    0: d = 0;
+    default: d = 1;
+  endcase
+  case (s)
+    0: e = 0;
+  endcase
+  if (en) begin
+    logic t;
+    t = 1;
+    f = t;
+  end else if (x) begin
+    f = 0;
+  end
+end
+endmodule
+"##;
+        assert_eq!(
+            check("latch_in_always_comb", src),
+            vec![
+                "3:latch is inferred for `a` in always_comb",
+                "13:latch is inferred for `e` in always_comb",
+                "18:latch is inferred for `f` in always_comb",
+            ]
+        );
+
+        // Loops, subroutine calls and unique/priority case are not reported
+        let src = r##"module test;
+always_comb for (int k = 0; k < 4; k++) q[k] = i[k];
+always_comb begin
+  unique case (s)
+    0: a = 0;
+    1: a = 1;
+  endcase
+  priority case (s) inside
+    [0:1]: b = 0;
+  endcase
+  unique if (s) c = 0;
+  else if (t) c = 1;
+  while (x) d = 0;
+  task_call(e);
+end
+endmodule
+"##;
+        assert!(check("latch_in_always_comb", src).is_empty());
+    }
+}
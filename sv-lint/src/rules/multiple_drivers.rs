@@ -0,0 +1,243 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::assigned_selects;
+use sv_parser::{CaseGenerateItem, Locate, NodeEvent, RefNode, SyntaxTree};
+
+/// Variable driven by multiple `always` blocks or continuous assignments
+pub struct MultipleDrivers;
+
+impl Rule for MultipleDrivers {
+    fn name(&self) -> &'static str {
+        "multiple_drivers"
+    }
+
+    fn description(&self) -> &'static str {
+        "variables must be driven by a single process"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            match node {
+                RefNode::ModuleDeclaration(_)
+                | RefNode::InterfaceDeclaration(_)
+                | RefNode::ProgramDeclaration(_) => {
+                    ret.append(&mut check_design_element(syntax_tree, node));
+                }
+                _ => (),
+            }
+        }
+        ret
+    }
+}
+
+// Branch taken in each enclosing conditional generate construct, identified by its offset
+type BranchPath = Vec<(usize, Option<usize>)>;
+
+// Process id, select, position and branch path of each driver of a variable
+type Drivers = Vec<(usize, String, Locate, BranchPath)>;
+
+fn check_design_element(syntax_tree: &SyntaxTree, node: RefNode) -> Vec<Failure> {
+    let mut drivers: Vec<(String, Drivers)> = Vec::new();
+    // Enclosing conditional generate constructs and the ranges of their branches
+    let mut constructs: Vec<(usize, Vec<(usize, usize)>)> = Vec::new();
+
+    // Each always construct, net assignment and continuous assignment is a process
+    let mut process = 0;
+
+    let mut add = |constructs: &[(usize, Vec<(usize, usize)>)],
+                   process: usize,
+                   name: String,
+                   select: String,
+                   locate: Locate| {
+        let path = constructs
+            .iter()
+            .map(|(x, branches)| {
+                let branch = branches
+                    .iter()
+                    .position(|(begin, end)| (*begin..*end).contains(&locate.offset));
+                (*x, branch)
+            })
+            .collect();
+        if let Some((_, x)) = drivers.iter_mut().find(|(x, _)| *x == name) {
+            x.push((process, select, locate, path));
+        } else {
+            drivers.push((name, vec![(process, select, locate, path)]));
+        }
+    };
+
+    for event in node.into_iter().event() {
+        match event {
+            NodeEvent::Enter(RefNode::IfGenerateConstruct(x)) => {
+                let mut branches = vec![range(RefNode::from(&x.nodes.2))];
+                if let Some((_, ref x)) = x.nodes.3 {
+                    branches.push(range(x.into()));
+                }
+                constructs.push((range(x.into()).0, branches));
+            }
+            NodeEvent::Enter(RefNode::CaseGenerateConstruct(x)) => {
+                let branches = x
+                    .nodes
+                    .2
+                    .iter()
+                    .map(|x| match x {
+                        CaseGenerateItem::Nondefault(x) => range(RefNode::from(&x.nodes.2)),
+                        CaseGenerateItem::Default(x) => range(RefNode::from(&x.nodes.2)),
+                    })
+                    .collect();
+                constructs.push((range(x.into()).0, branches));
+            }
+            NodeEvent::Leave(RefNode::IfGenerateConstruct(_))
+            | NodeEvent::Leave(RefNode::CaseGenerateConstruct(_)) => {
+                constructs.pop();
+            }
+            NodeEvent::Enter(RefNode::AlwaysConstruct(x)) => {
+                process += 1;
+                for (name, select, locate) in assigned_selects(syntax_tree, x.into()) {
+                    add(&constructs, process, name, select, locate);
+                }
+            }
+            NodeEvent::Enter(RefNode::NetAssignment(x)) => {
+                process += 1;
+                let lvalue = RefNode::from(&x.nodes.0);
+                for (name, select, locate) in assigned_selects(syntax_tree, lvalue) {
+                    add(&constructs, process, name, select, locate);
+                }
+            }
+            NodeEvent::Enter(RefNode::ContinuousAssign(x)) => {
+                for node in x {
+                    if let RefNode::VariableAssignment(x) = node {
+                        process += 1;
+                        let lvalue = RefNode::from(&x.nodes.0);
+                        for (name, select, locate) in assigned_selects(syntax_tree, lvalue) {
+                            add(&constructs, process, name, select, locate);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut ret = Vec::new();
+    for (name, locates) in drivers {
+        for (i, (process, select, locate, path)) in locates.iter().enumerate() {
+            if locates[..i]
+                .iter()
+                .any(|(p, x, _, y)| p != process && overlap(x, select) && !exclusive(y, path))
+            {
+                ret.push(Failure::new(
+                    *locate,
+                    format!("`{}` is driven by multiple processes", name),
+                ));
+            }
+        }
+    }
+    ret.sort_by_key(|x| x.locate.offset);
+    ret
+}
+
+// Processes in different branches of the same conditional generate construct are exclusive
+fn exclusive(a: &BranchPath, b: &BranchPath) -> bool {
+    a.iter().any(|(x, a)| {
+        b.iter()
+            .any(|(y, b)| x == y && a.is_some() && b.is_some() && a != b)
+    })
+}
+
+// Selects overlap unless some dimension or member of them is known to be disjoint
+fn overlap(a: &str, b: &str) -> bool {
+    let a = select_items(a);
+    let b = select_items(b);
+    !a.iter().zip(b.iter()).any(|(a, b)| match (a, b) {
+        (SelectItem::Member(a), SelectItem::Member(b)) => a != b,
+        (SelectItem::Index(Some(a)), SelectItem::Index(Some(b))) => a.1 < b.0 || b.1 < a.0,
+        _ => false,
+    })
+}
+
+enum SelectItem<'a> {
+    Member(&'a str),
+    // Range of indexes if it is constant
+    Index(Option<(i64, i64)>),
+}
+
+// Split a select such as `.x[1][3:0]` into members and indexes
+fn select_items(s: &str) -> Vec<SelectItem<'_>> {
+    let mut ret = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        if let Some(x) = rest.strip_prefix('[') {
+            let mut depth = 1;
+            let end = x.find(|c| {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => (),
+                }
+                depth == 0
+            });
+            let end = end.unwrap_or(x.len());
+            ret.push(SelectItem::Index(index_range(&x[..end])));
+            rest = x.get(end + 1..).unwrap_or("");
+        } else {
+            let x = rest.trim_start_matches('.');
+            let end = x.find(['.', '[']).unwrap_or(x.len());
+            ret.push(SelectItem::Member(&x[..end]));
+            rest = &x[end..];
+        }
+    }
+    ret
+}
+
+// Range of indexes selected by `[i]`, `[m:l]`, `[b+:w]` or `[b-:w]`
+fn index_range(s: &str) -> Option<(i64, i64)> {
+    if let Some((base, width)) = s.split_once("+:") {
+        let (base, width) = (number(base)?, number(width)?);
+        Some((base, base + width - 1))
+    } else if let Some((base, width)) = s.split_once("-:") {
+        let (base, width) = (number(base)?, number(width)?);
+        Some((base - width + 1, base))
+    } else if let Some((msb, lsb)) = s.split_once(':') {
+        let (msb, lsb) = (number(msb)?, number(lsb)?);
+        Some((msb.min(lsb), msb.max(lsb)))
+    } else {
+        let x = number(s)?;
+        Some((x, x))
+    }
+}
+
+// Value of a decimal or based integer literal
+fn number(s: &str) -> Option<i64> {
+    let s = s.replace('_', "");
+    if let Some((_, x)) = s.split_once('\'') {
+        let x = x.trim_start_matches(['s', 'S']);
+        let radix = match x.chars().next()? {
+            'b' | 'B' => 2,
+            'o' | 'O' => 8,
+            'd' | 'D' => 10,
+            'h' | 'H' => 16,
+            _ => return None,
+        };
+        i64::from_str_radix(&x[1..], radix).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+// Byte range of the specified node
+fn range(node: RefNode) -> (usize, usize) {
+    let mut begin = usize::MAX;
+    let mut end = 0;
+    for x in node {
+        if let RefNode::Locate(x) = x {
+            begin = begin.min(x.offset);
+            end = end.max(x.offset + x.len);
+        }
+    }
+    (begin, end)
+}
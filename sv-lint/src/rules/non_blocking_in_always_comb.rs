@@ -0,0 +1,42 @@
+use crate::config::Severity;
+use crate::rule::{Failure, Rule};
+use crate::rules::first_locate;
+use sv_parser::{AlwaysKeyword, RefNode, SyntaxTree};
+
+/// Non-blocking assignment in `always_comb`
+pub struct NonBlockingInAlwaysComb;
+
+impl Rule for NonBlockingInAlwaysComb {
+    fn name(&self) -> &'static str {
+        "non_blocking_in_always_comb"
+    }
+
+    fn description(&self) -> &'static str {
+        "non-blocking assignment must not be used in always_comb"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, syntax_tree: &SyntaxTree) -> Vec<Failure> {
+        let mut ret = Vec::new();
+        for node in syntax_tree {
+            if let RefNode::AlwaysConstruct(x) = node {
+                if let AlwaysKeyword::AlwaysComb(_) = x.nodes.0 {
+                    for node in RefNode::from(&x.nodes.1) {
+                        if let RefNode::NonblockingAssignment(x) = node {
+                            if let Some(locate) = first_locate(x.into()) {
+                                ret.push(Failure::new(
+                                    locate,
+                                    "non-blocking assignment in always_comb",
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
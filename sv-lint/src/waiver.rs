@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use sv_parser::{RefNode, SyntaxTree};
+
+// -----------------------------------------------------------------------------
+
+/// Rules waived by comments
+///
+/// The following comments are recognized:
+///
+/// ```systemverilog
+/// a = b; // sv-lint: disable-line rule_a, rule_b
+/// // sv-lint: disable-next-line rule_a
+/// // sv-lint: disable rule_a
+/// // sv-lint: enable rule_a
+/// ```
+///
+/// If no rule is specified, all rules are waived.
+#[derive(Debug, Default)]
+pub(crate) struct Waivers {
+    waivers: HashMap<PathBuf, Vec<Waiver>>,
+}
+
+#[derive(Debug)]
+struct Waiver {
+    begin: usize,
+    end: usize,
+    rules: Option<Vec<String>>,
+}
+
+impl Waiver {
+    fn matches(&self, rule: &str) -> bool {
+        self.rules
+            .as_ref()
+            .map(|x| x.iter().any(|x| x == rule))
+            .unwrap_or(true)
+    }
+}
+
+impl Waivers {
+    pub(crate) fn new(syntax_tree: &SyntaxTree) -> Self {
+        let mut ret = Waivers::default();
+        // Opened `disable` regions: (path, line, rules)
+        let mut regions: Vec<(PathBuf, usize, Option<Vec<String>>)> = Vec::new();
+
+        for node in syntax_tree {
+            let locate = if let RefNode::Comment(x) = node {
+                x.nodes.0
+            } else {
+                continue;
+            };
+            let text = syntax_tree.get_str(&locate).unwrap_or("");
+            let (command, rules) = if let Some(x) = parse_comment(text) {
+                x
+            } else {
+                continue;
+            };
+            let (path, line) = if let Some(x) = syntax_tree.get_location(&locate) {
+                (x.path, x.line)
+            } else {
+                continue;
+            };
+
+            match command {
+                "disable-line" => ret.push(&path, line, line, rules),
+                "disable-next-line" => ret.push(&path, line + 1, line + 1, rules),
+                "disable" => regions.push((path, line, rules)),
+                "enable" => {
+                    let mut i = 0;
+                    while i < regions.len() {
+                        let (ref p, begin, ref r) = regions[i];
+                        let closed = *p == path && (rules.is_none() || *r == rules);
+                        if closed {
+                            let (_, _, r) = regions.remove(i);
+                            ret.push(&path, begin, line, r);
+                        } else {
+                            i += 1;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for (path, begin, rules) in regions {
+            ret.push(&path, begin, usize::MAX, rules);
+        }
+        ret
+    }
+
+    fn push(&mut self, path: &Path, begin: usize, end: usize, rules: Option<Vec<String>>) {
+        self.waivers
+            .entry(PathBuf::from(path))
+            .or_default()
+            .push(Waiver { begin, end, rules });
+    }
+
+    /// Check whether the specified rule is waived at the specified line
+    pub(crate) fn is_waived(&self, rule: &str, path: &Path, line: usize) -> bool {
+        if let Some(waivers) = self.waivers.get(path) {
+            waivers
+                .iter()
+                .any(|x| x.begin <= line && line <= x.end && x.matches(rule))
+        } else {
+            false
+        }
+    }
+}
+
+fn parse_comment(text: &str) -> Option<(&str, Option<Vec<String>>)> {
+    let text = if let Some(x) = text.strip_prefix("//") {
+        x
+    } else if let Some(x) = text.strip_prefix("/*") {
+        x.trim_end().trim_end_matches("*/")
+    } else {
+        return None;
+    };
+    let text = text.trim().strip_prefix("sv-lint:")?.trim();
+
+    let mut words = text.splitn(2, char::is_whitespace);
+    let command = words.next()?;
+    let rules: Vec<String> = words
+        .next()
+        .unwrap_or("")
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect();
+    let rules = if rules.is_empty() { None } else { Some(rules) };
+    Some((command, rules))
+}
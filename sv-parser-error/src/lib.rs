@@ -1,8 +1,39 @@
+use std::fmt;
 use std::path::PathBuf;
 use thiserror::Error;
 
 // -----------------------------------------------------------------------------
 
+/// Position in a source file
+///
+/// `line` and `column` are 1-based. `offset` and `column` are counted in bytes
+/// of the original file even if it is decoded from another encoding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// `include directive of `Error::Include`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IncludeSite {
+    /// Filename given by the directive
+    pub path: PathBuf,
+    pub location: Location,
+    /// Paths searched for the file in order
+    pub tried: Vec<PathBuf>,
+}
+
+// -----------------------------------------------------------------------------
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("IO error: {0}")]
@@ -18,30 +49,110 @@ pub enum Error {
     #[error("File could not be read as UTF8: {0:?}")]
     ReadUtf8(PathBuf),
 
-    #[error("Include error")]
+    /// Error in an included file
+    #[error("Include error: {:?} at {}", .site.path, .site.location)]
     Include {
-        #[from]
+        #[source]
         source: Box<Error>,
+        site: Box<IncludeSite>,
+    },
+
+    /// Error in a macro expansion
+    ///
+    /// Errors in the expanded text are located at the outermost call site in
+    /// a file.
+    #[error("Macro error: `{identifier} at {location}")]
+    Macro {
+        #[source]
+        source: Box<Error>,
+        identifier: String,
+        location: Box<Location>,
     },
 
     #[error("Parse error: {0:?}")]
-    Parse(Option<(PathBuf, usize)>),
+    Parse(Option<Box<Location>>),
 
     #[error("Preprocess error: {0:?}")]
-    Preprocess(Option<(PathBuf, usize)>),
+    Preprocess(Option<Box<Location>>),
 
-    #[error("Define argument not found: {0}")]
-    DefineArgNotFound(String),
+    #[error("Define argument not found: {identifier} at {location}")]
+    DefineArgNotFound {
+        identifier: String,
+        location: Box<Location>,
+    },
 
-    #[error("Define not found: {0}")]
-    DefineNotFound(String),
+    #[error("Define not found: {identifier} at {location}")]
+    DefineNotFound {
+        identifier: String,
+        location: Box<Location>,
+    },
+
+    #[error("Define must have argument: {identifier} at {location}")]
+    DefineNoArgs {
+        identifier: String,
+        location: Box<Location>,
+    },
+
+    #[error("Exceed recursive limit at {location}")]
+    ExceedRecursiveLimit { location: Box<Location> },
+
+    #[error("Include line can't have other items at {location}")]
+    IncludeLine { location: Box<Location> },
+
+    #[error("Exceed input size limit: {0:?}")]
+    ExceedInputSize(PathBuf),
+
+    #[error("Exceed output size limit")]
+    ExceedOutputSize,
 
-    #[error("Define must have argument")]
-    DefineNoArgs(String), // String is the macro identifier.
+    #[error("Exceed include depth limit at {location}")]
+    ExceedIncludeDepth { location: Box<Location> },
+
+    #[error("Exceed token count limit")]
+    ExceedTokenCount,
+
+    #[error("Exceed deadline")]
+    ExceedDeadline,
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// SystemVerilog-only construct in source code parsed as Verilog
+    #[error("SystemVerilog construct is not allowed in Verilog: {construct} at {location:?}")]
+    SystemVerilogConstruct {
+        construct: String,
+        location: Option<Box<Location>>,
+    },
+}
 
-    #[error("Exceed recursive limit")]
-    ExceedRecursiveLimit,
+impl Error {
+    /// Get the location of this error
+    ///
+    /// The location of the root cause can be got from the innermost error of
+    /// `Include` and `Macro`.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Include { site, .. } => Some(&site.location),
+            Error::Macro { location, .. } => Some(location),
+            Error::Parse(x) => x.as_deref(),
+            Error::Preprocess(x) => x.as_deref(),
+            Error::DefineArgNotFound { location, .. } => Some(location),
+            Error::DefineNotFound { location, .. } => Some(location),
+            Error::DefineNoArgs { location, .. } => Some(location),
+            Error::ExceedRecursiveLimit { location } => Some(location),
+            Error::IncludeLine { location } => Some(location),
+            Error::ExceedIncludeDepth { location } => Some(location),
+            Error::SystemVerilogConstruct { location, .. } => location.as_deref(),
+            _ => None,
+        }
+    }
 
-    #[error("Include line can't have other items")]
-    IncludeLine,
+    /// Get the innermost error of `Include` and `Macro`
+    pub fn innermost(&self) -> &Error {
+        match self {
+            Error::Include { source, .. } => source.innermost(),
+            Error::Macro { source, .. } => source.innermost(),
+            x => x,
+        }
+    }
 }
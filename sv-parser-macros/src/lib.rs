@@ -21,7 +21,10 @@ fn impl_node(ast: &DeriveInput) -> TokenStream {
             let mut items = quote! {};
             for v in &data.variants {
                 let ident = &v.ident;
+                // Variants of optional features such as `ams`
+                let cfgs = v.attrs.iter().filter(|x| x.path().is_ident("cfg"));
                 let item = quote! {
+                    #(#cfgs)*
                     #name::#ident(x) => { x.into() },
                 };
                 items = quote! {
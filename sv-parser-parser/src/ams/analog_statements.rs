@@ -0,0 +1,268 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_construct(s: Span) -> IResult<Span, AnalogConstruct> {
+    let (s, a) = keyword("analog")(s)?;
+    let (s, b) = opt(keyword("initial"))(s)?;
+    let (s, c) = analog_statement(s)?;
+    Ok((s, AnalogConstruct { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_statement_or_null(s: Span) -> IResult<Span, AnalogStatementOrNull> {
+    alt((
+        map(analog_statement, |x| {
+            AnalogStatementOrNull::Statement(Box::new(x))
+        }),
+        analog_statement_or_null_attribute,
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_statement_or_null_attribute(s: Span) -> IResult<Span, AnalogStatementOrNull> {
+    let (s, (a, b)) = many_till(attribute_instance, symbol(";"))(s)?;
+    Ok((
+        s,
+        AnalogStatementOrNull::Attribute(Box::new(StatementOrNullAttribute { nodes: (a, b) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_statement(s: Span) -> IResult<Span, AnalogStatement> {
+    let (s, a) = many0(attribute_instance)(s)?;
+    let (s, b) = analog_statement_item(s)?;
+    Ok((s, AnalogStatement { nodes: (a, b) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_statement_item(s: Span) -> IResult<Span, AnalogStatementItem> {
+    alt((
+        map(contribution_statement, |x| {
+            AnalogStatementItem::ContributionStatement(Box::new(x))
+        }),
+        map(indirect_contribution_statement, |x| {
+            AnalogStatementItem::IndirectContributionStatement(Box::new(x))
+        }),
+        map(analog_conditional_statement, |x| {
+            AnalogStatementItem::AnalogConditionalStatement(Box::new(x))
+        }),
+        map(analog_case_statement, |x| {
+            AnalogStatementItem::AnalogCaseStatement(Box::new(x))
+        }),
+        map(analog_loop_statement, |x| {
+            AnalogStatementItem::AnalogLoopStatement(Box::new(x))
+        }),
+        map(analog_seq_block, |x| {
+            AnalogStatementItem::AnalogSeqBlock(Box::new(x))
+        }),
+        map(analog_event_control_statement, |x| {
+            AnalogStatementItem::AnalogEventControlStatement(Box::new(x))
+        }),
+        map(analog_system_task_enable, |x| {
+            AnalogStatementItem::AnalogSystemTaskEnable(Box::new(x))
+        }),
+        map(analog_procedural_assignment, |x| {
+            AnalogStatementItem::AnalogProceduralAssignment(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn contribution_statement(s: Span) -> IResult<Span, ContributionStatement> {
+    let (s, a) = access_function_reference(s)?;
+    let (s, b) = symbol("<+")(s)?;
+    let (s, c) = expression(s)?;
+    let (s, d) = symbol(";")(s)?;
+    Ok((
+        s,
+        ContributionStatement {
+            nodes: (a, b, c, d),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn indirect_contribution_statement(
+    s: Span,
+) -> IResult<Span, IndirectContributionStatement> {
+    let (s, a) = access_function_reference(s)?;
+    let (s, b) = symbol(":")(s)?;
+    let (s, c) = expression(s)?;
+    let (s, d) = symbol(";")(s)?;
+    Ok((
+        s,
+        IndirectContributionStatement {
+            nodes: (a, b, c, d),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn access_function_reference(s: Span) -> IResult<Span, AccessFunctionReference> {
+    let (s, a) = identifier(s)?;
+    let (s, b) = paren(branch_reference)(s)?;
+    Ok((s, AccessFunctionReference { nodes: (a, b) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_conditional_statement(s: Span) -> IResult<Span, AnalogConditionalStatement> {
+    let (s, a) = keyword("if")(s)?;
+    let (s, b) = paren(expression)(s)?;
+    let (s, c) = analog_statement_or_null(s)?;
+    let (s, d) = opt(pair(keyword("else"), analog_statement_or_null))(s)?;
+    Ok((
+        s,
+        AnalogConditionalStatement {
+            nodes: (a, b, c, d),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_case_statement(s: Span) -> IResult<Span, AnalogCaseStatement> {
+    let (s, a) = case_keyword(s)?;
+    let (s, b) = paren(expression)(s)?;
+    let (s, c) = analog_case_item(s)?;
+    let (s, (d, e)) = many_till(analog_case_item, keyword("endcase"))(s)?;
+    Ok((
+        s,
+        AnalogCaseStatement {
+            nodes: (a, b, c, d, e),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_case_item(s: Span) -> IResult<Span, AnalogCaseItem> {
+    alt((analog_case_item_default, analog_case_item_nondefault))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_case_item_nondefault(s: Span) -> IResult<Span, AnalogCaseItem> {
+    let (s, a) = list(symbol(","), expression)(s)?;
+    let (s, b) = symbol(":")(s)?;
+    let (s, c) = analog_statement_or_null(s)?;
+    Ok((
+        s,
+        AnalogCaseItem::NonDefault(Box::new(AnalogCaseItemNondefault { nodes: (a, b, c) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_case_item_default(s: Span) -> IResult<Span, AnalogCaseItem> {
+    let (s, a) = keyword("default")(s)?;
+    let (s, b) = opt(symbol(":"))(s)?;
+    let (s, c) = analog_statement_or_null(s)?;
+    Ok((
+        s,
+        AnalogCaseItem::Default(Box::new(AnalogCaseItemDefault { nodes: (a, b, c) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_loop_statement(s: Span) -> IResult<Span, AnalogLoopStatement> {
+    alt((
+        analog_loop_statement_repeat,
+        analog_loop_statement_while,
+        analog_loop_statement_for,
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_loop_statement_repeat(s: Span) -> IResult<Span, AnalogLoopStatement> {
+    let (s, a) = keyword("repeat")(s)?;
+    let (s, b) = paren(expression)(s)?;
+    let (s, c) = analog_statement(s)?;
+    Ok((
+        s,
+        AnalogLoopStatement::Repeat(Box::new(AnalogLoopStatementRepeat { nodes: (a, b, c) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_loop_statement_while(s: Span) -> IResult<Span, AnalogLoopStatement> {
+    let (s, a) = keyword("while")(s)?;
+    let (s, b) = paren(expression)(s)?;
+    let (s, c) = analog_statement(s)?;
+    Ok((
+        s,
+        AnalogLoopStatement::While(Box::new(AnalogLoopStatementWhile { nodes: (a, b, c) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_loop_statement_for(s: Span) -> IResult<Span, AnalogLoopStatement> {
+    let (s, a) = keyword("for")(s)?;
+    let (s, b) = paren(tuple((
+        variable_assignment,
+        symbol(";"),
+        expression,
+        symbol(";"),
+        variable_assignment,
+    )))(s)?;
+    let (s, c) = analog_statement(s)?;
+    Ok((
+        s,
+        AnalogLoopStatement::For(Box::new(AnalogLoopStatementFor { nodes: (a, b, c) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_seq_block(s: Span) -> IResult<Span, AnalogSeqBlock> {
+    let (s, a) = keyword("begin")(s)?;
+    let (s, b) = opt(pair(symbol(":"), block_identifier))(s)?;
+    let (s, c) = many0(block_item_declaration)(s)?;
+    let (s, (d, e)) = many_till(analog_statement_or_null, keyword("end"))(s)?;
+    Ok((
+        s,
+        AnalogSeqBlock {
+            nodes: (a, b, c, d, e),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_event_control_statement(
+    s: Span,
+) -> IResult<Span, AnalogEventControlStatement> {
+    let (s, a) = event_control(s)?;
+    let (s, b) = analog_statement_or_null(s)?;
+    Ok((s, AnalogEventControlStatement { nodes: (a, b) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_system_task_enable(s: Span) -> IResult<Span, AnalogSystemTaskEnable> {
+    let (s, a) = system_tf_call(s)?;
+    let (s, b) = symbol(";")(s)?;
+    Ok((s, AnalogSystemTaskEnable { nodes: (a, b) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn analog_procedural_assignment(s: Span) -> IResult<Span, AnalogProceduralAssignment> {
+    let (s, a) = variable_assignment(s)?;
+    let (s, b) = symbol(";")(s)?;
+    Ok((s, AnalogProceduralAssignment { nodes: (a, b) }))
+}
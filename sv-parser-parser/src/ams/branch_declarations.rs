@@ -0,0 +1,64 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn branch_declaration(s: Span) -> IResult<Span, BranchDeclaration> {
+    let (s, a) = keyword("branch")(s)?;
+    let (s, b) = paren(branch_reference)(s)?;
+    let (s, c) = list(symbol(","), branch_identifier)(s)?;
+    let (s, d) = symbol(";")(s)?;
+    Ok((
+        s,
+        BranchDeclaration {
+            nodes: (a, b, c, d),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn branch_reference(s: Span) -> IResult<Span, BranchReference> {
+    alt((branch_reference_port, branch_reference_terminals))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn branch_reference_terminals(s: Span) -> IResult<Span, BranchReference> {
+    let (s, a) = branch_terminal(s)?;
+    let (s, b) = opt(pair(symbol(","), branch_terminal))(s)?;
+    Ok((s, BranchReference::Terminals(Box::new((a, b)))))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn branch_reference_port(s: Span) -> IResult<Span, BranchReference> {
+    let (s, a) = symbol("<")(s)?;
+    let (s, b) = port_identifier(s)?;
+    let (s, c) = symbol(">")(s)?;
+    Ok((s, BranchReference::Port(Box::new((a, b, c)))))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn branch_terminal(s: Span) -> IResult<Span, BranchTerminal> {
+    let (s, a) = hierarchical_net_identifier(s)?;
+    let (s, b) = opt(bracket(constant_range_expression))(s)?;
+    Ok((s, BranchTerminal { nodes: (a, b) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ground_declaration(s: Span) -> IResult<Span, GroundDeclaration> {
+    let (s, a) = keyword("ground")(s)?;
+    let (s, b) = list(symbol(","), net_identifier)(s)?;
+    let (s, c) = symbol(";")(s)?;
+    Ok((s, GroundDeclaration { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+pub(crate) fn branch_identifier(s: Span) -> IResult<Span, BranchIdentifier> {
+    let (s, a) = identifier(s)?;
+    Ok((s, BranchIdentifier { nodes: (a,) }))
+}
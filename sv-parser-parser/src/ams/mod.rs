@@ -0,0 +1,6 @@
+pub mod analog_statements;
+pub mod branch_declarations;
+pub mod natures_and_disciplines;
+pub(crate) use analog_statements::*;
+pub(crate) use branch_declarations::*;
+pub(crate) use natures_and_disciplines::*;
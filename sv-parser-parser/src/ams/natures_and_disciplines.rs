@@ -0,0 +1,166 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn nature_declaration(s: Span) -> IResult<Span, NatureDeclaration> {
+    let (s, a) = keyword("nature")(s)?;
+    let (s, b) = nature_identifier(s)?;
+    let (s, c) = opt(pair(symbol(":"), parent_nature))(s)?;
+    let (s, d) = opt(symbol(";"))(s)?;
+    let (s, (e, f)) = many_till(nature_attribute, keyword("endnature"))(s)?;
+    Ok((
+        s,
+        NatureDeclaration {
+            nodes: (a, b, c, d, e, f),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn parent_nature(s: Span) -> IResult<Span, ParentNature> {
+    alt((
+        parent_nature_discipline,
+        map(nature_identifier, |x| ParentNature::Nature(Box::new(x))),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn parent_nature_discipline(s: Span) -> IResult<Span, ParentNature> {
+    let (s, a) = discipline_identifier(s)?;
+    let (s, b) = symbol(".")(s)?;
+    let (s, c) = potential_or_flow(s)?;
+    Ok((s, ParentNature::Discipline(Box::new((a, b, c)))))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn nature_attribute(s: Span) -> IResult<Span, NatureAttribute> {
+    let (s, a) = nature_attribute_identifier(s)?;
+    let (s, b) = symbol("=")(s)?;
+    let (s, c) = constant_expression(s)?;
+    let (s, d) = symbol(";")(s)?;
+    Ok((
+        s,
+        NatureAttribute {
+            nodes: (a, b, c, d),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn nature_attribute_identifier(s: Span) -> IResult<Span, NatureAttributeIdentifier> {
+    alt((
+        map(
+            alt((
+                keyword("abstol"),
+                keyword("access"),
+                keyword("ddt_nature"),
+                keyword("idt_nature"),
+                keyword("units"),
+            )),
+            |x| NatureAttributeIdentifier::Keyword(Box::new(x)),
+        ),
+        map(identifier, |x| {
+            NatureAttributeIdentifier::Identifier(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn discipline_declaration(s: Span) -> IResult<Span, DisciplineDeclaration> {
+    let (s, a) = keyword("discipline")(s)?;
+    let (s, b) = discipline_identifier(s)?;
+    let (s, c) = opt(symbol(";"))(s)?;
+    let (s, (d, e)) = many_till(discipline_item, keyword("enddiscipline"))(s)?;
+    Ok((
+        s,
+        DisciplineDeclaration {
+            nodes: (a, b, c, d, e),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn discipline_item(s: Span) -> IResult<Span, DisciplineItem> {
+    alt((
+        map(nature_binding, |x| {
+            DisciplineItem::NatureBinding(Box::new(x))
+        }),
+        map(discipline_domain_binding, |x| {
+            DisciplineItem::DisciplineDomainBinding(Box::new(x))
+        }),
+        map(nature_attribute_override, |x| {
+            DisciplineItem::NatureAttributeOverride(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn nature_binding(s: Span) -> IResult<Span, NatureBinding> {
+    let (s, a) = potential_or_flow(s)?;
+    let (s, b) = nature_identifier(s)?;
+    let (s, c) = symbol(";")(s)?;
+    Ok((s, NatureBinding { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn potential_or_flow(s: Span) -> IResult<Span, PotentialOrFlow> {
+    alt((
+        map(keyword("potential"), |x| {
+            PotentialOrFlow::Potential(Box::new(x))
+        }),
+        map(keyword("flow"), |x| PotentialOrFlow::Flow(Box::new(x))),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn discipline_domain_binding(s: Span) -> IResult<Span, DisciplineDomainBinding> {
+    let (s, a) = keyword("domain")(s)?;
+    let (s, b) = discrete_or_continuous(s)?;
+    let (s, c) = symbol(";")(s)?;
+    Ok((s, DisciplineDomainBinding { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn discrete_or_continuous(s: Span) -> IResult<Span, DiscreteOrContinuous> {
+    alt((
+        map(keyword("discrete"), |x| {
+            DiscreteOrContinuous::Discrete(Box::new(x))
+        }),
+        map(keyword("continuous"), |x| {
+            DiscreteOrContinuous::Continuous(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn nature_attribute_override(s: Span) -> IResult<Span, NatureAttributeOverride> {
+    let (s, a) = potential_or_flow(s)?;
+    let (s, b) = symbol(".")(s)?;
+    let (s, c) = nature_attribute(s)?;
+    Ok((s, NatureAttributeOverride { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+pub(crate) fn nature_identifier(s: Span) -> IResult<Span, NatureIdentifier> {
+    let (s, a) = identifier(s)?;
+    Ok((s, NatureIdentifier { nodes: (a,) }))
+}
+
+#[tracable_parser]
+pub(crate) fn discipline_identifier(s: Span) -> IResult<Span, DisciplineIdentifier> {
+    let (s, a) = identifier(s)?;
+    Ok((s, DisciplineIdentifier { nodes: (a,) }))
+}
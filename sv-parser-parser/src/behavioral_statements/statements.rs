@@ -38,6 +38,7 @@ pub(crate) fn statement(s: Span) -> IResult<Span, Statement> {
 #[packrat_parser]
 pub(crate) fn statement_item(s: Span) -> IResult<Span, StatementItem> {
     alt((
+        statement_item_macro_invocation,
         map(pair(blocking_assignment, symbol(";")), |x| {
             StatementItem::BlockingAssignment(Box::new(x))
         }),
@@ -95,6 +96,20 @@ pub(crate) fn statement_item(s: Span) -> IResult<Span, StatementItem> {
     ))(s)
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn statement_item_macro_invocation(s: Span) -> IResult<Span, StatementItem> {
+    let (s, (a, call, b)) = macro_invocation(statement_item)(s)?;
+    let a = MacroInvocationItem::StatementItem(Box::new(a));
+    Ok((
+        s,
+        StatementItem::MacroInvocation(Box::new(MacroInvocation {
+            nodes: (a, b),
+            call,
+        })),
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn function_statement(s: Span) -> IResult<Span, FunctionStatement> {
@@ -33,9 +33,15 @@ pub(crate) fn function_data_type_or_implicit(s: Span) -> IResult<Span, FunctionD
 #[packrat_parser]
 pub(crate) fn function_declaration(s: Span) -> IResult<Span, FunctionDeclaration> {
     let (s, a) = keyword("function")(s)?;
-    let (s, b) = opt(lifetime)(s)?;
-    let (s, c) = function_body_declaration(s)?;
-    Ok((s, FunctionDeclaration { nodes: (a, b, c) }))
+    let (s, b) = opt(dynamic_override_specifiers)(s)?;
+    let (s, c) = opt(lifetime)(s)?;
+    let (s, d) = function_body_declaration(s)?;
+    Ok((
+        s,
+        FunctionDeclaration {
+            nodes: (a, b, c, d),
+        },
+    ))
 }
 
 #[tracable_parser]
@@ -107,13 +113,14 @@ pub(crate) fn interface_identifier_or_class_scope(
 #[packrat_parser]
 pub(crate) fn function_prototype(s: Span) -> IResult<Span, FunctionPrototype> {
     let (s, a) = keyword("function")(s)?;
-    let (s, b) = data_type_or_void(s)?;
-    let (s, c) = function_identifier(s)?;
-    let (s, d) = opt(paren(opt(tf_port_list)))(s)?;
+    let (s, b) = opt(dynamic_override_specifiers)(s)?;
+    let (s, c) = data_type_or_void(s)?;
+    let (s, d) = function_identifier(s)?;
+    let (s, e) = opt(paren(opt(tf_port_list)))(s)?;
     Ok((
         s,
         FunctionPrototype {
-            nodes: (a, b, c, d),
+            nodes: (a, b, c, d, e),
         },
     ))
 }
@@ -239,3 +246,60 @@ pub(crate) fn dpi_task_proto(s: Span) -> IResult<Span, DpiTaskProto> {
     let (s, a) = task_prototype(s)?;
     Ok((s, DpiTaskProto { nodes: (a,) }))
 }
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn dynamic_override_specifiers(s: Span) -> IResult<Span, DynamicOverrideSpecifiers> {
+    alt((
+        dynamic_override_specifiers_initial_or_extends,
+        dynamic_override_specifiers_final,
+    ))(s)
+}
+
+#[tracable_parser]
+pub(crate) fn dynamic_override_specifiers_initial_or_extends(
+    s: Span,
+) -> IResult<Span, DynamicOverrideSpecifiers> {
+    let (s, a) = initial_or_extends_specifier(s)?;
+    let (s, b) = opt(final_specifier)(s)?;
+    Ok((
+        s,
+        DynamicOverrideSpecifiers {
+            nodes: (Some(a), b),
+        },
+    ))
+}
+
+#[tracable_parser]
+pub(crate) fn dynamic_override_specifiers_final(
+    s: Span,
+) -> IResult<Span, DynamicOverrideSpecifiers> {
+    let (s, a) = final_specifier(s)?;
+    Ok((
+        s,
+        DynamicOverrideSpecifiers {
+            nodes: (None, Some(a)),
+        },
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn initial_or_extends_specifier(s: Span) -> IResult<Span, InitialOrExtendsSpecifier> {
+    alt((
+        map(pair(symbol(":"), keyword("initial")), |x| {
+            InitialOrExtendsSpecifier::Initial(Box::new(x))
+        }),
+        map(pair(symbol(":"), keyword("extends")), |x| {
+            InitialOrExtendsSpecifier::Extends(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn final_specifier(s: Span) -> IResult<Span, FinalSpecifier> {
+    let (s, a) = symbol(":")(s)?;
+    let (s, b) = keyword("final")(s)?;
+    Ok((s, FinalSpecifier { nodes: (a, b) }))
+}
@@ -511,6 +511,9 @@ pub(crate) fn struct_union(s: Span) -> IResult<Span, StructUnion> {
         map(pair(keyword("union"), keyword("tagged")), |x| {
             StructUnion::UnionTagged(Box::new(x))
         }),
+        map(pair(keyword("union"), keyword("soft")), |x| {
+            StructUnion::UnionSoft(Box::new(x))
+        }),
         map(keyword("union"), |x| StructUnion::Union(Box::new(x))),
     ))(s)
 }
@@ -6,9 +6,15 @@ use crate::*;
 #[packrat_parser]
 pub(crate) fn task_declaration(s: Span) -> IResult<Span, TaskDeclaration> {
     let (s, a) = keyword("task")(s)?;
-    let (s, b) = opt(lifetime)(s)?;
-    let (s, c) = task_body_declaration(s)?;
-    Ok((s, TaskDeclaration { nodes: (a, b, c) }))
+    let (s, b) = opt(dynamic_override_specifiers)(s)?;
+    let (s, c) = opt(lifetime)(s)?;
+    let (s, d) = task_body_declaration(s)?;
+    Ok((
+        s,
+        TaskDeclaration {
+            nodes: (a, b, c, d),
+        },
+    ))
 }
 
 #[tracable_parser]
@@ -134,6 +140,10 @@ pub(crate) fn data_type_or_implicit_tf_port_item(s: Span) -> IResult<Span, DataT
 #[packrat_parser]
 pub(crate) fn tf_port_direction(s: Span) -> IResult<Span, TfPortDirection> {
     alt((
+        map(
+            triple(opt(keyword("const")), keyword("ref"), keyword("static")),
+            |x| TfPortDirection::RefStatic(Box::new(x)),
+        ),
         map(port_direction, |x| {
             TfPortDirection::PortDirection(Box::new(x))
         }),
@@ -180,7 +190,13 @@ pub(crate) fn data_type_or_implicit_tf_port_declaration(
 #[packrat_parser]
 pub(crate) fn task_prototype(s: Span) -> IResult<Span, TaskPrototype> {
     let (s, a) = keyword("task")(s)?;
-    let (s, b) = task_identifier(s)?;
-    let (s, c) = opt(paren(opt(tf_port_list)))(s)?;
-    Ok((s, TaskPrototype { nodes: (a, b, c) }))
+    let (s, b) = opt(dynamic_override_specifiers)(s)?;
+    let (s, c) = task_identifier(s)?;
+    let (s, d) = opt(paren(opt(tf_port_list)))(s)?;
+    Ok((
+        s,
+        TaskPrototype {
+            nodes: (a, b, c, d),
+        },
+    ))
 }
@@ -257,6 +257,7 @@ pub(crate) fn constant_indexed_range(s: Span) -> IResult<Span, ConstantIndexedRa
 #[packrat_parser]
 pub(crate) fn expression(s: Span) -> IResult<Span, Expression> {
     alt((
+        expression_macro_invocation,
         map(terminated(primary, peek(one_of(",();"))), |x| {
             Expression::Primary(Box::new(x))
         }),
@@ -279,6 +280,21 @@ pub(crate) fn expression(s: Span) -> IResult<Span, Expression> {
     ))(s)
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn expression_macro_invocation(s: Span) -> IResult<Span, Expression> {
+    let (s, (a, call, b)) = macro_invocation(expression)(s)?;
+    let (s, _) = peek(one_of(",();:]}"))(s)?;
+    let a = MacroInvocationItem::Expression(Box::new(a));
+    Ok((
+        s,
+        Expression::MacroInvocation(Box::new(MacroInvocation {
+            nodes: (a, b),
+            call,
+        })),
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn expression_unary(s: Span) -> IResult<Span, Expression> {
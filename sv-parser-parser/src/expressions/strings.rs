@@ -11,6 +11,14 @@ pub(crate) fn string_literal(s: Span) -> IResult<Span, StringLiteral> {
 
 #[tracable_parser]
 pub(crate) fn string_literal_impl(s: Span) -> IResult<Span, Locate> {
+    alt((
+        triple_quoted_string_literal_impl,
+        quoted_string_literal_impl,
+    ))(s)
+}
+
+#[tracable_parser]
+pub(crate) fn quoted_string_literal_impl(s: Span) -> IResult<Span, Locate> {
     let (s, a) = tag("\"")(s)?;
     let (s, b) = many0(alt((
         is_not("\\\""),
@@ -19,7 +27,25 @@ pub(crate) fn string_literal_impl(s: Span) -> IResult<Span, Locate> {
         }),
     )))(s)?;
     let (s, c) = tag("\"")(s)?;
+    Ok((s, into_locate(concat_string(a, b, c))))
+}
 
+// IEEE1800-2023 Clause 5.9, a string which may contain newlines and `"`
+#[tracable_parser]
+pub(crate) fn triple_quoted_string_literal_impl(s: Span) -> IResult<Span, Locate> {
+    let (s, a) = tag("\"\"\"")(s)?;
+    let (s, b) = many0(alt((
+        is_not("\\\""),
+        map(pair(tag("\\"), take(1usize)), |(x, y)| {
+            concat(x, y).unwrap()
+        }),
+        terminated(tag("\""), not(tag("\"\""))),
+    )))(s)?;
+    let (s, c) = tag("\"\"\"")(s)?;
+    Ok((s, into_locate(concat_string(a, b, c))))
+}
+
+fn concat_string<'a>(a: Span<'a>, b: Vec<Span<'a>>, c: Span<'a>) -> Span<'a> {
     let mut ret = None;
     for x in b {
         ret = if let Some(ret) = ret {
@@ -29,12 +55,10 @@ pub(crate) fn string_literal_impl(s: Span) -> IResult<Span, Locate> {
         };
     }
 
-    let a = if let Some(b) = ret {
+    if let Some(b) = ret {
         let a = concat(a, b).unwrap();
         concat(a, c).unwrap()
     } else {
         concat(a, c).unwrap()
-    };
-
-    Ok((s, into_locate(a)))
+    }
 }
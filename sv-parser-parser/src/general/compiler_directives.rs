@@ -43,6 +43,9 @@ pub(crate) fn compiler_directive(s: Span) -> IResult<Span, CompilerDirective> {
         map(endcelldefine_compiler_directive, |x| {
             CompilerDirective::EndcelldefineDriveCompilerDirective(Box::new(x))
         }),
+        map(protected_envelope, |x| {
+            CompilerDirective::ProtectedEnvelope(Box::new(x))
+        }),
         map(pragma, |x| CompilerDirective::Pragma(Box::new(x))),
         map(line_compiler_directive, |x| {
             CompilerDirective::LineCompilerDirective(Box::new(x))
@@ -102,6 +105,9 @@ pub(crate) fn compiler_directive_without_resetall(s: Span) -> IResult<Span, Comp
         map(endcelldefine_compiler_directive, |x| {
             CompilerDirective::EndcelldefineDriveCompilerDirective(Box::new(x))
         }),
+        map(protected_envelope, |x| {
+            CompilerDirective::ProtectedEnvelope(Box::new(x))
+        }),
         map(pragma, |x| CompilerDirective::Pragma(Box::new(x))),
         map(line_compiler_directive, |x| {
             CompilerDirective::LineCompilerDirective(Box::new(x))
@@ -376,6 +382,14 @@ pub(crate) fn define_argument_inner(s: Span) -> IResult<Span, Span> {
 
 #[tracable_parser]
 pub(crate) fn define_argument_str(s: Span) -> IResult<Span, Span> {
+    alt((
+        recognize(triple_quoted_string_literal_impl),
+        define_argument_quoted_str,
+    ))(s)
+}
+
+#[tracable_parser]
+pub(crate) fn define_argument_quoted_str(s: Span) -> IResult<Span, Span> {
     let (s, (a, b, c)) = triple(tag("\""), opt(is_not("\"")), tag("\""))(s)?;
     let a = if let Some(b) = b {
         concat(concat(a, b).unwrap(), c).unwrap()
@@ -460,12 +474,12 @@ pub(crate) fn conditional_compiler_directive(
 pub(crate) fn ifdef_directive(s: Span) -> IResult<Span, IfdefDirective> {
     let (s, a) = symbol("`")(s)?;
     let (s, b) = keyword("ifdef")(s)?;
-    let (s, c) = text_macro_identifier(s)?;
+    let (s, c) = ifdef_condition(s)?;
     let (s, d) = ifdef_group_of_lines(s)?;
     let (s, e) = many0(tuple((
         symbol("`"),
         keyword("elsif"),
-        text_macro_identifier,
+        ifdef_condition,
         elsif_group_of_lines,
     )))(s)?;
     let (s, f) = opt(tuple((symbol("`"), keyword("else"), else_group_of_lines)))(s)?;
@@ -484,12 +498,12 @@ pub(crate) fn ifdef_directive(s: Span) -> IResult<Span, IfdefDirective> {
 pub(crate) fn ifndef_directive(s: Span) -> IResult<Span, IfndefDirective> {
     let (s, a) = symbol("`")(s)?;
     let (s, b) = keyword("ifndef")(s)?;
-    let (s, c) = text_macro_identifier(s)?;
+    let (s, c) = ifdef_condition(s)?;
     let (s, d) = ifndef_group_of_lines(s)?;
     let (s, e) = many0(tuple((
         symbol("`"),
         keyword("elsif"),
-        text_macro_identifier,
+        ifdef_condition,
         elsif_group_of_lines,
     )))(s)?;
     let (s, f) = opt(tuple((symbol("`"), keyword("else"), else_group_of_lines)))(s)?;
@@ -503,6 +517,105 @@ pub(crate) fn ifndef_directive(s: Span) -> IResult<Span, IfndefDirective> {
     ))
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_condition(s: Span) -> IResult<Span, IfdefCondition> {
+    alt((
+        map(text_macro_identifier, |x| {
+            IfdefCondition::TextMacroIdentifier(Box::new(x))
+        }),
+        ifdef_condition_paren,
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_condition_paren(s: Span) -> IResult<Span, IfdefCondition> {
+    let (s, a) = paren(ifdef_macro_expression)(s)?;
+    Ok((
+        s,
+        IfdefCondition::Paren(Box::new(IfdefConditionParen { nodes: (a,) })),
+    ))
+}
+
+// `->` and `<->` have the lowest precedence and are right associative as
+// same as expression.
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    let (s, a) = ifdef_macro_expression_or(s)?;
+    let (s, b) = opt(pair(
+        alt((symbol("->"), symbol("<->"))),
+        ifdef_macro_expression,
+    ))(s)?;
+    if let Some((b, c)) = b {
+        Ok((
+            s,
+            IfdefMacroExpression::Binary(Box::new(IfdefMacroExpressionBinary { nodes: (a, b, c) })),
+        ))
+    } else {
+        Ok((s, a))
+    }
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression_or(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    let (s, a) = ifdef_macro_expression_and(s)?;
+    let (s, b) = many0(pair(symbol("||"), ifdef_macro_expression_and))(s)?;
+    Ok((s, fold_ifdef_macro_expression(a, b)))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression_and(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    let (s, a) = ifdef_macro_expression_unary(s)?;
+    let (s, b) = many0(pair(symbol("&&"), ifdef_macro_expression_unary))(s)?;
+    Ok((s, fold_ifdef_macro_expression(a, b)))
+}
+
+fn fold_ifdef_macro_expression(
+    a: IfdefMacroExpression,
+    b: Vec<(Symbol, IfdefMacroExpression)>,
+) -> IfdefMacroExpression {
+    b.into_iter().fold(a, |a, (b, c)| {
+        IfdefMacroExpression::Binary(Box::new(IfdefMacroExpressionBinary { nodes: (a, b, c) }))
+    })
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression_unary(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    alt((
+        ifdef_macro_expression_not,
+        ifdef_macro_expression_paren,
+        map(text_macro_identifier, |x| {
+            IfdefMacroExpression::TextMacroIdentifier(Box::new(x))
+        }),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression_not(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    let (s, a) = symbol("!")(s)?;
+    let (s, b) = ifdef_macro_expression_unary(s)?;
+    Ok((
+        s,
+        IfdefMacroExpression::Not(Box::new(IfdefMacroExpressionNot { nodes: (a, b) })),
+    ))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn ifdef_macro_expression_paren(s: Span) -> IResult<Span, IfdefMacroExpression> {
+    let (s, a) = paren(ifdef_macro_expression)(s)?;
+    Ok((
+        s,
+        IfdefMacroExpression::Paren(Box::new(IfdefMacroExpressionParen { nodes: (a,) })),
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn ifdef_group_of_lines(s: Span) -> IResult<Span, IfdefGroupOfLines> {
@@ -778,6 +891,104 @@ pub(crate) fn simple_identifier_pragma_impl(s: Span) -> IResult<Span, Locate> {
     Ok((s, into_locate(a)))
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn protected_envelope(s: Span) -> IResult<Span, ProtectedEnvelope> {
+    let (s, a) = pragma_protect_begin(s)?;
+    let (s, (b, c)) = many_till(protected_envelope_item, pragma_protect_end)(s)?;
+    Ok((s, ProtectedEnvelope { nodes: (a, b, c) }))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn pragma_protect_begin(s: Span) -> IResult<Span, Pragma> {
+    let (t, a) = pragma(s)?;
+    if !protect_keywords(s, &a).contains(&"begin_protected") {
+        return Err(Err::Error(make_error(s, ErrorKind::Verify)));
+    }
+    Ok((t, a))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn pragma_protect_end(s: Span) -> IResult<Span, Pragma> {
+    let (t, a) = pragma(s)?;
+    if !protect_keywords(s, &a).contains(&"end_protected") {
+        return Err(Err::Error(make_error(s, ErrorKind::Verify)));
+    }
+    Ok((t, a))
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn protected_envelope_item(s: Span) -> IResult<Span, ProtectedEnvelopeItem> {
+    alt((
+        map(protected_block, |x| {
+            ProtectedEnvelopeItem::ProtectedBlock(Box::new(x))
+        }),
+        map(pragma, |x| ProtectedEnvelopeItem::Pragma(Box::new(x))),
+    ))(s)
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn protected_block(s: Span) -> IResult<Span, ProtectedBlock> {
+    let (t, a) = pragma(s)?;
+    let block = protect_keywords(s, &a).iter().any(|x| {
+        matches!(
+            *x,
+            "key_block" | "data_block" | "digest_block" | "key_public_key"
+        )
+    });
+    if !block {
+        return Err(Err::Error(make_error(s, ErrorKind::Verify)));
+    }
+    let (t, b) = protected_data(t)?;
+    Ok((t, ProtectedBlock { nodes: (a, b) }))
+}
+
+/// Keywords without value in `pragma protect
+fn protect_keywords<'a>(s: Span<'a>, x: &Pragma) -> Vec<&'a str> {
+    let text = |x: &Locate| &s.fragment()[x.offset - s.location_offset()..][..x.len];
+    let (_, _, ref name, ref expressions) = x.nodes;
+    if text(&name.nodes.0.nodes.0) != "protect" {
+        return vec![];
+    }
+    let mut ret = vec![];
+    if let Some(expressions) = expressions {
+        for x in expressions.contents() {
+            if let PragmaExpression::PragmaKeyword(x) = x {
+                ret.push(text(&x.nodes.0.nodes.0));
+            }
+        }
+    }
+    ret
+}
+
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn protected_data(s: Span) -> IResult<Span, ProtectedData> {
+    // Encoded data may contain any character, so it continues until the next
+    // `pragma line.
+    let mut len = 0;
+    for line in s.fragment().split_inclusive('\n') {
+        if line.trim_start().starts_with("`pragma") {
+            break;
+        }
+        len += line.len();
+    }
+    if len == 0 {
+        return Err(Err::Error(make_error(s, ErrorKind::TakeUntil)));
+    }
+    let (s, a) = take(len)(s)?;
+    Ok((
+        s,
+        ProtectedData {
+            nodes: (into_locate(a),),
+        },
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn line_compiler_directive(s: Span) -> IResult<Span, LineCompilerDirective> {
@@ -829,6 +1040,14 @@ pub(crate) fn keywords_directive(s: Span) -> IResult<Span, KeywordsDirective> {
 #[packrat_parser]
 pub(crate) fn version_specifier(s: Span) -> IResult<Span, VersionSpecifier> {
     let (s, a) = alt((
+        ams!(map(keyword("VAMS-2.4"), |x| {
+            begin_keywords("VAMS-2.4");
+            x
+        })),
+        map(keyword("1800-2023"), |x| {
+            begin_keywords("1800-2023");
+            x
+        }),
         map(keyword("1800-2017"), |x| {
             begin_keywords("1800-2017");
             x
@@ -1445,6 +1445,173 @@ pub(crate) const KEYWORDS_1800_2017: &[&str] = &[
     "xor",
 ];
 
+// IEEE1800-2023 Clause 22.14 adds no reserved keywords to IEEE1800-2017
+pub(crate) const KEYWORDS_1800_2023: &[&str] = KEYWORDS_1800_2017;
+
+// Verilog-AMS 2.4 Annex B without analog operators and built-in functions
+// such as `ddt` and `sin`, which are parsed as function calls
+#[cfg(feature = "ams")]
+pub(crate) const KEYWORDS_VAMS_2_4: &[&str] = &[
+    "abstol",
+    "access",
+    "aliasparam",
+    "always",
+    "analog",
+    "and",
+    "assign",
+    "automatic",
+    "begin",
+    "branch",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "cmos",
+    "config",
+    "connect",
+    "connectmodule",
+    "connectrules",
+    "continuous",
+    "ddt_nature",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "discipline",
+    "discrete",
+    "domain",
+    "driver_update",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endconfig",
+    "endconnectrules",
+    "enddiscipline",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endnature",
+    "endparamset",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "event",
+    "exclude",
+    "flow",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "from",
+    "function",
+    "generate",
+    "genvar",
+    "ground",
+    "highz0",
+    "highz1",
+    "idt_nature",
+    "if",
+    "ifnone",
+    "incdir",
+    "include",
+    "inf",
+    "initial",
+    "inout",
+    "input",
+    "instance",
+    "integer",
+    "join",
+    "large",
+    "liblist",
+    "library",
+    "localparam",
+    "macromodule",
+    "medium",
+    "merged",
+    "module",
+    "nand",
+    "nature",
+    "negedge",
+    "net_resolution",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "parameter",
+    "paramset",
+    "pmos",
+    "posedge",
+    "potential",
+    "primitive",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "rcmos",
+    "real",
+    "realtime",
+    "reg",
+    "release",
+    "repeat",
+    "resolveto",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "showcancelled",
+    "signed",
+    "small",
+    "specify",
+    "specparam",
+    "split",
+    "string",
+    "strong0",
+    "strong1",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "time",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "units",
+    "unsigned",
+    "use",
+    "uwire",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "while",
+    "wire",
+    "wor",
+    "wreal",
+    "xnor",
+    "xor",
+];
+
 pub(crate) const KEYWORDS_DIRECTIVE: &[&str] = &[
     "begin_keywords",
     "celldefine",
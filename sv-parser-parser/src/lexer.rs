@@ -0,0 +1,306 @@
+use crate::{keywords, parse_version, LanguageVersion, Version};
+use sv_parser_syntaxtree::Locate;
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    EscapedIdentifier,
+    SystemIdentifier,
+    Number,
+    String,
+    Operator,
+    Comment,
+    Directive,
+    WhiteSpace,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub locate: Locate,
+}
+
+impl Token {
+    pub fn str<'a>(&self, s: &'a str) -> &'a str {
+        self.locate.str(s)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+const OPERATORS: &[&str] = &[
+    "<<<=", ">>>=", "===", "!==", "==?", "!=?", "<<<", ">>>", "<->", "|->", "|=>", "<<=", ">>=",
+    "#-#", "#=#", "->>", "&&&", "::", ":=", ":/", "+:", "-:", "++", "--", "**", "==", "!=", "<=",
+    ">=", "&&", "||", "<<", ">>", "->", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "~&", "~|",
+    "~^", "^~", "##", "(*", "*)", ".*", "@@", "+", "-", "*", "/", "%", "=", "<", ">", "!", "~",
+    "&", "|", "^", "?", ":", ";", ",", ".", "(", ")", "[", "]", "{", "}", "#", "@", "'", "$",
+];
+
+const TIME_UNITS: &[&str] = &["step", "ms", "us", "ns", "ps", "fs", "s"];
+
+/// Lexer producing a flat token stream without parsing
+///
+/// Every byte of the source belongs to exactly one token, so unparsable code
+/// is also tokenized. Compiler directives are not processed, so the lexer can
+/// be applied to source code before and after preprocessing. Keywords follow
+/// `` `begin_keywords `` and `` `end_keywords `` in the source, and the
+/// language version given by `with_language_version` (IEEE 1800-2017 by
+/// default) is used outside of them.
+pub struct Lexer<'a> {
+    s: &'a str,
+    pos: usize,
+    line: u32,
+    versions: Vec<Version>,
+    default: Option<Version>,
+    begin_keywords: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Lexer {
+            s,
+            pos: 0,
+            line: 1,
+            versions: Vec::new(),
+            default: None,
+            begin_keywords: false,
+        }
+    }
+
+    pub fn with_language_version(s: &'a str, version: LanguageVersion) -> Self {
+        Lexer {
+            default: Some(version.version()),
+            ..Lexer::new(s)
+        }
+    }
+
+    fn scan_token(&self, rest: &str) -> (TokenKind, usize) {
+        let b = rest.as_bytes();
+        let c = b[0];
+        let next = b.get(1).copied();
+        match c {
+            _ if c.is_ascii_whitespace() => (TokenKind::WhiteSpace, take_while(b, 0, is_space)),
+            b'/' if next == Some(b'/') => {
+                (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+            }
+            b'/' if next == Some(b'*') => (
+                TokenKind::Comment,
+                rest[2..].find("*/").map_or(rest.len(), |x| x + 4),
+            ),
+            b'"' if rest.starts_with("\"\"\"") => (TokenKind::String, scan_triple_string(b)),
+            b'"' => (TokenKind::String, scan_string(b)),
+            b'`' => match next {
+                Some(x) if is_ident_start(x) => {
+                    (TokenKind::Directive, take_while(b, 1, is_ident_char))
+                }
+                Some(b'`') | Some(b'"') => (TokenKind::Directive, 2),
+                _ if rest.starts_with("`\\`\"") => (TokenKind::Directive, 4),
+                _ => (TokenKind::Unknown, 1),
+            },
+            b'\\' => match next {
+                Some(b'\n') => (TokenKind::WhiteSpace, 2),
+                Some(b'\r') if b.get(2) == Some(&b'\n') => (TokenKind::WhiteSpace, 3),
+                Some(x) if !x.is_ascii_whitespace() => (
+                    TokenKind::EscapedIdentifier,
+                    take_while(b, 1, |x| !x.is_ascii_whitespace()),
+                ),
+                _ => (TokenKind::Unknown, 1),
+            },
+            b'$' if next.is_some_and(is_ident_char) => {
+                (TokenKind::SystemIdentifier, take_while(b, 1, is_ident_char))
+            }
+            _ if is_ident_start(c) => {
+                let len = take_while(b, 0, is_ident_char);
+                let version = self.versions.last().copied().or(self.default);
+                if keywords(version).contains(&&rest[..len]) {
+                    (TokenKind::Keyword, len)
+                } else {
+                    (TokenKind::Identifier, len)
+                }
+            }
+            b'0'..=b'9' => (TokenKind::Number, scan_number(b)),
+            b'\'' => {
+                if let Some(len) = scan_based(b, 0) {
+                    (TokenKind::Number, len)
+                } else if matches!(next, Some(b'0' | b'1' | b'x' | b'X' | b'z' | b'Z'))
+                    && !b.get(2).copied().is_some_and(is_ident_char)
+                {
+                    (TokenKind::Number, 2)
+                } else {
+                    (TokenKind::Operator, 1)
+                }
+            }
+            // `*` of `@(*)` is not the end of an attribute
+            b'(' if rest.starts_with("(*)") => (TokenKind::Operator, 1),
+            b'*' if rest.starts_with("*)") && self.s[..self.pos].ends_with('(') => {
+                (TokenKind::Operator, 1)
+            }
+            _ => {
+                if let Some(x) = OPERATORS.iter().find(|x| rest.starts_with(*x)) {
+                    (TokenKind::Operator, x.len())
+                } else {
+                    let len = rest.chars().next().map_or(1, |x| x.len_utf8());
+                    (TokenKind::Unknown, len)
+                }
+            }
+        }
+    }
+
+    fn update_keywords(&mut self, kind: TokenKind, text: &str) {
+        match kind {
+            TokenKind::WhiteSpace | TokenKind::Comment => return,
+            TokenKind::String if self.begin_keywords => {
+                let version = text.trim_start_matches('"').trim_end_matches('"');
+                if let Some(x) = parse_version(version) {
+                    self.versions.push(x);
+                }
+            }
+            TokenKind::Directive if text == "`end_keywords" => {
+                self.versions.pop();
+            }
+            _ => (),
+        }
+        self.begin_keywords = kind == TokenKind::Directive && text == "`begin_keywords";
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let rest = &self.s[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+        let (kind, len) = self.scan_token(rest);
+        let text = &rest[..len];
+        let locate = Locate {
+            offset: self.pos,
+            line: self.line,
+            len,
+        };
+        self.pos += len;
+        self.line += text.matches('\n').count() as u32;
+        self.update_keywords(kind, text);
+        Some(Token { kind, locate })
+    }
+}
+
+/// Tokenize the whole source code by `Lexer`
+pub fn tokenize(s: &str) -> Vec<Token> {
+    Lexer::new(s).collect()
+}
+
+// -----------------------------------------------------------------------------
+
+fn is_space(c: u8) -> bool {
+    c.is_ascii_whitespace()
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$'
+}
+
+fn is_decimal_digit(c: u8) -> bool {
+    c.is_ascii_digit() || c == b'_'
+}
+
+fn is_based_digit(c: u8) -> bool {
+    c.is_ascii_hexdigit() || matches!(c, b'_' | b'x' | b'X' | b'z' | b'Z' | b'?')
+}
+
+// Return the end of the bytes satisfying `f` from `start`
+fn take_while(b: &[u8], start: usize, f: impl Fn(u8) -> bool) -> usize {
+    b[start..]
+        .iter()
+        .position(|x| !f(*x))
+        .map_or(b.len(), |x| start + x)
+}
+
+// An unterminated string ends at the end of line
+fn scan_string(b: &[u8]) -> usize {
+    let mut i = 1;
+    while i < b.len() {
+        match b[i] {
+            b'"' => return i + 1,
+            b'\n' => return i,
+            // The returned end is always at an ASCII byte even if the escaped
+            // character is multi-byte
+            b'\\' => i += 2,
+            _ => i += 1,
+        }
+    }
+    b.len()
+}
+
+// A triple-quoted string of IEEE1800-2023 may contain newlines and `"`
+fn scan_triple_string(b: &[u8]) -> usize {
+    let mut i = 3;
+    while i < b.len() {
+        if b[i..].starts_with(b"\"\"\"") {
+            return i + 3;
+        }
+        i += if b[i] == b'\\' { 2 } else { 1 };
+    }
+    b.len()
+}
+
+// Scan `'[s]<base> <digits>` at `start`
+fn scan_based(b: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    if b.get(i) != Some(&b'\'') {
+        return None;
+    }
+    i += 1;
+    if matches!(b.get(i), Some(b's' | b'S')) {
+        i += 1;
+    }
+    if !matches!(
+        b.get(i),
+        Some(b'b' | b'B' | b'o' | b'O' | b'd' | b'D' | b'h' | b'H')
+    ) {
+        return None;
+    }
+    i += 1;
+    let j = take_while(b, i, |x| x == b' ' || x == b'\t');
+    if b.get(j).copied().is_some_and(is_based_digit) {
+        Some(take_while(b, j, is_based_digit))
+    } else {
+        Some(i)
+    }
+}
+
+fn scan_number(b: &[u8]) -> usize {
+    let mut i = take_while(b, 0, is_decimal_digit);
+    if let Some(x) = scan_based(b, i) {
+        return x;
+    }
+    if b.get(i) == Some(&b'.') && b.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        i = take_while(b, i + 1, is_decimal_digit);
+    }
+    if matches!(b.get(i), Some(b'e' | b'E')) {
+        let j = if matches!(b.get(i + 1), Some(b'+' | b'-')) {
+            i + 2
+        } else {
+            i + 1
+        };
+        if b.get(j).is_some_and(u8::is_ascii_digit) {
+            i = take_while(b, j, is_decimal_digit);
+        }
+    }
+    for unit in TIME_UNITS {
+        let end = i + unit.len();
+        if b[i..].starts_with(unit.as_bytes()) && !b.get(end).copied().is_some_and(is_ident_char) {
+            return end;
+        }
+    }
+    i
+}
@@ -2,13 +2,18 @@
 #![allow(clippy::many_single_char_names, clippy::module_inception)]
 
 pub mod keywords;
+pub mod lexer;
+pub mod limits;
 #[macro_use]
 pub mod utils;
 pub(crate) use keywords::*;
+pub(crate) use limits::count_token;
 pub(crate) use utils::*;
 
 mod tests;
 
+#[cfg(feature = "ams")]
+pub mod ams;
 pub mod behavioral_statements;
 pub mod declarations;
 pub mod expressions;
@@ -19,6 +24,8 @@ pub mod primitive_instances;
 pub mod source_text;
 pub mod specify_section;
 pub mod udp_declaration_and_instantiation;
+#[cfg(feature = "ams")]
+pub(crate) use ams::*;
 pub(crate) use behavioral_statements::*;
 pub(crate) use declarations::*;
 pub(crate) use expressions::*;
@@ -81,15 +88,18 @@ impl HasTracableInfo for SpanInfo {
     }
 }
 
-impl HasExtraState<bool> for SpanInfo {
-    fn get_extra_state(&self) -> bool {
-        in_directive()
+/// Packrat results depend on whether in a directive and the end of a macro expansion
+pub(crate) type ExtraState = (bool, Option<usize>);
+
+impl HasExtraState<ExtraState> for SpanInfo {
+    fn get_extra_state(&self) -> ExtraState {
+        (in_directive(), macro_limit())
     }
 }
 
 // -----------------------------------------------------------------------------
 
-nom_packrat::storage!(AnyNode, bool, 1024);
+nom_packrat::storage!(AnyNode, ExtraState, 1024);
 
 pub fn sv_parser(s: Span) -> IResult<Span, SourceText> {
     init();
@@ -101,6 +111,16 @@ pub fn sv_parser_incomplete(s: Span) -> IResult<Span, SourceText> {
     source_text_incomplete(s)
 }
 
+pub fn sv_parser_stream_begin(s: Span) -> IResult<Span, Vec<WhiteSpace>> {
+    init();
+    many0(white_space)(s)
+}
+
+pub fn sv_parser_stream_next(s: Span) -> IResult<Span, Description> {
+    nom_packrat::init!();
+    description(s)
+}
+
 pub fn lib_parser(s: Span) -> IResult<Span, LibraryText> {
     init();
     library_text(s)
@@ -116,6 +136,59 @@ pub fn pp_parser(s: Span) -> IResult<Span, PreprocessorText> {
     preprocessor_text(s)
 }
 
+pub fn pp_parser_stream_next(s: Span) -> IResult<Span, SourceDescription> {
+    init();
+    source_description(s)
+}
+
+// Parse a fragment of source code by `f` skipping leading white spaces
+fn fragment<'a, O, F>(mut f: F) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O>
+where
+    F: FnMut(Span<'a>) -> IResult<Span<'a>, O>,
+{
+    move |s: Span<'a>| {
+        init();
+        let (s, _) = many0(white_space)(s)?;
+        all_consuming(&mut f)(s)
+    }
+}
+
+pub fn expression_parser(s: Span) -> IResult<Span, Expression> {
+    fragment(expression)(s)
+}
+
+pub fn constant_expression_parser(s: Span) -> IResult<Span, ConstantExpression> {
+    fragment(constant_expression)(s)
+}
+
+pub fn statement_parser(s: Span) -> IResult<Span, Statement> {
+    fragment(statement)(s)
+}
+
+pub fn data_type_parser(s: Span) -> IResult<Span, DataType> {
+    fragment(data_type)(s)
+}
+
+pub fn module_item_parser(s: Span) -> IResult<Span, ModuleItem> {
+    fragment(module_item)(s)
+}
+
+pub fn interface_item_parser(s: Span) -> IResult<Span, InterfaceItem> {
+    fragment(interface_item)(s)
+}
+
+pub fn package_item_parser(s: Span) -> IResult<Span, PackageItem> {
+    fragment(package_item)(s)
+}
+
+pub fn class_item_parser(s: Span) -> IResult<Span, ClassItem> {
+    fragment(class_item)(s)
+}
+
+pub fn description_parser(s: Span) -> IResult<Span, Description> {
+    fragment(description)(s)
+}
+
 fn init() {
     nom_packrat::init!();
     clear_directive();
@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use sv_parser_error::Error;
+
+// -----------------------------------------------------------------------------
+
+/// Token to cancel preprocessing and parsing from another thread
+///
+/// Cancellation is cooperative: a running preprocess or parse checks the token
+/// periodically and returns `Error::Cancelled`.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Resource limits of preprocessing and parsing
+///
+/// `None` means unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Maximum size in bytes of each source file or string
+    pub max_input_size: Option<usize>,
+    /// Maximum size in bytes of the preprocessed text and each macro expansion
+    pub max_output_size: Option<usize>,
+    /// Maximum nesting depth of `include
+    ///
+    /// Nesting deeper than 64 causes `Error::ExceedRecursiveLimit` regardless of it.
+    pub max_include_depth: Option<usize>,
+    /// Maximum number of tokens consumed by each parse including backtracking
+    pub max_token_count: Option<usize>,
+    /// Time when preprocessing and parsing are aborted
+    pub deadline: Option<Instant>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Limits {
+    /// Check the deadline and the cancellation
+    pub fn check(&self) -> Result<(), Error> {
+        if let Some(ref x) = self.cancellation {
+            if x.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+        }
+        if let Some(x) = self.deadline {
+            if Instant::now() >= x {
+                return Err(Error::ExceedDeadline);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.max_token_count.is_none() && self.deadline.is_none() && self.cancellation.is_none()
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+// The deadline and the cancellation are checked once per this number of tokens
+const CHECK_INTERVAL: usize = 64;
+
+struct ParseLimits {
+    limits: Limits,
+    tokens: usize,
+    exceeded: Option<Error>,
+}
+
+thread_local!(
+    static PARSE_LIMITS: RefCell<Option<ParseLimits>> = const { RefCell::new(None) }
+);
+
+/// Set limits of the following parse on this thread
+pub fn set_limits(limits: &Limits) {
+    let x = if limits.is_unlimited() {
+        None
+    } else {
+        Some(ParseLimits {
+            limits: limits.clone(),
+            tokens: 0,
+            exceeded: None,
+        })
+    };
+    PARSE_LIMITS.with(|limits| *limits.borrow_mut() = x);
+}
+
+/// Clear limits and return the error if the parse was aborted by them
+pub fn clear_limits() -> Option<Error> {
+    PARSE_LIMITS.with(|x| x.borrow_mut().take().and_then(|x| x.exceeded))
+}
+
+/// Count a token and return `false` if the parse should be aborted
+///
+/// Once a limit is exceeded, the following calls also return `false`.
+pub(crate) fn count_token() -> bool {
+    PARSE_LIMITS.with(|x| {
+        let mut x = x.borrow_mut();
+        let x = match x.as_mut() {
+            Some(x) => x,
+            None => return true,
+        };
+        if x.exceeded.is_some() {
+            return false;
+        }
+        x.tokens += 1;
+        if let Some(max) = x.limits.max_token_count {
+            if x.tokens > max {
+                x.exceeded = Some(Error::ExceedTokenCount);
+                return false;
+            }
+        }
+        if x.tokens % CHECK_INTERVAL == 0 {
+            if let Err(e) = x.limits.check() {
+                x.exceeded = Some(e);
+                return false;
+            }
+        }
+        true
+    })
+}
@@ -6,6 +6,7 @@ use crate::*;
 #[packrat_parser]
 pub(crate) fn class_item(s: Span) -> IResult<Span, ClassItem> {
     alt((
+        class_item_macro_invocation,
         class_item_property,
         class_item_method,
         class_item_constraint,
@@ -21,6 +22,20 @@ pub(crate) fn class_item(s: Span) -> IResult<Span, ClassItem> {
     ))(s)
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn class_item_macro_invocation(s: Span) -> IResult<Span, ClassItem> {
+    let (s, (a, call, b)) = macro_invocation(class_item)(s)?;
+    let a = MacroInvocationItem::ClassItem(Box::new(a));
+    Ok((
+        s,
+        ClassItem::MacroInvocation(Box::new(MacroInvocation {
+            nodes: (a, b),
+            call,
+        })),
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn class_item_property(s: Span) -> IResult<Span, ClassItem> {
@@ -117,6 +117,9 @@ pub(crate) fn module_common_item(s: Span) -> IResult<Span, ModuleCommonItem> {
         map(elaboration_system_task, |x| {
             ModuleCommonItem::ElaborationSystemTask(Box::new(x))
         }),
+        ams!(map(analog_construct, |x| {
+            ModuleCommonItem::AnalogConstruct(Box::new(x))
+        })),
     ))(s)
 }
 
@@ -209,6 +212,12 @@ pub(crate) fn module_or_generate_item_declaration(
     s: Span,
 ) -> IResult<Span, ModuleOrGenerateItemDeclaration> {
     alt((
+        ams!(map(branch_declaration, |x| {
+            ModuleOrGenerateItemDeclaration::BranchDeclaration(Box::new(x))
+        })),
+        ams!(map(ground_declaration, |x| {
+            ModuleOrGenerateItemDeclaration::GroundDeclaration(Box::new(x))
+        })),
         map(package_or_generate_item_declaration, |x| {
             ModuleOrGenerateItemDeclaration::PackageOrGenerateItemDeclaration(Box::new(x))
         }),
@@ -266,6 +275,7 @@ pub(crate) fn module_or_generate_item_declaration_disable(
 #[packrat_parser]
 pub(crate) fn non_port_module_item(s: Span) -> IResult<Span, NonPortModuleItem> {
     alt((
+        non_port_module_item_macro_invocation,
         map(generate_region, |x| {
             NonPortModuleItem::GenerateRegion(Box::new(x))
         }),
@@ -291,6 +301,20 @@ pub(crate) fn non_port_module_item(s: Span) -> IResult<Span, NonPortModuleItem>
     ))(s)
 }
 
+#[tracable_parser]
+#[packrat_parser]
+pub(crate) fn non_port_module_item_macro_invocation(s: Span) -> IResult<Span, NonPortModuleItem> {
+    let (s, (a, call, b)) = macro_invocation(non_port_module_item)(s)?;
+    let a = MacroInvocationItem::NonPortModuleItem(Box::new(a));
+    Ok((
+        s,
+        NonPortModuleItem::MacroInvocation(Box::new(MacroInvocation {
+            nodes: (a, b),
+            call,
+        })),
+    ))
+}
+
 #[tracable_parser]
 #[packrat_parser]
 pub(crate) fn non_port_module_item_specparam(s: Span) -> IResult<Span, NonPortModuleItem> {
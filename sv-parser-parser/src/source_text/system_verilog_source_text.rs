@@ -45,6 +45,12 @@ pub(crate) fn description(s: Span) -> IResult<Span, Description> {
         map(package_declaration, |x| {
             Description::PackageDeclaration(Box::new(x))
         }),
+        ams!(map(nature_declaration, |x| {
+            Description::NatureDeclaration(Box::new(x))
+        })),
+        ams!(map(discipline_declaration, |x| {
+            Description::DisciplineDeclaration(Box::new(x))
+        })),
         description_package_item,
         description_bind_directive,
         map(config_declaration, |x| {
@@ -476,25 +482,26 @@ pub(crate) fn checker_declaration(s: Span) -> IResult<Span, CheckerDeclaration>
 pub(crate) fn class_declaration(s: Span) -> IResult<Span, ClassDeclaration> {
     let (s, a) = opt(map(keyword("virtual"), |x| Virtual { nodes: (x,) }))(s)?;
     let (s, b) = keyword("class")(s)?;
-    let (s, c) = opt(lifetime)(s)?;
-    let (s, d) = class_identifier(s)?;
-    let (s, e) = opt(parameter_port_list)(s)?;
-    let (s, f) = opt(triple(
+    let (s, c) = opt(final_specifier)(s)?;
+    let (s, d) = opt(lifetime)(s)?;
+    let (s, e) = class_identifier(s)?;
+    let (s, f) = opt(parameter_port_list)(s)?;
+    let (s, g) = opt(triple(
         keyword("extends"),
         class_type,
         opt(paren(list_of_arguments)),
     ))(s)?;
-    let (s, g) = opt(pair(
+    let (s, h) = opt(pair(
         keyword("implements"),
         list(symbol(","), interface_class_type),
     ))(s)?;
-    let (s, h) = symbol(";")(s)?;
-    let (s, (i, j)) = many_till(class_item, keyword("endclass"))(s)?;
-    let (s, k) = opt(pair(symbol(":"), class_identifier))(s)?;
+    let (s, i) = symbol(";")(s)?;
+    let (s, (j, k)) = many_till(class_item, keyword("endclass"))(s)?;
+    let (s, l) = opt(pair(symbol(":"), class_identifier))(s)?;
     Ok((
         s,
         ClassDeclaration {
-            nodes: (a, b, c, d, e, f, g, h, i, j, k),
+            nodes: (a, b, c, d, e, f, g, h, i, j, k, l),
         },
     ))
 }
@@ -45,6 +45,81 @@ macro_rules! error_test {
 mod unit {
     use super::*;
 
+    #[test]
+    fn test_lexer() {
+        use crate::lexer::{tokenize, TokenKind::*};
+
+        let src = r##"`begin_keywords "1364-2001"
+module m(input logic a); // comment
+  always @(*) x <= 8 'h FF + 'x + 1.5e-3 + 10ns + $clog2(\bus[0] );
+  /* block
+     comment */ (* keep *) s = "a\"b";
+`end_keywords
+logic #
+"##;
+        let tokens = tokenize(src);
+        let text: std::string::String = tokens.iter().map(|x| x.str(src)).collect();
+        assert_eq!(text, src);
+
+        let tokens: Vec<_> = tokens
+            .iter()
+            .filter(|x| x.kind != WhiteSpace)
+            .map(|x| (x.kind, x.str(src), x.locate.line))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (Directive, "`begin_keywords", 1),
+                (String, "\"1364-2001\"", 1),
+                (Keyword, "module", 2),
+                (Identifier, "m", 2),
+                (Operator, "(", 2),
+                (Keyword, "input", 2),
+                (Identifier, "logic", 2),
+                (Identifier, "a", 2),
+                (Operator, ")", 2),
+                (Operator, ";", 2),
+                (Comment, "// comment", 2),
+                (Keyword, "always", 3),
+                (Operator, "@", 3),
+                (Operator, "(", 3),
+                (Operator, "*", 3),
+                (Operator, ")", 3),
+                (Identifier, "x", 3),
+                (Operator, "<=", 3),
+                (Number, "8", 3),
+                (Number, "'h FF", 3),
+                (Operator, "+", 3),
+                (Number, "'x", 3),
+                (Operator, "+", 3),
+                (Number, "1.5e-3", 3),
+                (Operator, "+", 3),
+                (Number, "10ns", 3),
+                (Operator, "+", 3),
+                (SystemIdentifier, "$clog2", 3),
+                (Operator, "(", 3),
+                (EscapedIdentifier, "\\bus[0]", 3),
+                (Operator, ")", 3),
+                (Operator, ";", 3),
+                (Comment, "/* block\n     comment */", 4),
+                (Operator, "(*", 5),
+                (Identifier, "keep", 5),
+                (Operator, "*)", 5),
+                (Identifier, "s", 5),
+                (Operator, "=", 5),
+                (String, "\"a\\\"b\"", 5),
+                (Operator, ";", 5),
+                (Directive, "`end_keywords", 6),
+                (Keyword, "logic", 7),
+                (Operator, "#", 7),
+            ]
+        );
+        assert_eq!(tokenize("8'hFF")[0].locate.len, 5);
+        assert_eq!(tokenize("\"abc")[0].locate.len, 4);
+        assert_eq!(tokenize("\u{3042}")[0].kind, Unknown);
+        assert_eq!(tokenize("\"\"\"a\n\"b\" c\"\"\" x")[0].locate.len, 13);
+    }
+
     #[test]
     fn test_chained_method_call() {
         test!(method_call, "variable.method1().method2()", Ok((_, _)));
@@ -364,6 +439,30 @@ mod unit {
         );
     }
 
+    #[test]
+    fn test_ifdef_macro_expression() {
+        test!(ifdef_condition, r##"a"##, Ok((_, _)));
+        test!(ifdef_condition, r##"(a && !b)"##, Ok((_, _)));
+        test!(ifdef_condition, r##"(a || (b -> c) <-> !(d))"##, Ok((_, _)));
+        test!(ifdef_condition, r##"(a && )"##, Err(_));
+        test!(
+            source_text,
+            r##"module test;
+                  `ifdef (A && !B)
+                    initial $display("A");
+                  `elsif (B || C)
+                    initial $display("B or C");
+                  `else
+                    initial $display("none");
+                  `endif
+                  `ifndef (A -> B)
+                    initial $display("not A -> B");
+                  `endif
+                endmodule"##,
+            Ok((_, _))
+        );
+    }
+
     #[test]
     fn test_regression() {
         test!(
@@ -489,6 +588,172 @@ mod unit {
             Ok((_, _))
         );
     }
+
+    #[test]
+    fn test_ieee1800_2023_keywords() {
+        test!(
+            source_text,
+            r##"`begin_keywords "1800-2023"
+                module m; logic a; endmodule
+                `end_keywords"##,
+            Ok((_, _))
+        );
+    }
+
+    #[test]
+    fn test_ieee1800_2023_triple_quoted_string() {
+        test!(string_literal, r##""""""""##, Ok((_, _)));
+        test!(
+            string_literal,
+            r##""""first line
+                "quoted" second line \""" escaped
+                """"##,
+            Ok((_, _))
+        );
+        test!(string_literal, r##""""unterminated"""##, Err(_));
+        test!(
+            source_text,
+            r##"module a; initial $display("""a
+                b"""); endmodule"##,
+            Ok((_, _))
+        );
+        test!(
+            preprocessor_text,
+            r##"`define A """a "b" c"""
+                `define M(x) x
+                `M("""d, "e" f""")"##,
+            Ok((_, _))
+        );
+    }
+
+    #[test]
+    fn test_ieee1800_2023_method_qualifiers() {
+        test!(
+            source_text,
+            r##"class C extends B;
+                  virtual function :initial void f(); endfunction
+                  function :extends :final int g(); return 0; endfunction
+                  task :final t(); endtask
+                  extern virtual task :extends u();
+                  pure virtual function :initial void h();
+                endclass"##,
+            Ok((_, _))
+        );
+        test!(source_text, r##"class :final C; endclass"##, Ok((_, _)));
+        test!(
+            source_text,
+            r##"class C; function :final :initial void f(); endfunction endclass"##,
+            Err(_)
+        );
+        test!(source_text, r##"class :initial C; endclass"##, Err(_));
+    }
+
+    #[test]
+    fn test_ieee1800_2023_union_soft() {
+        test!(
+            data_type,
+            r##"union soft packed { logic [7:0] a; logic [3:0] b; }"##,
+            Ok((_, _))
+        );
+        test!(
+            data_type,
+            r##"union soft { logic [7:0] a; logic [3:0] b; }"##,
+            Ok((_, _))
+        );
+    }
+
+    #[test]
+    fn test_ieee1800_2023_ref_static() {
+        test!(
+            source_text,
+            r##"module a;
+                  task automatic t(ref static logic a); endtask
+                  function automatic void f(const ref static int b); endfunction
+                  function automatic void g;
+                    ref static int d;
+                    const ref static int e;
+                  endfunction
+                endmodule"##,
+            Ok((_, _))
+        );
+    }
+
+    #[cfg(feature = "ams")]
+    #[test]
+    fn test_ams() {
+        test!(
+            source_text,
+            r##"`begin_keywords "VAMS-2.4"
+                nature Current
+                  units = "A";
+                  access = I;
+                  abstol = 1e-12;
+                endnature
+                nature Voltage : electrical.potential;
+                endnature
+                discipline electrical
+                  potential Voltage;
+                  flow Current;
+                  flow.abstol = 1e-9;
+                  domain continuous;
+                enddiscipline
+                module res(p, n);
+                  inout p, n;
+                  electrical p, n, m[0:1];
+                  ground gnd;
+                  branch (p, n) br, br2;
+                  branch (<p>) pbr;
+                  branch (m[0]) mbr;
+                  parameter real r = 1e3;
+                  analog initial $strobe("start");
+                  analog begin : blk
+                    real x;
+                    @(initial_step) x = 0;
+                    @(cross(V(p) - 0.5, +1)) $strobe("cross");
+                    I(br) <+ V(br) / r;
+                    I(<p>) <+ 1e-3 * ddt(V(p, n));
+                    V(n): V(p) == 0;
+                    if (V(p) > 1.0) V(n, gnd) <+ 0; else I(p, gnd) <+ 0;
+                    case (x)
+                      0, 1: x = 1;
+                      default ;
+                    endcase
+                    for (x = 0; x < 2; x = x + 1) V(m[0]) <+ x;
+                  end
+                endmodule
+                `end_keywords"##,
+            Ok((_, _))
+        );
+        test!(
+            source_text,
+            r##"`begin_keywords "VAMS-2.4"
+                module a(p); inout p; electrical p; ground g;
+                  analog V(p) <+ 1; endmodule
+                `end_keywords"##,
+            Ok((_, _))
+        );
+        test!(
+            source_text,
+            r##"`begin_keywords "VAMS-2.4"
+                module a; wire analog; endmodule
+                `end_keywords"##,
+            Err(_)
+        );
+    }
+
+    #[cfg(not(feature = "ams"))]
+    #[test]
+    fn test_ams_disabled() {
+        test!(
+            source_text,
+            r##"`begin_keywords "VAMS-2.4"
+                module a; endmodule
+                `end_keywords"##,
+            Err(_)
+        );
+        assert!("verilog-ams".parse::<LanguageVersion>().is_err());
+        assert!("VAMS-2.4".parse::<LanguageVersion>().is_err());
+    }
 }
 
 mod spec {
@@ -2,6 +2,24 @@ use crate::*;
 
 // -----------------------------------------------------------------------------
 
+/// Alternative of Verilog-AMS constructs, which always fails without `ams`
+/// feature
+#[cfg(feature = "ams")]
+macro_rules! ams {
+    ($x:expr) => {
+        $x
+    };
+}
+
+#[cfg(not(feature = "ams"))]
+macro_rules! ams {
+    ($x:expr) => {
+        fail
+    };
+}
+
+// -----------------------------------------------------------------------------
+
 pub(crate) fn ws<'a, O, F>(
     mut f: F,
 ) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (O, Vec<WhiteSpace>)>
@@ -10,6 +28,9 @@ where
 {
     move |s: Span<'a>| {
         let (s, x) = f(s)?;
+        if !count_token() {
+            return Err(Err::Failure(make_error(s, ErrorKind::TooLarge)));
+        }
         let (s, y) = many0(white_space)(s)?;
         Ok((s, (x, y)))
     }
@@ -23,6 +44,9 @@ where
 {
     move |s: Span<'a>| {
         let (s, x) = f(s)?;
+        if !count_token() {
+            return Err(Err::Failure(make_error(s, ErrorKind::TooLarge)));
+        }
         Ok((s, (x, vec![])))
     }
 }
@@ -365,6 +389,9 @@ pub(crate) enum Version {
     Ieee1800_2009,
     Ieee1800_2012,
     Ieee1800_2017,
+    Ieee1800_2023,
+    #[cfg(feature = "ams")]
+    Vams2_4,
     Directive,
 }
 
@@ -374,21 +401,130 @@ thread_local!(
     }
 );
 
+/// Language version of source code outside of `` `begin_keywords ``
+///
+/// It selects the keywords. Verilog source code is parsed by the
+/// SystemVerilog grammar with Verilog keywords.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LanguageVersion {
+    Verilog1995,
+    Verilog2001,
+    Verilog2005,
+    SystemVerilog2005,
+    SystemVerilog2009,
+    SystemVerilog2012,
+    #[default]
+    SystemVerilog2017,
+    SystemVerilog2023,
+    #[cfg(feature = "ams")]
+    VerilogAms,
+}
+
+impl LanguageVersion {
+    /// Version specifier of `` `begin_keywords `` such as `1364-2005`
+    pub fn specifier(&self) -> &'static str {
+        match self {
+            LanguageVersion::Verilog1995 => "1364-1995",
+            LanguageVersion::Verilog2001 => "1364-2001",
+            LanguageVersion::Verilog2005 => "1364-2005",
+            LanguageVersion::SystemVerilog2005 => "1800-2005",
+            LanguageVersion::SystemVerilog2009 => "1800-2009",
+            LanguageVersion::SystemVerilog2012 => "1800-2012",
+            LanguageVersion::SystemVerilog2017 => "1800-2017",
+            LanguageVersion::SystemVerilog2023 => "1800-2023",
+            #[cfg(feature = "ams")]
+            LanguageVersion::VerilogAms => "VAMS-2.4",
+        }
+    }
+
+    pub fn is_verilog(&self) -> bool {
+        matches!(
+            self,
+            LanguageVersion::Verilog1995
+                | LanguageVersion::Verilog2001
+                | LanguageVersion::Verilog2005
+        )
+    }
+
+    /// Whether `s` is a reserved keyword of this version
+    pub fn is_keyword(&self, s: &str) -> bool {
+        keywords(Some(self.version())).contains(&s)
+    }
+
+    pub(crate) fn version(&self) -> Version {
+        parse_version(self.specifier()).unwrap()
+    }
+}
+
+impl std::str::FromStr for LanguageVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "1364-1995" | "verilog-1995" => Ok(LanguageVersion::Verilog1995),
+            "1364-2001" | "1364-2001-noconfig" | "verilog-2001" => Ok(LanguageVersion::Verilog2001),
+            "1364-2005" | "verilog-2005" | "verilog" => Ok(LanguageVersion::Verilog2005),
+            "1800-2005" => Ok(LanguageVersion::SystemVerilog2005),
+            "1800-2009" => Ok(LanguageVersion::SystemVerilog2009),
+            "1800-2012" => Ok(LanguageVersion::SystemVerilog2012),
+            "1800-2017" | "systemverilog" => Ok(LanguageVersion::SystemVerilog2017),
+            "1800-2023" => Ok(LanguageVersion::SystemVerilog2023),
+            #[cfg(feature = "ams")]
+            "vams-2.4" | "verilog-ams" => Ok(LanguageVersion::VerilogAms),
+            _ => Err(format!("unknown language version: {}", s)),
+        }
+    }
+}
+
+thread_local!(
+    static DEFAULT_VERSION: core::cell::Cell<Option<Version>> = const {
+        core::cell::Cell::new(None)
+    }
+);
+
+/// Set the language version of the following parses on this thread
+///
+/// The previous version is restored when the returned guard is dropped, even
+/// if the parse panics.
+#[must_use]
+pub fn set_language_version(version: LanguageVersion) -> LanguageVersionGuard {
+    let previous = DEFAULT_VERSION.with(|x| x.replace(Some(version.version())));
+    LanguageVersionGuard { previous }
+}
+
+/// Guard of `set_language_version`
+pub struct LanguageVersionGuard {
+    previous: Option<Version>,
+}
+
+impl Drop for LanguageVersionGuard {
+    fn drop(&mut self) {
+        DEFAULT_VERSION.with(|x| x.set(self.previous));
+    }
+}
+
 pub(crate) fn begin_keywords(version: &str) {
-    CURRENT_VERSION.with(|current_version| match version {
-        "1364-1995" => current_version.borrow_mut().push(Version::Ieee1364_1995),
-        "1364-2001" => current_version.borrow_mut().push(Version::Ieee1364_2001),
-        "1364-2001-noconfig" => current_version
-            .borrow_mut()
-            .push(Version::Ieee1364_2001Noconfig),
-        "1364-2005" => current_version.borrow_mut().push(Version::Ieee1364_2005),
-        "1800-2005" => current_version.borrow_mut().push(Version::Ieee1800_2005),
-        "1800-2009" => current_version.borrow_mut().push(Version::Ieee1800_2009),
-        "1800-2012" => current_version.borrow_mut().push(Version::Ieee1800_2012),
-        "1800-2017" => current_version.borrow_mut().push(Version::Ieee1800_2017),
-        "directive" => current_version.borrow_mut().push(Version::Directive),
-        _ => (),
-    });
+    if let Some(version) = parse_version(version) {
+        CURRENT_VERSION.with(|current_version| current_version.borrow_mut().push(version));
+    }
+}
+
+pub(crate) fn parse_version(version: &str) -> Option<Version> {
+    match version {
+        "1364-1995" => Some(Version::Ieee1364_1995),
+        "1364-2001" => Some(Version::Ieee1364_2001),
+        "1364-2001-noconfig" => Some(Version::Ieee1364_2001Noconfig),
+        "1364-2005" => Some(Version::Ieee1364_2005),
+        "1800-2005" => Some(Version::Ieee1800_2005),
+        "1800-2009" => Some(Version::Ieee1800_2009),
+        "1800-2012" => Some(Version::Ieee1800_2012),
+        "1800-2017" => Some(Version::Ieee1800_2017),
+        "1800-2023" => Some(Version::Ieee1800_2023),
+        #[cfg(feature = "ams")]
+        "VAMS-2.4" => Some(Version::Vams2_4),
+        "directive" => Some(Version::Directive),
+        _ => None,
+    }
 }
 
 pub(crate) fn end_keywords() {
@@ -400,7 +536,7 @@ pub(crate) fn end_keywords() {
 pub(crate) fn current_version() -> Option<Version> {
     CURRENT_VERSION.with(|current_version| match current_version.borrow().last() {
         Some(x) => Some(*x),
-        None => None,
+        None => DEFAULT_VERSION.with(|x| x.get()),
     })
 }
 
@@ -412,6 +548,79 @@ pub(crate) fn clear_version() {
 
 // -----------------------------------------------------------------------------
 
+thread_local!(
+    static MACRO_CALLS: core::cell::RefCell<Vec<(usize, usize, String)>> = const {
+        core::cell::RefCell::new(Vec::new())
+    }
+);
+
+thread_local!(
+    static MACRO_LIMIT: core::cell::Cell<Option<usize>> = const {
+        core::cell::Cell::new(None)
+    }
+);
+
+/// Set macro calls to be parsed as `MacroInvocation`
+///
+/// Each call is given by the byte range of its expansion in the parsed text,
+/// and the call text.
+pub fn set_macro_calls(mut calls: Vec<(usize, usize, String)>) {
+    calls.sort_by_key(|x| x.0);
+    MACRO_CALLS.with(|x| *x.borrow_mut() = calls);
+}
+
+pub fn clear_macro_calls() {
+    MACRO_CALLS.with(|x| x.borrow_mut().clear());
+}
+
+/// End of the expansion being parsed as `MacroInvocation`
+pub(crate) fn macro_limit() -> Option<usize> {
+    MACRO_LIMIT.with(|x| x.get())
+}
+
+/// Parse a macro expansion starting at the current position by `f`
+///
+/// `f` must consume the whole expansion. Macro calls inside the expansion
+/// are not parsed as `MacroInvocation`.
+pub(crate) fn macro_invocation<'a, O, F>(
+    mut f: F,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (O, String, Vec<WhiteSpace>)>
+where
+    F: FnMut(Span<'a>) -> IResult<Span<'a>, O>,
+{
+    move |s: Span<'a>| {
+        let offset = s.location_offset();
+        let call = if macro_limit().is_none() {
+            MACRO_CALLS.with(|x| {
+                let x = x.borrow();
+                x.binary_search_by_key(&offset, |x| x.0)
+                    .ok()
+                    .map(|i| (x[i].1, x[i].2.clone()))
+            })
+        } else {
+            None
+        };
+        let (end, call) = match call {
+            Some((end, call)) if end > offset && end - offset <= s.fragment().len() => (end, call),
+            _ => return Err(Err::Error(make_error(s, ErrorKind::Fix))),
+        };
+
+        let (rest, body) = nom::InputTake::take_split(&s, end - offset);
+        MACRO_LIMIT.with(|x| x.set(Some(end)));
+        let ret = f(body);
+        MACRO_LIMIT.with(|x| x.set(None));
+        let x = match ret {
+            Ok((t, x)) if t.fragment().is_empty() => x,
+            _ => return Err(Err::Error(make_error(s, ErrorKind::Fix))),
+        };
+
+        let (s, y) = many0(white_space)(rest)?;
+        Ok((s, (x, call, y)))
+    }
+}
+
+// -----------------------------------------------------------------------------
+
 pub(crate) fn concat<'a>(a: Span<'a>, b: Span<'a>) -> Option<Span<'a>> {
     let c = unsafe { str_concat::concat(a.fragment(), b.fragment()) };
     if let Ok(c) = c {
@@ -424,8 +633,8 @@ pub(crate) fn concat<'a>(a: Span<'a>, b: Span<'a>) -> Option<Span<'a>> {
     }
 }
 
-pub(crate) fn is_keyword(s: &Span) -> bool {
-    let keywords = match current_version() {
+pub(crate) fn keywords(version: Option<Version>) -> &'static [&'static str] {
+    match version {
         Some(Version::Ieee1364_1995) => KEYWORDS_1364_1995,
         Some(Version::Ieee1364_2001) => KEYWORDS_1364_2001,
         Some(Version::Ieee1364_2001Noconfig) => KEYWORDS_1364_2001_NOCONFIG,
@@ -434,15 +643,16 @@ pub(crate) fn is_keyword(s: &Span) -> bool {
         Some(Version::Ieee1800_2009) => KEYWORDS_1800_2009,
         Some(Version::Ieee1800_2012) => KEYWORDS_1800_2012,
         Some(Version::Ieee1800_2017) => KEYWORDS_1800_2017,
+        Some(Version::Ieee1800_2023) => KEYWORDS_1800_2023,
+        #[cfg(feature = "ams")]
+        Some(Version::Vams2_4) => KEYWORDS_VAMS_2_4,
         Some(Version::Directive) => KEYWORDS_DIRECTIVE,
         None => KEYWORDS_1800_2017,
-    };
-    for k in keywords {
-        if s.fragment() == k {
-            return true;
-        }
     }
-    false
+}
+
+pub(crate) fn is_keyword(s: &Span) -> bool {
+    keywords(current_version()).contains(s.fragment())
 }
 
 pub(crate) fn into_locate(s: Span) -> Locate {
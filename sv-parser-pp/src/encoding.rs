@@ -0,0 +1,121 @@
+use std::str::FromStr;
+use sv_parser_error::Location;
+
+/// Encoding of source files which have no BOM and are not valid UTF-8
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    /// ISO-8859-1
+    Latin1,
+    Windows1252,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "windows-1252" | "cp1252" => Ok(Encoding::Windows1252),
+            _ => Err(format!("unknown encoding: {}", s)),
+        }
+    }
+}
+
+// Characters of 0x80-0x9f in Windows-1252. Undefined ones are mapped as Latin-1.
+static WINDOWS1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// Mapping from offsets of decoded text to offsets of the original bytes
+///
+/// Each point is the pair of offsets at the beginning of a span where both
+/// offsets increase together.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OffsetMap {
+    points: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    pub(crate) fn original(&self, pos: usize) -> usize {
+        let i = self.points.partition_point(|(x, _)| *x <= pos);
+        match i.checked_sub(1).map(|i| self.points[i]) {
+            Some((decoded, original)) => original + pos - decoded,
+            None => pos,
+        }
+    }
+
+    /// Convert the offset and column of `location` in the decoded text to the
+    /// original bytes
+    pub(crate) fn locate(&self, location: &mut Location) {
+        let line_start = location.offset + 1 - location.column;
+        location.offset = self.original(location.offset);
+        location.column = location.offset - self.original(line_start) + 1;
+    }
+}
+
+/// Decode source bytes
+///
+/// UTF-8 and UTF-16 with BOM are detected. Bytes without BOM are decoded as
+/// UTF-8, or `fallback` if they are not valid UTF-8. `OffsetMap` is returned
+/// if offsets of the decoded text differ from the original bytes.
+pub(crate) fn decode(
+    bytes: Vec<u8>,
+    fallback: Option<Encoding>,
+) -> Option<(String, Option<OffsetMap>)> {
+    if let Some(x) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+        let s = String::from_utf8(x.to_vec()).ok()?;
+        let map = OffsetMap {
+            points: vec![(0, 3)],
+        };
+        return Some((s, Some(map)));
+    }
+    if let Some(x) = bytes.strip_prefix(&[0xff, 0xfe]) {
+        return decode_utf16(x, u16::from_le_bytes);
+    }
+    if let Some(x) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        return decode_utf16(x, u16::from_be_bytes);
+    }
+
+    let bytes = match String::from_utf8(bytes) {
+        Ok(s) => return Some((s, None)),
+        Err(x) => x.into_bytes(),
+    };
+    let fallback = fallback?;
+
+    let mut s = String::with_capacity(bytes.len());
+    let mut map = OffsetMap::default();
+    for (i, x) in bytes.iter().enumerate() {
+        let c = match (fallback, *x) {
+            (Encoding::Windows1252, 0x80..=0x9f) => WINDOWS1252[(*x - 0x80) as usize],
+            _ => char::from(*x),
+        };
+        s.push(c);
+        if !x.is_ascii() {
+            map.points.push((s.len(), i + 1));
+        }
+    }
+    Some((s, Some(map)))
+}
+
+fn decode_utf16(bytes: &[u8], f: fn([u8; 2]) -> u16) -> Option<(String, Option<OffsetMap>)> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes.chunks(2).map(|x| f([x[0], x[1]]));
+
+    let mut s = String::with_capacity(bytes.len() / 2);
+    let mut map = OffsetMap {
+        points: vec![(0, 2)],
+    };
+    let mut pos = 2;
+    for c in char::decode_utf16(units) {
+        let c = c.ok()?;
+        s.push(c);
+        pos += c.len_utf16() * 2;
+        map.points.push((s.len(), pos));
+    }
+    Some((s, Some(map)))
+}
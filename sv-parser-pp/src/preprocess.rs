@@ -1,26 +1,62 @@
+use crate::encoding::{decode, Encoding, OffsetMap};
 use crate::range::Range;
 use nom::combinator::all_consuming;
-use nom_greedyerror::error_position;
+use nom::error::ErrorKind;
+use nom_greedyerror::{error_position, GreedyError};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
+use std::fmt;
 use std::fs::File;
 use std::hash::BuildHasher;
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
-use sv_parser_error::Error;
-use sv_parser_parser::{pp_parser, Span, SpanInfo};
+use std::str::FromStr;
+use sv_parser_error::{Error, IncludeSite, Location};
+use sv_parser_parser::limits::{clear_limits, set_limits};
+pub use sv_parser_parser::limits::{CancellationToken, Limits};
+use sv_parser_parser::{pp_parser, pp_parser_stream_next, Span, SpanInfo};
 use sv_parser_syntaxtree::{
-    IncludeCompilerDirective, Locate, NodeEvent, RefNode, SourceDescription, TextMacroUsage,
-    WhiteSpace,
+    CompilerDirective, IfdefCondition, IfdefMacroExpression, IncludeCompilerDirective, Locate,
+    NodeEvent, PreprocessorText, RefNode, SourceDescription, TextMacroUsage, WhiteSpace,
 };
 use std::collections::hash_map::RandomState;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 const RECURSIVE_LIMIT: usize = 64;
 
-#[derive(Debug)]
+// Size of the source preprocessed at a time by `PreprocessStream`
+const STREAM_PART_SIZE: usize = 0x10000;
+// Size of the source following an item to be sure that the item is complete
+const STREAM_LOOKAHEAD: usize = 0x1000;
+
+#[derive(Debug, Default)]
 pub struct PreprocessedText {
     text: String,
     origins: BTreeMap<Range, Origin>,
+    expansions: Vec<MacroExpansion>,
+    warnings: Vec<PreprocessWarning>,
+    macros: MacroDatabase,
+    // Boxed because `PreprocessedText` is on the stack of every nested
+    // macro expansion
+    records: Box<Records>,
+}
+
+/// Optional records of `PreprocessedText` enabled by `PreprocessConfig`
+#[derive(Debug, Default)]
+struct Records {
+    conditionals: Vec<ConditionalDirective>,
+    regions: Vec<ConditionalRegion>,
+    macro_calls: Vec<MacroCall>,
+    metacomments: Vec<Metacomment>,
+    // Files whose offsets differ from the original bytes
+    offset_maps: HashMap<PathBuf, OffsetMap>,
+    // Offsets of the original bytes at the beginning of each line of files
+    line_starts: HashMap<PathBuf, Vec<usize>>,
+    // The followings are recorded when `PreprocessConfig::line_markers` is enabled
+    includes: Vec<(usize, u8)>,
+    macro_ranges: Vec<(Range, PathBuf, usize)>,
 }
 
 #[derive(Debug)]
@@ -34,6 +70,10 @@ impl PreprocessedText {
         PreprocessedText {
             text: String::new(),
             origins: BTreeMap::new(),
+            expansions: Vec::new(),
+            warnings: Vec::new(),
+            macros: MacroDatabase::default(),
+            records: Box::default(),
         }
     }
 
@@ -53,7 +93,8 @@ impl PreprocessedText {
         self.origins.insert(range, origin);
     }
 
-    fn merge(&mut self, other: PreprocessedText) {
+    /// Append `other` to the end of the text with its records
+    pub fn merge(&mut self, other: PreprocessedText) {
         let base = self.text.len();
         self.text.push_str(&other.text);
         for (mut range, mut origin) in other.origins {
@@ -61,17 +102,266 @@ impl PreprocessedText {
             origin.range.offset(base);
             self.origins.insert(range, origin);
         }
+        self.expansions.extend(other.expansions);
+        self.warnings.extend(other.warnings);
+        self.macros.merge(other.macros);
+        self.records.conditionals.extend(other.records.conditionals);
+        self.records.regions.extend(other.records.regions);
+        self.records.metacomments.extend(other.records.metacomments);
+        self.records.offset_maps.extend(other.records.offset_maps);
+        for mut x in other.records.macro_calls {
+            x.begin += base;
+            x.end += base;
+            self.records.macro_calls.push(x);
+        }
+        self.records.line_starts.extend(other.records.line_starts);
+        for (pos, level) in other.records.includes {
+            self.records.includes.push((pos + base, level));
+        }
+        for (mut range, path, pos) in other.records.macro_ranges {
+            range.offset(base);
+            self.records.macro_ranges.push((range, path, pos));
+        }
+    }
+
+    /// Drop the text before `pos` with its origins
+    ///
+    /// The remaining text begins at 0. Records without positions in the text,
+    /// such as warnings, are kept.
+    pub fn drain(&mut self, pos: usize) {
+        self.text.drain(..pos);
+
+        for (_, mut origin) in std::mem::take(&mut self.origins) {
+            if origin.range.end <= pos {
+                continue;
+            }
+            if origin.range.begin < pos {
+                if let Some((_, ref mut range)) = origin.origin {
+                    range.begin += pos - origin.range.begin;
+                }
+                origin.range.begin = pos;
+            }
+            origin.range.begin -= pos;
+            origin.range.end -= pos;
+            self.origins.insert(origin.range, origin);
+        }
+
+        self.records.macro_calls.retain(|x| x.begin >= pos);
+        for x in &mut self.records.macro_calls {
+            x.begin -= pos;
+            x.end -= pos;
+        }
+        self.records.includes.retain(|(x, _)| *x >= pos);
+        for (x, _) in &mut self.records.includes {
+            *x -= pos;
+        }
+        self.records.macro_ranges.retain(|(x, _, _)| x.end > pos);
+        for (x, _, _) in &mut self.records.macro_ranges {
+            x.begin = x.begin.saturating_sub(pos);
+            x.end -= pos;
+        }
+    }
+
+    fn warn<T: AsRef<Path>, U: Into<String>>(
+        &mut self,
+        kind: PreprocessWarningKind,
+        message: U,
+        path: T,
+        pos: usize,
+    ) {
+        self.warnings.push(PreprocessWarning {
+            kind,
+            message: message.into(),
+            origin: Some((PathBuf::from(path.as_ref()), pos)),
+        });
     }
 
     pub fn text(&self) -> &str {
         &self.text
     }
 
+    /// Get macro expansions recorded when `PreprocessConfig::expand_trace` is enabled
+    pub fn expansions(&self) -> &[MacroExpansion] {
+        &self.expansions
+    }
+
+    /// Get warnings found by preprocess
+    pub fn warnings(&self) -> &[PreprocessWarning] {
+        &self.warnings
+    }
+
+    /// Get macro database recorded when `PreprocessConfig::macro_database` is enabled
+    pub fn macros(&self) -> &MacroDatabase {
+        &self.macros
+    }
+
+    /// Get `ifdef/`ifndef directives recorded when
+    /// `PreprocessConfig::conditional_directives` is enabled
+    pub fn conditional_directives(&self) -> &[ConditionalDirective] {
+        &self.records.conditionals
+    }
+
+    /// Get `ifdef/`ifndef regions recorded when
+    /// `PreprocessConfig::conditional_regions` is enabled
+    ///
+    /// Regions of included files are placed at the top level.
+    pub fn conditional_regions(&self) -> &[ConditionalRegion] {
+        &self.records.regions
+    }
+
+    /// Get top-level macro calls recorded when
+    /// `PreprocessConfig::macro_invocations` is enabled
+    pub fn macro_calls(&self) -> &[MacroCall] {
+        &self.records.macro_calls
+    }
+
+    /// Get metacomments recorded when `PreprocessConfig::metacomments` is enabled
+    pub fn metacomments(&self) -> &[Metacomment] {
+        &self.records.metacomments
+    }
+
+    /// Get branches guarding the specified line from the outermost one
+    pub fn conditional_guards<T: AsRef<Path>>(&self, path: T, line: u32) -> Vec<&ConditionalBranch> {
+        let mut ret = Vec::new();
+        let mut regions = &self.records.regions;
+        'outer: loop {
+            for x in regions {
+                if x.path != path.as_ref() {
+                    continue;
+                }
+                for x in &x.branches {
+                    if x.line < line && line < x.end_line {
+                        ret.push(x);
+                        regions = &x.regions;
+                        continue 'outer;
+                    }
+                }
+            }
+            break;
+        }
+        ret
+    }
+
+    /// Get text with `line directives pointing the original positions
+    ///
+    /// A directive is inserted at the beginning, include entry/exit, and
+    /// every line which doesn't follow the previous line in the original
+    /// source. Lines of a macro expansion are regarded as its call site.
+    /// This requires `PreprocessConfig::line_markers`.
+    pub fn text_with_line_markers(&self) -> String {
+        let mut ret = String::new();
+        let mut includes = self.records.includes.iter().peekable();
+        let mut macro_ranges = self.records.macro_ranges.iter().peekable();
+        let mut expected: Option<(&PathBuf, usize)> = None;
+        let mut level = 0;
+        let mut pos = 0;
+
+        for line in self.text.split_inclusive('\n') {
+            let begin = pos;
+            pos += line.len();
+
+            // IEEE1800-2017 Clause 22.12, page 687
+            // The level parameter shall be 0, 1, or 2. 1 indicates that the
+            // following line is the first line after an include file has been
+            // entered, 2 indicates that the following line is the first line
+            // after an include file has been exited, and 0 indicates any other
+            // line.
+            while let Some((x, y)) = includes.peek() {
+                if *x > begin {
+                    break;
+                }
+                level = *y;
+                includes.next();
+            }
+
+            // Blank lines are left by directives, so they are not checked.
+            if line.trim().is_empty() {
+                expected = expected.map(|(path, line)| (path, line + 1));
+                ret.push_str(line);
+                continue;
+            }
+
+            // Macro ranges are sorted and don't overlap each other.
+            while let Some((x, _, _)) = macro_ranges.peek() {
+                if x.end > begin {
+                    break;
+                }
+                macro_ranges.next();
+            }
+            let in_macro = macro_ranges.peek().filter(|(x, _, _)| x.begin <= begin);
+            let origin = match in_macro {
+                Some((x, _, _)) if x.begin < begin => None,
+                Some((_, path, pos)) => Some((path, *pos)),
+                None => self.origin(begin),
+            };
+            let origin = origin.and_then(|(path, pos)| {
+                let line_starts = self.records.line_starts.get(path)?;
+                let line = match line_starts.binary_search(&pos) {
+                    Ok(x) => x + 1,
+                    Err(x) => x,
+                };
+                Some((path, line))
+            });
+
+            if let Some((path, line)) = origin {
+                if level != 0 || expected != Some((path, line)) {
+                    // The filename is a string literal
+                    let path = path
+                        .to_string_lossy()
+                        .replace('\\', "\\\\")
+                        .replace('"', "\\\"");
+                    ret.push_str(&format!("`line {} \"{}\" {}\n", line, path, level));
+                    level = 0;
+                }
+                expected = Some((path, line + 1));
+            } else {
+                expected = expected.map(|(path, line)| (path, line + 1));
+            }
+            ret.push_str(line);
+        }
+        ret
+    }
+
+    /// Get the location in the original source of the specified position
+    pub fn location(&self, pos: usize) -> Option<Location> {
+        let (path, offset) = self.origin(pos)?;
+        Some(self.source_location(path, offset))
+    }
+
+    /// Get the location in the original source of the specified warning
+    pub fn warning_location(&self, warning: &PreprocessWarning) -> Option<Location> {
+        let (path, offset) = warning.origin.as_ref()?;
+        let offset = match self.records.offset_maps.get(path) {
+            Some(map) => map.original(*offset),
+            None => *offset,
+        };
+        Some(self.source_location(path, offset))
+    }
+
+    fn source_location(&self, path: &Path, offset: usize) -> Location {
+        let (line, line_start) = match self.records.line_starts.get(path) {
+            Some(x) => {
+                let line = x.partition_point(|x| *x <= offset).max(1);
+                (line, x[line - 1])
+            }
+            None => (1, 0),
+        };
+        Location {
+            path: PathBuf::from(path),
+            offset,
+            line,
+            column: offset - line_start + 1,
+        }
+    }
+
     pub fn origin(&self, pos: usize) -> Option<(&PathBuf, usize)> {
         let origin = self.origins.get(&Range::new(pos, pos + 1));
         if let Some(origin) = origin {
             if let Some((ref origin_path, ref origin_range)) = origin.origin {
-                let ret_pos = pos - origin.range.begin + origin_range.begin;
+                let mut ret_pos = pos - origin.range.begin + origin_range.begin;
+                if let Some(map) = self.records.offset_maps.get(origin_path) {
+                    ret_pos = map.original(ret_pos);
+                }
                 Some((&origin_path, ret_pos))
             } else {
                 None
@@ -117,19 +407,562 @@ impl DefineText {
 
 pub type Defines<V=RandomState> = HashMap<String, Option<Define>, V>;
 
+/// A location searched for a relative `include path
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IncludeSearch {
+    /// The current working directory of the process
+    WorkingDirectory,
+    /// The directory of the file containing the `include directive
+    IncludingFile,
+    /// `include_paths` in the specified order
+    IncludePaths,
+}
+
+/// A tool whose predefined macros and leniencies are emulated
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Profile {
+    /// IEEE1800-2017 without tool-specific macros
+    Generic1800_2017,
+    Vcs,
+    Questa,
+    Xcelium,
+    Verilator,
+    /// Synthesis tools which define `SYNTHESIS`
+    Synthesis,
+}
+
+impl Profile {
+    /// Macros defined by the tool
+    ///
+    /// `SV_COV_*` constants are defined in all profiles.
+    pub fn predefined_macros(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        match self {
+            Profile::Generic1800_2017 => &[],
+            Profile::Vcs => &[("VCS", None), ("SYNOPSYS", None)],
+            Profile::Questa => &[("MODEL_TECH", None), ("QUESTA", None)],
+            Profile::Xcelium => &[("INCA", None), ("XCELIUM", None)],
+            Profile::Verilator => &[
+                ("VERILATOR", None),
+                ("verilator", None),
+                ("verilator3", None),
+                ("SYSTEMVERILOG", None),
+                ("coverage_block_off", Some("/*verilator coverage_block_off*/")),
+            ],
+            Profile::Synthesis => &[("SYNTHESIS", None)],
+        }
+    }
+
+    /// Whether `include can be on a line with other items
+    ///
+    /// IEEE1800-2017 forbids it, but simulators accept it.
+    pub fn include_line(&self) -> bool {
+        !matches!(self, Profile::Generic1800_2017 | Profile::Synthesis)
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "generic" | "1800-2017" => Ok(Profile::Generic1800_2017),
+            "vcs" => Ok(Profile::Vcs),
+            "questa" => Ok(Profile::Questa),
+            "xcelium" => Ok(Profile::Xcelium),
+            "verilator" => Ok(Profile::Verilator),
+            "synthesis" => Ok(Profile::Synthesis),
+            _ => Err(format!("unknown profile: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PreprocessConfig {
+    pub strip_comments: bool,
+    pub ignore_include: bool,
+    /// Record each macro expansion to `PreprocessedText::expansions`
+    pub expand_trace: bool,
+    /// Record macro definitions and usages to `PreprocessedText::macros`
+    pub macro_database: bool,
+    /// Record positions for `PreprocessedText::text_with_line_markers`
+    pub line_markers: bool,
+    /// Record `ifdef/`ifndef to `PreprocessedText::conditional_directives`
+    pub conditional_directives: bool,
+    /// Record `ifdef/`ifndef to `PreprocessedText::conditional_regions`
+    pub conditional_regions: bool,
+    /// Record macro calls to `PreprocessedText::macro_calls`
+    ///
+    /// The parser keeps them as `MacroInvocation` nodes if possible.
+    pub macro_invocations: bool,
+    /// Search order of a relative `include path
+    pub include_search: Vec<IncludeSearch>,
+    /// Directories searched for `include <filename>
+    ///
+    /// If the file is not found in them, `include_search` is used as same as
+    /// `include "filename"`.
+    pub system_include_paths: Vec<PathBuf>,
+    /// Predefined macros and leniencies of a tool
+    pub profile: Profile,
+    /// Record metacomments to `PreprocessedText::metacomments`
+    pub metacomments: bool,
+    /// Exclude text between translate_off and translate_on metacomments in
+    /// active branches as synthesis tools do
+    ///
+    /// The excluded text is replaced by whitespace, so lines and columns are kept.
+    pub exclude_translate_off: bool,
+    /// Encoding of files which have no BOM and are not valid UTF-8
+    ///
+    /// If `None`, such files cause `Error::ReadUtf8`. `PreprocessedText::origin`
+    /// returns offsets of the original bytes.
+    pub fallback_encoding: Option<Encoding>,
+    /// Resource limits and cancellation
+    pub limits: Limits,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        PreprocessConfig {
+            strip_comments: false,
+            ignore_include: false,
+            expand_trace: false,
+            macro_database: false,
+            line_markers: false,
+            conditional_directives: false,
+            conditional_regions: false,
+            macro_invocations: false,
+            include_search: vec![
+                IncludeSearch::WorkingDirectory,
+                IncludeSearch::IncludingFile,
+                IncludeSearch::IncludePaths,
+            ],
+            system_include_paths: vec![],
+            profile: Profile::Generic1800_2017,
+            metacomments: false,
+            exclude_translate_off: false,
+            fallback_encoding: None,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// Kind of `PreprocessWarning`
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PreprocessWarningKind {
+    /// `define overwrites a different definition
+    Redefinition,
+    /// `undef of a macro which is not defined
+    UndefineUndefined,
+    /// `elsif is taken because of a predefined macro in `ifdef/`ifndef
+    ElsifPredefined,
+    /// `include <filename> is not found in `system_include_paths`
+    IncludeFallback,
+    /// `begin_keywords without `end_keywords in the same file
+    UnterminatedKeywords,
+}
+
+impl PreprocessWarningKind {
+    /// Stable code of the warning kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            PreprocessWarningKind::Redefinition => "PP001",
+            PreprocessWarningKind::UndefineUndefined => "PP002",
+            PreprocessWarningKind::ElsifPredefined => "PP003",
+            PreprocessWarningKind::IncludeFallback => "PP004",
+            PreprocessWarningKind::UnterminatedKeywords => "PP005",
+        }
+    }
+}
+
+/// A suspicious construct which doesn't stop preprocess
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreprocessWarning {
+    pub kind: PreprocessWarningKind,
+    pub message: String,
+    /// Path and byte position in the source decoded to UTF-8
+    ///
+    /// `PreprocessedText::warning_location` returns the location in the
+    /// original bytes.
+    pub origin: Option<(PathBuf, usize)>,
+}
+
+impl PreprocessWarning {
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for PreprocessWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "warning[{}]: {}", self.code(), self.message)
+    }
+}
+
+/// A position in the original source
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacroSite {
+    pub path: PathBuf,
+    pub line: u32,
+}
+
+/// A `define of `MacroRecord`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacroDefinition {
+    pub site: MacroSite,
+    /// Formal arguments and their defaults
+    pub arguments: Vec<(String, Option<String>)>,
+    pub text: Option<String>,
+}
+
+impl MacroDefinition {
+    /// Get formal arguments which don't appear in the macro text
+    pub fn unused_arguments(&self) -> Vec<&str> {
+        let tokens = self.text.as_deref().map(split_text).unwrap_or_default();
+        self.arguments
+            .iter()
+            .filter(|(x, _)| !tokens.contains(x))
+            .map(|(x, _)| x.as_str())
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MacroUsageKind {
+    /// `NAME or `NAME(...)
+    Expansion,
+    /// Condition of `ifdef, `ifndef or `elsif
+    Condition,
+}
+
+/// A usage of `MacroRecord`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacroUsage {
+    pub site: MacroSite,
+    pub kind: MacroUsageKind,
+    /// The number of actual arguments, or `None` without parentheses
+    pub arguments: Option<usize>,
+    /// The actual arguments don't match the definition at the usage
+    pub arity_mismatch: bool,
+}
+
+/// All definitions, `undef and usages of a macro
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacroRecord {
+    pub name: String,
+    pub definitions: Vec<MacroDefinition>,
+    pub undefs: Vec<MacroSite>,
+    pub usages: Vec<MacroUsage>,
+}
+
+/// A suspicious macro found by `MacroDatabase::issues`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MacroIssue {
+    /// The macro is defined but never used
+    Unused { name: String, site: MacroSite },
+    /// The number of actual arguments doesn't match the definition
+    ArityMismatch { name: String, site: MacroSite },
+    /// The formal argument doesn't appear in the macro text
+    UnusedArgument {
+        name: String,
+        argument: String,
+        site: MacroSite,
+    },
+}
+
+/// Macros defined, undefined or used during preprocess
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacroDatabase {
+    macros: BTreeMap<String, MacroRecord>,
+}
+
+impl MacroDatabase {
+    /// Get the record of the specified macro
+    pub fn get(&self, name: &str) -> Option<&MacroRecord> {
+        self.macros.get(name)
+    }
+
+    /// Get all records in name order
+    pub fn records(&self) -> impl Iterator<Item = &MacroRecord> {
+        self.macros.values()
+    }
+
+    /// Find unused macros, arity mismatches and unused formal arguments
+    pub fn issues(&self) -> Vec<MacroIssue> {
+        let mut ret = Vec::new();
+        for x in self.macros.values() {
+            if let Some(definition) = x.definitions.first() {
+                if x.usages.is_empty() {
+                    ret.push(MacroIssue::Unused {
+                        name: x.name.clone(),
+                        site: definition.site.clone(),
+                    });
+                }
+            }
+            for usage in x.usages.iter().filter(|x| x.arity_mismatch) {
+                ret.push(MacroIssue::ArityMismatch {
+                    name: x.name.clone(),
+                    site: usage.site.clone(),
+                });
+            }
+            for definition in &x.definitions {
+                for argument in definition.unused_arguments() {
+                    ret.push(MacroIssue::UnusedArgument {
+                        name: x.name.clone(),
+                        argument: String::from(argument),
+                        site: definition.site.clone(),
+                    });
+                }
+            }
+        }
+        ret
+    }
+
+    fn record(&mut self, name: &str) -> &mut MacroRecord {
+        self.macros
+            .entry(String::from(name))
+            .or_insert_with(|| MacroRecord {
+                name: String::from(name),
+                ..Default::default()
+            })
+    }
+
+    fn merge(&mut self, other: MacroDatabase) {
+        for (name, other) in other.macros {
+            let x = self.record(&name);
+            x.definitions.extend(other.definitions);
+            x.undefs.extend(other.undefs);
+            x.usages.extend(other.usages);
+        }
+    }
+
+    /// Replace all sites by the specified one
+    fn relocate(&mut self, site: &MacroSite) {
+        for x in self.macros.values_mut() {
+            for x in &mut x.definitions {
+                x.site = site.clone();
+            }
+            for x in &mut x.undefs {
+                *x = site.clone();
+            }
+            for x in &mut x.usages {
+                x.site = site.clone();
+            }
+        }
+    }
+}
+
+/// An `ifdef or `ifndef evaluated by preprocess
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionalDirective {
+    pub path: PathBuf,
+    pub line: u32,
+    /// Macros in the conditions of the directive and its `elsif
+    pub macros: Vec<String>,
+    /// Macros in the conditions of enclosing directives
+    pub enclosing: Vec<String>,
+}
+
+/// Tool namespaces recognized as the first word of a metacomment
+const METACOMMENT_NAMESPACES: &[&str] = &[
+    "synopsys",
+    "synthesis",
+    "pragma",
+    "cadence",
+    "ambit",
+    "rtl_synthesis",
+    "verilator",
+    "spyglass",
+];
+
+/// A comment carrying a tool directive such as `// synopsys translate_off`
+/// or `/* verilator lint_off WIDTH */`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metacomment {
+    pub path: PathBuf,
+    pub line: u32,
+    /// Tool namespace such as `synopsys`, `pragma` or `verilator`
+    pub namespace: String,
+    /// The word following the namespace such as `translate_off` or `lint_off`
+    pub directive: String,
+    /// The remaining words such as `WIDTH` of `verilator lint_off WIDTH`
+    pub arguments: String,
+}
+
+impl Metacomment {
+    /// Parse the text of a comment including `//` or `/* */`
+    pub fn parse<T: AsRef<Path>>(text: &str, path: T, line: u32) -> Option<Metacomment> {
+        let text = if let Some(x) = text.strip_prefix("//") {
+            x
+        } else {
+            text.strip_prefix("/*")?.strip_suffix("*/")?
+        };
+        let mut words = text.split_whitespace();
+        let namespace = words.next()?;
+        if !METACOMMENT_NAMESPACES.contains(&namespace) {
+            return None;
+        }
+        let directive = words.next()?;
+        Some(Metacomment {
+            path: PathBuf::from(path.as_ref()),
+            line,
+            namespace: String::from(namespace),
+            directive: String::from(directive),
+            arguments: words.collect::<Vec<_>>().join(" "),
+        })
+    }
+
+    /// Whether the metacomment starts a region ignored by synthesis tools
+    pub fn is_translate_off(&self) -> bool {
+        self.translate() == Some(false)
+    }
+
+    /// Whether the metacomment ends a region ignored by synthesis tools
+    pub fn is_translate_on(&self) -> bool {
+        self.translate() == Some(true)
+    }
+
+    fn translate(&self) -> Option<bool> {
+        let namespace = self.namespace.as_str();
+        let directive = self.directive.as_str();
+        match (namespace, directive, self.arguments.as_str()) {
+            ("verilator", _, _) | ("spyglass", _, _) => None,
+            ("ambit", "synthesis", "off") | ("rtl_synthesis", "off", _) => Some(false),
+            ("ambit", "synthesis", "on") | ("rtl_synthesis", "on", _) => Some(true),
+            (_, "translate_off", _) | (_, "synthesis_off", _) => Some(false),
+            (_, "translate_on", _) | (_, "synthesis_on", _) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// A macro call and its expansion in `PreprocessedText::text`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MacroCall {
+    /// Call text in the original source
+    pub text: String,
+    /// Byte range of the expansion
+    pub begin: usize,
+    pub end: usize,
+}
+
+/// An `ifdef or `ifndef directive in the original source
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionalRegion {
+    pub path: PathBuf,
+    /// Lines of `ifdef/`ifndef and `endif
+    pub line: u32,
+    pub end_line: u32,
+    pub branches: Vec<ConditionalBranch>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConditionalBranchKind {
+    Ifdef,
+    Ifndef,
+    Elsif,
+    Else,
+}
+
+/// A group of lines in `ConditionalRegion`
+///
+/// The lines between `line` and `end_line` are guarded by `condition`, and
+/// `elsif/`else branches are also guarded by the preceding branches.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionalBranch {
+    pub kind: ConditionalBranchKind,
+    /// Condition as written, or `None` for `else
+    pub condition: Option<String>,
+    /// Line of the directive starting the branch
+    pub line: u32,
+    /// Line of the directive ending the branch
+    pub end_line: u32,
+    /// Whether the branch is taken under the defines at the directive
+    pub active: bool,
+    /// Nested regions
+    pub regions: Vec<ConditionalRegion>,
+}
+
+/// A record of `TextMacroUsage` expansion
+///
+/// `path`, `offset` and `line` point the call site. For nested expansions,
+/// they are relative to `body` of the parent expansion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MacroExpansion {
+    pub name: String,
+    pub path: PathBuf,
+    pub offset: usize,
+    pub line: u32,
+    /// Formal and actual arguments after default substitution
+    pub arguments: Vec<(String, String)>,
+    /// Macro text after argument substitution
+    pub body: String,
+    /// Expansions of macros used in `body`
+    pub nested: Vec<MacroExpansion>,
+    /// Emitted text
+    pub text: String,
+}
+
+impl MacroExpansion {
+    fn fmt_indent(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        let pad = "  ".repeat(indent);
+        let lines = |x: &str| x.trim_end().replace('\n', &format!("\n{}    ", pad));
+        writeln!(
+            f,
+            "{}`{} at {}:{}",
+            pad,
+            self.name,
+            self.path.to_string_lossy(),
+            self.line
+        )?;
+        for (formal, actual) in &self.arguments {
+            writeln!(f, "{}  arg  {} = {}", pad, formal, actual)?;
+        }
+        writeln!(f, "{}", format!("{}  body {}", pad, lines(&self.body)).trim_end())?;
+        for x in &self.nested {
+            x.fmt_indent(f, indent + 1)?;
+        }
+        writeln!(f, "{}", format!("{}  text {}", pad, lines(&self.text)).trim_end())
+    }
+}
+
+impl fmt::Display for MacroExpansion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indent(f, 0)
+    }
+}
+
 pub fn preprocess<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     path: T,
     pre_defines: &Defines<V>,
     include_paths: &[U],
     strip_comments: bool,
     ignore_include: bool,
+) -> Result<(PreprocessedText, Defines), Error> {
+    let config = PreprocessConfig {
+        strip_comments,
+        ignore_include,
+        ..Default::default()
+    };
+    preprocess_with_config(path, pre_defines, include_paths, &config)
+}
+
+pub fn preprocess_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
 ) -> Result<(PreprocessedText, Defines), Error> {
     preprocess_inner(
         path,
-        pre_defines,
+        &profile_defines(config.profile, pre_defines),
         include_paths,
-        strip_comments,
-        ignore_include,
+        config,
         0, // include_depth
     )
 }
@@ -138,32 +971,62 @@ fn preprocess_inner<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     path: T,
     pre_defines: &Defines<V>,
     include_paths: &[U],
-    strip_comments: bool,
-    ignore_include: bool,
+    config: &PreprocessConfig,
     include_depth: usize,
 ) -> Result<(PreprocessedText, Defines), Error> {
+    let (s, offset_map) = read_source(path.as_ref(), config)?;
+    let (mut ret, defines) = preprocess_str_inner(
+        &s,
+        path.as_ref(),
+        pre_defines,
+        include_paths,
+        config,
+        0, // resolve_depth
+        include_depth,
+    )
+    .map_err(|mut x| {
+        if let Some(ref map) = offset_map {
+            locate_original(&mut x, path.as_ref(), map);
+        }
+        x
+    })?;
+    if let Some(x) = offset_map {
+        let line_starts = line_starts(&s).into_iter().map(|pos| x.original(pos));
+        ret.records
+            .line_starts
+            .insert(PathBuf::from(path.as_ref()), line_starts.collect());
+        ret.records
+            .offset_maps
+            .insert(PathBuf::from(path.as_ref()), x);
+    }
+    Ok((ret, defines))
+}
 
-    let f = File::open(path.as_ref()).map_err(|x| Error::File {
+fn read_source(
+    path: &Path,
+    config: &PreprocessConfig,
+) -> Result<(String, Option<OffsetMap>), Error> {
+    let f = File::open(path).map_err(|x| Error::File {
         source: x,
-        path: PathBuf::from(path.as_ref()),
+        path: PathBuf::from(path),
     })?;
+    if let Some(max) = config.limits.max_input_size {
+        let len = f.metadata().map_err(|x| Error::File {
+            source: x,
+            path: PathBuf::from(path),
+        })?.len();
+        if len > max as u64 {
+            return Err(Error::ExceedInputSize(PathBuf::from(path)));
+        }
+    }
     let mut reader = BufReader::new(f);
-    let mut s = String::new();
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|x| Error::File {
+        source: x,
+        path: PathBuf::from(path),
+    })?;
 
-    if let Err(_) = reader.read_to_string(&mut s) {
-        Err(Error::ReadUtf8(PathBuf::from(path.as_ref())))
-    } else {
-        preprocess_str(
-            &s,
-            path,
-            pre_defines,
-            include_paths,
-            ignore_include,
-            strip_comments,
-            0, // resolve_depth
-            include_depth,
-        )
-    }
+    decode(bytes, config.fallback_encoding).ok_or_else(|| Error::ReadUtf8(PathBuf::from(path)))
 }
 
 struct SkipNodes<'a> {
@@ -189,22 +1052,322 @@ impl<'a> SkipNodes<'a> {
         }
     }
 
-    fn contains(&self, node: &RefNode<'a>) -> bool {
-        self.nodes.contains(node)
+    fn contains(&self, node: &RefNode<'a>) -> bool {
+        self.nodes.contains(node)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    ignore_include: bool,
+    strip_comments: bool,
+    resolve_depth: usize,
+    include_depth: usize,
+) -> Result<(PreprocessedText, Defines), Error> {
+    let config = PreprocessConfig {
+        strip_comments,
+        ignore_include,
+        ..Default::default()
+    };
+    preprocess_str_inner(
+        s,
+        path,
+        pre_defines,
+        include_paths,
+        &config,
+        resolve_depth,
+        include_depth,
+    )
+}
+
+pub fn preprocess_str_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+) -> Result<(PreprocessedText, Defines), Error> {
+    preprocess_str_inner(
+        s,
+        path,
+        &profile_defines(config.profile, pre_defines),
+        include_paths,
+        config,
+        0, // resolve_depth
+        0, // include_depth
+    )
+}
+
+/// Preprocess one part at a time
+///
+/// The source is read at once, but each part is preprocessed when the
+/// iterator is advanced.
+pub fn preprocess_stream_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+) -> Result<PreprocessStream, Error> {
+    let (s, offset_map) = read_source(path.as_ref(), config)?;
+    PreprocessStream::new(
+        s,
+        path.as_ref(),
+        offset_map,
+        pre_defines,
+        include_paths,
+        config,
+    )
+}
+
+/// Preprocess a string one part at a time
+pub fn preprocess_str_stream_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+) -> Result<PreprocessStream, Error> {
+    PreprocessStream::new(
+        String::from(s),
+        path.as_ref(),
+        None,
+        pre_defines,
+        include_paths,
+        config,
+    )
+}
+
+/// Iterator preprocessing a source one part at a time
+///
+/// Each part is preprocessed from top-level items of about 64 KiB of the
+/// source and owned by the caller, so the preprocessed text is not kept
+/// as a whole. A top-level `ifdef is a single item, so a source wrapped by
+/// an include guard is preprocessed as one part.
+///
+/// The text of each part begins at 0 and follows the previous part as if
+/// they were merged. The lines of the source are recorded in the first part,
+/// and `begin_keywords without `end_keywords is warned in the last part.
+pub struct PreprocessStream {
+    s: String,
+    path: PathBuf,
+    include_paths: Vec<PathBuf>,
+    config: PreprocessConfig,
+    offset_map: Option<OffsetMap>,
+    state: PreprocessState,
+    offset: usize,
+    line: u32,
+    started: bool,
+    finished: bool,
+}
+
+impl PreprocessStream {
+    fn new<U: AsRef<Path>, V: BuildHasher>(
+        s: String,
+        path: &Path,
+        offset_map: Option<OffsetMap>,
+        pre_defines: &Defines<V>,
+        include_paths: &[U],
+        config: &PreprocessConfig,
+    ) -> Result<Self, Error> {
+        check_source(&s, path, config, 0, 0)?;
+        let state = PreprocessState::new(&profile_defines(config.profile, pre_defines));
+        Ok(PreprocessStream {
+            s,
+            path: PathBuf::from(path),
+            include_paths: include_paths
+                .iter()
+                .map(|x| PathBuf::from(x.as_ref()))
+                .collect(),
+            config: config.clone(),
+            offset_map,
+            state,
+            offset: 0,
+            line: 1,
+            started: false,
+            finished: false,
+        })
+    }
+
+    /// Get macros defined by the preprocessed parts
+    pub fn defines(&self) -> &Defines {
+        &self.state.defines
+    }
+
+    fn part(&mut self) -> Result<PreprocessedText, Error> {
+        let PreprocessStream {
+            ref s,
+            ref path,
+            ref include_paths,
+            ref config,
+            ref offset_map,
+            ref mut state,
+            ..
+        } = *self;
+        config.limits.check()?;
+
+        let mut items = Vec::new();
+        let mut offset = self.offset;
+        let mut line = self.line;
+        let mut len = STREAM_PART_SIZE;
+        while offset < s.len() && offset - self.offset < STREAM_PART_SIZE {
+            let mut end = (offset + len).min(s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+            let span = unsafe {
+                Span::new_from_raw_offset(offset, line, &s[offset..end], SpanInfo::default())
+            };
+            set_limits(&config.limits);
+            let result = pp_parser_stream_next(span);
+            if let Some(e) = clear_limits() {
+                return Err(e);
+            }
+
+            // An item near the end of the span may be parsed differently in
+            // a longer span, and so may a macro usage followed by arguments
+            // which are not closed in the span. Plain text can be split
+            // anywhere but after `/`.
+            let complete = match result {
+                _ if end == s.len() => true,
+                Ok((ref rest, SourceDescription::NotDirective(_))) => {
+                    !rest.fragment().is_empty() || !s[..end].ends_with('/')
+                }
+                Ok((ref rest, SourceDescription::CompilerDirective(ref x))) => {
+                    let no_args = matches!(
+                        **x,
+                        CompilerDirective::TextMacroUsage(ref x) if x.nodes.2.is_none()
+                    );
+                    rest.fragment().len() >= STREAM_LOOKAHEAD
+                        && !(no_args && rest.fragment().starts_with('('))
+                }
+                Ok((ref rest, _)) => rest.fragment().len() >= STREAM_LOOKAHEAD,
+                Err(_) => false,
+            };
+            if !complete {
+                if !items.is_empty() {
+                    break;
+                }
+                len *= 2;
+                continue;
+            }
+
+            let (rest, x) = result.map_err(|x| parse_error(x, path, s))?;
+            offset = rest.location_offset();
+            line = rest.location_line();
+            items.push(x);
+        }
+
+        let mut ret = PreprocessedText::new();
+        if !self.started {
+            let line_starts = line_starts(s);
+            let line_starts = match offset_map {
+                Some(x) => line_starts.into_iter().map(|pos| x.original(pos)).collect(),
+                None => line_starts,
+            };
+            ret.records.line_starts.insert(path.clone(), line_starts);
+            if let Some(x) = offset_map {
+                ret.records.offset_maps.insert(path.clone(), x.clone());
+            }
+        }
+
+        let pp_text = PreprocessorText { nodes: (items,) };
+        preprocess_items(
+            &pp_text,
+            s,
+            path,
+            state,
+            &mut ret,
+            include_paths,
+            config,
+            0, // resolve_depth
+            0, // include_depth
+        )?;
+        if offset == s.len() {
+            state.finish(&mut ret, path);
+        }
+
+        self.offset = offset;
+        self.line = line;
+        self.started = true;
+        Ok(ret)
+    }
+}
+
+impl Iterator for PreprocessStream {
+    type Item = Result<PreprocessedText, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let ret = self.part();
+        self.finished = ret.is_err() || self.offset == self.s.len();
+        Some(ret.map_err(|mut x| {
+            if let Some(ref map) = self.offset_map {
+                locate_original(&mut x, &self.path, map);
+            }
+            x
+        }))
+    }
+}
+
+fn preprocess_str_inner<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    resolve_depth: usize,
+    include_depth: usize,
+) -> Result<(PreprocessedText, Defines), Error> {
+    check_source(s, path.as_ref(), config, resolve_depth, include_depth)?;
+
+    let mut state = PreprocessState::new(pre_defines);
+    let pp_text = parse_preprocessor_text(s, path.as_ref(), config)?;
+
+    let mut ret = PreprocessedText::new();
+
+    // Macro text is not a file, so only the lines of files are recorded.
+    if resolve_depth == 0 {
+        ret.records
+            .line_starts
+            .insert(PathBuf::from(path.as_ref()), line_starts(s));
+    }
+
+    preprocess_items(
+        &pp_text,
+        s,
+        path.as_ref(),
+        &mut state,
+        &mut ret,
+        include_paths,
+        config,
+        resolve_depth,
+        include_depth,
+    )?;
+
+    check_output_size(&ret.text, config)?;
+
+    // Macro text is not a file, so `begin_keywords is checked in only files.
+    if resolve_depth == 0 {
+        state.finish(&mut ret, path.as_ref());
     }
+
+    Ok((ret, state.defines))
 }
 
-pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+fn check_source(
     s: &str,
-    path: T,
-    pre_defines: &Defines<V>,
-    include_paths: &[U],
-    ignore_include: bool,
-    strip_comments: bool,
+    path: &Path,
+    config: &PreprocessConfig,
     resolve_depth: usize,
     include_depth: usize,
-) -> Result<(PreprocessedText, Defines), Error> {
-
+) -> Result<(), Error> {
     // IEEE1800-2017 Clause 22.4, page 675
     // A file included in the source using the `include compiler directive
     // may contain other `include compiler directives.
@@ -212,72 +1375,133 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     // Implementations may limit the maximum number of levels to which
     // include files can be nested, but the limit shall be at least 15.
     if include_depth > RECURSIVE_LIMIT {
-        return Err(Error::ExceedRecursiveLimit);
+        return Err(Error::ExceedRecursiveLimit {
+            location: location(path, s, 0),
+        });
     }
-
-    let mut skip = false;
-    let mut skip_whitespace = false;
-    let mut skip_nodes = SkipNodes::new();
-    let mut defines = HashMap::new();
-
-    let mut last_item_line = None;
-    let mut last_include_line = None;
-
-    // IEEE1800-2017 Clause 40.3.1, page 1121
-    // The following predefined `define macros represent basic real-time
-    // coverage capabilities accessible directly from SystemVerilog:
-    let sv_cov_pre_defines = [
-        ("SV_COV_START",        "0"),
-        ("SV_COV_STOP",         "1"),
-        ("SV_COV_RESET",        "2"),
-        ("SV_COV_CHECK",        "3"),
-        ("SV_COV_MODULE",       "10"),
-        ("SV_COV_HIER",         "11"),
-        ("SV_COV_ASSERTION",    "20"),
-        ("SV_COV_FSM_STATE",    "21"),
-        ("SV_COV_STATEMENT",    "22"),
-        ("SV_COV_TOGGLE",       "23"),
-        ("SV_COV_OVERFLOW",     "-2"),
-        ("SV_COV_ERROR",        "-1"),
-        ("SV_COV_NOCOV",        "0"),
-        ("SV_COV_OK",           "1"),
-        ("SV_COV_PARTIAL",      "2"),
-    ];
-    for (k, v) in sv_cov_pre_defines {
-        let define = Define {
-            identifier: k.to_string(),
-            arguments: Vec::new(),
-            text: Some(DefineText {text: v.to_string(), origin: None}),
-        };
-        defines.insert(k.to_string(), Some(define));
+    if let Some(max) = config.limits.max_include_depth {
+        if include_depth > max {
+            return Err(Error::ExceedIncludeDepth {
+                location: location(path, s, 0),
+            });
+        }
     }
 
-    for (k, v) in pre_defines {
-        defines.insert(k.clone(), (*v).clone());
+    // Macro text is an expansion, so it is limited as output.
+    if resolve_depth == 0 {
+        if let Some(max) = config.limits.max_input_size {
+            if s.len() > max {
+                return Err(Error::ExceedInputSize(PathBuf::from(path)));
+            }
+        }
+    } else {
+        check_output_size(s, config)?;
     }
+    config.limits.check()
+}
 
-    let span = Span::new_extra(&s, SpanInfo::default());
-    let (_, pp_text) = all_consuming(pp_parser)(span).map_err(|x| match x {
-        nom::Err::Incomplete(_) => Error::Preprocess(None),
-        nom::Err::Error(e) => {
-            if let Some(pos) = error_position(&e) {
-                Error::Preprocess(Some((PathBuf::from(path.as_ref()), pos)))
-            } else {
-                Error::Preprocess(None)
-            }
+/// State of preprocessing carried from a top-level item of a source to the next
+struct PreprocessState {
+    defines: Defines,
+    last_item_line: Option<u32>,
+    last_include_line: Option<u32>,
+    keywords: Vec<usize>,
+    translate_off: bool,
+}
+
+impl PreprocessState {
+    fn new<V: BuildHasher>(pre_defines: &Defines<V>) -> Self {
+        let mut defines = HashMap::new();
+
+        // IEEE1800-2017 Clause 40.3.1, page 1121
+        // The following predefined `define macros represent basic real-time
+        // coverage capabilities accessible directly from SystemVerilog:
+        let sv_cov_pre_defines = [
+            ("SV_COV_START",        "0"),
+            ("SV_COV_STOP",         "1"),
+            ("SV_COV_RESET",        "2"),
+            ("SV_COV_CHECK",        "3"),
+            ("SV_COV_MODULE",       "10"),
+            ("SV_COV_HIER",         "11"),
+            ("SV_COV_ASSERTION",    "20"),
+            ("SV_COV_FSM_STATE",    "21"),
+            ("SV_COV_STATEMENT",    "22"),
+            ("SV_COV_TOGGLE",       "23"),
+            ("SV_COV_OVERFLOW",     "-2"),
+            ("SV_COV_ERROR",        "-1"),
+            ("SV_COV_NOCOV",        "0"),
+            ("SV_COV_OK",           "1"),
+            ("SV_COV_PARTIAL",      "2"),
+        ];
+        for (k, v) in sv_cov_pre_defines {
+            let define = Define {
+                identifier: k.to_string(),
+                arguments: Vec::new(),
+                text: Some(DefineText {text: v.to_string(), origin: None}),
+            };
+            defines.insert(k.to_string(), Some(define));
         }
-        nom::Err::Failure(e) => {
-            if let Some(pos) = error_position(&e) {
-                Error::Preprocess(Some((PathBuf::from(path.as_ref()), pos)))
-            } else {
-                Error::Preprocess(None)
-            }
+
+        for (k, v) in pre_defines {
+            defines.insert(k.clone(), (*v).clone());
         }
-    })?;
 
-    let mut ret = PreprocessedText::new();
+        PreprocessState {
+            defines,
+            last_item_line: None,
+            last_include_line: None,
+            keywords: Vec::new(),
+            translate_off: false,
+        }
+    }
+
+    fn finish(&self, ret: &mut PreprocessedText, path: &Path) {
+        for pos in &self.keywords {
+            ret.warn(
+                PreprocessWarningKind::UnterminatedKeywords,
+                "`begin_keywords is not terminated by `end_keywords",
+                path,
+                *pos,
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn preprocess_items<T: AsRef<Path>, U: AsRef<Path>>(
+    pp_text: &PreprocessorText,
+    s: &str,
+    path: T,
+    state: &mut PreprocessState,
+    ret: &mut PreprocessedText,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    resolve_depth: usize,
+    include_depth: usize,
+) -> Result<(), Error> {
+    let strip_comments = config.strip_comments;
+    let ignore_include = config.ignore_include;
+    let including_path = path.as_ref();
+
+    let mut skip = false;
+    let mut skip_whitespace = false;
+    let mut skip_nodes = SkipNodes::new();
+    let mut conditionals: Vec<Vec<String>> = Vec::new();
+    let mut regions: Vec<ConditionalRegion> = Vec::new();
+
+    // An error ends preprocessing, so the state is written back only on success.
+    let mut defines = std::mem::take(&mut state.defines);
+    let mut last_item_line = state.last_item_line;
+    let mut last_include_line = state.last_include_line;
+    let mut keywords = std::mem::take(&mut state.keywords);
+    let mut translate_off = state.translate_off;
+    // Whether each `ifdef/`ifndef being visited is excluded by translate_off
+    let mut excluded_conditionals = Vec::new();
 
     for n in pp_text.into_iter().event() {
+        check_output_size(&ret.text, config)?;
+        config.limits.check()?;
+
         match n.clone() {
             NodeEvent::Enter(x) => {
                 if skip_nodes.contains(&x) {
@@ -294,20 +1518,69 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
             continue;
         }
 
+        // Text between translate_off and translate_on metacomments in active
+        // branches of files is replaced by whitespace keeping newlines.
+        if config.exclude_translate_off && resolve_depth == 0 {
+            let excluded = match n {
+                NodeEvent::Enter(RefNode::Comment(x)) if translate_off => {
+                    let locate: Locate = x.try_into().unwrap();
+                    if let Some(x) = Metacomment::parse(locate.str(s), path.as_ref(), locate.line) {
+                        translate_off = !x.is_translate_on();
+                    }
+                    translate_off
+                }
+                NodeEvent::Leave(RefNode::Comment(x)) if !translate_off => {
+                    let locate: Locate = x.try_into().unwrap();
+                    if let Some(x) = Metacomment::parse(locate.str(s), path.as_ref(), locate.line) {
+                        translate_off = x.is_translate_off();
+                    }
+                    false
+                }
+                NodeEvent::Enter(RefNode::IfdefDirective(_))
+                | NodeEvent::Enter(RefNode::IfndefDirective(_)) => {
+                    excluded_conditionals.push(translate_off);
+                    translate_off
+                }
+                // The end of `ifdef entered before translate_off is not excluded.
+                NodeEvent::Leave(RefNode::IfdefDirective(_))
+                | NodeEvent::Leave(RefNode::IfndefDirective(_)) => {
+                    excluded_conditionals.pop().unwrap_or(false)
+                }
+                NodeEvent::Enter(RefNode::Locate(x)) if translate_off => {
+                    let text: String = x
+                        .str(s)
+                        .bytes()
+                        .map(|x| if x == b'\n' { '\n' } else { ' ' })
+                        .collect();
+                    let range = Range::new(x.offset, x.offset + x.len);
+                    ret.push(&text, Some((path.as_ref(), range)));
+                    true
+                }
+                _ => translate_off,
+            };
+            if excluded {
+                continue;
+            }
+        }
+
         match n.clone() {
             NodeEvent::Enter(RefNode::SourceDescriptionNotDirective(x)) => {
                 let locate: Locate = x.try_into().unwrap();
                 if let Some(last_include_line) = last_include_line {
-                    if last_include_line == locate.line {
-                        return Err(Error::IncludeLine);
+                    if last_include_line == locate.line && !config.profile.include_line() {
+                        return Err(Error::IncludeLine {
+                            location: location(path.as_ref(), s, locate.offset),
+                        });
                     }
                 }
             }
             NodeEvent::Enter(RefNode::CompilerDirective(x)) => {
                 let locate: Locate = x.try_into().unwrap();
                 if let Some(last_include_line) = last_include_line {
-                    if last_include_line == locate.line {
-                        return Err(Error::IncludeLine);
+                    if last_include_line == locate.line && !config.profile.include_line() {
+                        return Err(Error::IncludeLine {
+                            location: location(path.as_ref(), s, locate.offset),
+                        });
                     }
                 }
             }
@@ -406,6 +1679,14 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
             NodeEvent::Leave(RefNode::EndcelldefineDriveCompilerDirective(_)) => {
                 skip_whitespace = false;
             }
+            NodeEvent::Enter(RefNode::ProtectedEnvelope(x)) => {
+                // Encrypted data is kept as is without macro expansion.
+                skip_nodes.push(x.into());
+                skip = true;
+                let locate: Locate = x.try_into().unwrap();
+                let range = Range::new(locate.offset, locate.offset + locate.len);
+                ret.push(locate.str(s), Some((path.as_ref(), range)));
+            }
             NodeEvent::Enter(RefNode::Pragma(x)) => {
                 let locate: Locate = x.try_into().unwrap();
                 let range = Range::new(locate.offset, locate.offset + locate.len);
@@ -426,6 +1707,7 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
             }
             NodeEvent::Enter(RefNode::KeywordsDirective(x)) => {
                 let locate: Locate = x.try_into().unwrap();
+                keywords.push(locate.offset);
                 let range = Range::new(locate.offset, locate.offset + locate.len);
                 ret.push(locate.str(&s), Some((path.as_ref(), range)));
                 skip_whitespace = true;
@@ -435,6 +1717,7 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
             }
             NodeEvent::Enter(RefNode::EndkeywordsDirective(x)) => {
                 let locate: Locate = x.try_into().unwrap();
+                keywords.pop();
                 let range = Range::new(locate.offset, locate.offset + locate.len);
                 ret.push(locate.str(&s), Some((path.as_ref(), range)));
                 skip_whitespace = true;
@@ -445,9 +1728,22 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
             NodeEvent::Enter(RefNode::UndefineCompilerDirective(x)) => {
                 let (_, _, ref name) = x.nodes;
                 let id = identifier((&name.nodes.0).into(), &s).unwrap();
-                defines.remove(&id);
-
                 let locate: Locate = x.try_into().unwrap();
+                if config.macro_database {
+                    ret.macros.record(&id).undefs.push(MacroSite {
+                        path: PathBuf::from(path.as_ref()),
+                        line: locate.line,
+                    });
+                }
+                if defines.remove(&id).is_none() && !is_predefined_text_macro(&id) {
+                    ret.warn(
+                        PreprocessWarningKind::UndefineUndefined,
+                        format!("`{}` is not defined", id),
+                        path.as_ref(),
+                        locate.offset,
+                    );
+                }
+
                 let range = Range::new(locate.offset, locate.offset + locate.len);
                 ret.push(locate.str(&s), Some((path.as_ref(), range)));
                 skip_whitespace = true;
@@ -471,24 +1767,55 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                 skip_nodes.push(keyword.into());
                 skip_nodes.push(ifid.into());
 
-                let ifid = identifier(ifid.into(), &s).unwrap();
+                let conditions: Vec<_> =
+                    std::iter::once(ifid).chain(elsif.iter().map(|x| &x.2)).collect();
+
+                if config.conditional_directives {
+                    let locate: Locate = x.try_into().unwrap();
+                    let macros = condition_macros(&conditions, s);
+                    ret.records.conditionals.push(ConditionalDirective {
+                        path: PathBuf::from(path.as_ref()),
+                        line: locate.line,
+                        macros: macros.clone(),
+                        enclosing: conditionals.concat(),
+                    });
+                    conditionals.push(macros);
+                }
+
+                if config.macro_database {
+                    record_conditions(&conditions, s, path.as_ref(), &mut ret.macros);
+                }
+
+                let predefined = is_predefined_condition(ifid, s);
                 let mut hit = false;
-                if defines.contains_key(&ifid) || is_predefined_text_macro(&ifid) {
+                let mut taken = None;
+                if ifdef_condition(ifid, s, &defines) {
                     hit = true;
+                    taken = Some(0);
                 } else {
                     skip_nodes.push(ifbody.into());
                 }
 
-                for x in elsif {
-                    let (_, ref keyword, ref elsifid, ref elsifbody) = x;
+                for (i, x) in elsif.iter().enumerate() {
+                    let (ref symbol, ref keyword, ref elsifid, ref elsifbody) = x;
                     skip_nodes.push(keyword.into());
                     skip_nodes.push(elsifid.into());
 
-                    let elsifid = identifier(elsifid.into(), &s).unwrap();
                     if hit {
                         skip_nodes.push(elsifbody.into());
-                    } else if defines.contains_key(&elsifid) || is_predefined_text_macro(&ifid) {
+                    } else if ifdef_condition(elsifid, s, &defines) {
                         hit = true;
+                        taken = Some(i + 1);
+                    } else if predefined {
+                        hit = true;
+                        taken = Some(i + 1);
+                        let locate: Locate = symbol.nodes.0;
+                        ret.warn(
+                            PreprocessWarningKind::ElsifPredefined,
+                            "`elsif is taken because the condition is a predefined macro",
+                            path.as_ref(),
+                            locate.offset,
+                        );
                     } else {
                         skip_nodes.push(elsifbody.into());
                     }
@@ -499,8 +1826,14 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                     skip_nodes.push(keyword.into());
                     if hit {
                         skip_nodes.push(elsebody.into());
+                    } else {
+                        taken = Some(elsif.len() + 1);
                     }
                 }
+
+                if config.conditional_regions {
+                    regions.push(conditional_region(x.into(), s, path.as_ref(), taken));
+                }
             }
             NodeEvent::Enter(RefNode::WhiteSpace(x)) if !skip_whitespace && !strip_comments => {
                 if let WhiteSpace::Space(_) = x {
@@ -509,34 +1842,70 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                     ret.push(locate.str(&s), Some((path.as_ref(), range)));
                 }
             }
-            NodeEvent::Enter(RefNode::Comment(x)) if !strip_comments => {
+            NodeEvent::Enter(RefNode::Comment(x)) => {
                 let locate: Locate = x.try_into().unwrap();
-                let range = Range::new(locate.offset, locate.offset + locate.len);
-                ret.push(locate.str(&s), Some((path.as_ref(), range)));
+                if config.metacomments {
+                    record_metacomment(ret, &locate, s, path.as_ref());
+                }
+                if !strip_comments {
+                    let range = Range::new(locate.offset, locate.offset + locate.len);
+                    ret.push(locate.str(&s), Some((path.as_ref(), range)));
+                }
             }
             NodeEvent::Enter(RefNode::IfndefDirective(x)) => {
                 let (_, ref keyword, ref ifid, ref ifbody, ref elsif, ref elsebody, _, _) = x.nodes;
                 skip_nodes.push(keyword.into());
                 skip_nodes.push(ifid.into());
 
-                let ifid = identifier(ifid.into(), &s).unwrap();
+                let conditions: Vec<_> =
+                    std::iter::once(ifid).chain(elsif.iter().map(|x| &x.2)).collect();
+
+                if config.conditional_directives {
+                    let locate: Locate = x.try_into().unwrap();
+                    let macros = condition_macros(&conditions, s);
+                    ret.records.conditionals.push(ConditionalDirective {
+                        path: PathBuf::from(path.as_ref()),
+                        line: locate.line,
+                        macros: macros.clone(),
+                        enclosing: conditionals.concat(),
+                    });
+                    conditionals.push(macros);
+                }
+
+                if config.macro_database {
+                    record_conditions(&conditions, s, path.as_ref(), &mut ret.macros);
+                }
+
+                let predefined = is_predefined_condition(ifid, s);
                 let mut hit = false;
-                if !defines.contains_key(&ifid) && !is_predefined_text_macro(&ifid) {
+                let mut taken = None;
+                if !ifdef_condition(ifid, s, &defines) {
                     hit = true;
+                    taken = Some(0);
                 } else {
                     skip_nodes.push(ifbody.into());
                 }
 
-                for x in elsif {
-                    let (_, ref keyword, ref elsifid, ref elsifbody) = x;
+                for (i, x) in elsif.iter().enumerate() {
+                    let (ref symbol, ref keyword, ref elsifid, ref elsifbody) = x;
                     skip_nodes.push(keyword.into());
                     skip_nodes.push(elsifid.into());
 
-                    let elsifid = identifier(elsifid.into(), &s).unwrap();
                     if hit {
                         skip_nodes.push(elsifbody.into());
-                    } else if defines.contains_key(&elsifid) || is_predefined_text_macro(&ifid) {
+                    } else if ifdef_condition(elsifid, s, &defines) {
                         hit = true;
+                        taken = Some(i + 1);
+                    } else if predefined {
+                        hit = true;
+                        taken = Some(i + 1);
+                        let locate: Locate = symbol.nodes.0;
+                        ret.warn(
+                            PreprocessWarningKind::ElsifPredefined,
+                            "`elsif is taken because the condition is a predefined macro",
+                            path.as_ref(),
+                            locate.offset,
+                        );
                     } else {
                         skip_nodes.push(elsifbody.into());
                     }
@@ -547,8 +1916,19 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                     skip_nodes.push(keyword.into());
                     if hit {
                         skip_nodes.push(elsebody.into());
+                    } else {
+                        taken = Some(elsif.len() + 1);
                     }
                 }
+
+                if config.conditional_regions {
+                    regions.push(conditional_region(x.into(), s, path.as_ref(), taken));
+                }
+            }
+            NodeEvent::Leave(RefNode::IfdefDirective(_))
+            | NodeEvent::Leave(RefNode::IfndefDirective(_)) => {
+                conditionals.pop();
+                close_conditional_region(&mut regions, &mut ret.records.regions);
             }
             NodeEvent::Enter(RefNode::TextMacroDefinition(x)) => {
                 skip_nodes.push(x.into());
@@ -598,6 +1978,30 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                         text: define_text,
                     };
 
+                    if config.macro_database {
+                        let locate: Locate = x.try_into().unwrap();
+                        ret.macros.record(&id).definitions.push(MacroDefinition {
+                            site: MacroSite {
+                                path: PathBuf::from(path.as_ref()),
+                                line: locate.line,
+                            },
+                            arguments: define.arguments.clone(),
+                            text: define.text.as_ref().map(|x| x.text.clone()),
+                        });
+                    }
+
+                    if let Some(old) = defines.get(&id) {
+                        if !is_same_define(old, &define) {
+                            let locate: Locate = x.try_into().unwrap();
+                            ret.warn(
+                                PreprocessWarningKind::Redefinition,
+                                format!("`{}` is redefined with different text", id),
+                                path.as_ref(),
+                                locate.offset,
+                            );
+                        }
+                    }
+
                     defines.insert(id, Some(define));
                 }
 
@@ -617,12 +2021,15 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                 // Only white space or a comment may appear on the same line as
                 // the `include compiler directive.
                 if let Some(last_item_line) = last_item_line {
-                    if last_item_line == locate.line {
-                        return Err(Error::IncludeLine);
+                    if last_item_line == locate.line && !config.profile.include_line() {
+                        return Err(Error::IncludeLine {
+                            location: location(path.as_ref(), s, locate.offset),
+                        });
                     }
                 }
 
-                let mut path = match x {
+                let angle_bracket = matches!(x, IncludeCompilerDirective::AngleBracket(_));
+                let path = match x {
                     IncludeCompilerDirective::DoubleQuote(x) => {
                         let (_, ref keyword, ref literal) = x.nodes;
                         skip_nodes.push(keyword.into());
@@ -646,12 +2053,17 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
 
                         if let Some((p, _, _)) = resolve_text_macro_usage(
                             x,
-                            s,
-                            path.as_ref(),
-                            &defines,
-                            include_paths,
-                            strip_comments,
-                            resolve_depth + 1,
+                            MacroContext {
+                                s,
+                                path: path.as_ref(),
+                                defines: &defines,
+                                include_paths,
+                                config,
+                                resolve_depth: resolve_depth + 1,
+                                expansions: &mut ret.expansions,
+                                warnings: &mut ret.warnings,
+                                macros: &mut ret.macros,
+                            },
                         )? {
                             let p = p.trim().trim_matches('"');
                             PathBuf::from(p)
@@ -672,32 +2084,56 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
                 //   by the language standard is searched. Relative path names are
                 //   interpreted relative to that location
                 //
-                // In this implementation, filenames enclosed in angle brackets are
-                // treated equivalently to those enclosed in double quotes.
-                if path.is_relative() && !path.exists() {
-                    for include_path in include_paths {
-                        let new_path = include_path.as_ref().join(&path);
-                        if new_path.exists() {
-                            path = new_path;
-                            break;
-                        }
-                    }
+                // In this implementation, the location of angle brackets is
+                // `system_include_paths`, and filenames not found there are
+                // searched as same as those enclosed in double quotes.
+                let name = path.clone();
+                let (path, fallback, tried) = resolve_include_path(
+                    path,
+                    angle_bracket,
+                    including_path,
+                    include_paths,
+                    config,
+                );
+                if fallback {
+                    ret.warn(
+                        PreprocessWarningKind::IncludeFallback,
+                        format!(
+                            "<{}> is not found in system include paths",
+                            name.to_string_lossy()
+                        ),
+                        including_path,
+                        locate.offset,
+                    );
                 }
 
-                let (include, new_defines) =
+                let (mut include, new_defines) =
                     preprocess_inner(
                         path,
                         &defines,
                         include_paths,
-                        strip_comments,
-                        false, // ignore_include
+                        config,
                         include_depth + 1).map_err(
                         |x| Error::Include {
                             source: Box::new(x),
+                            site: Box::new(IncludeSite {
+                                path: name,
+                                location: *location(including_path, s, locate.offset),
+                                tried,
+                            }),
                         },
                     )?;
                 defines = new_defines;
+                for x in &mut include.records.conditionals {
+                    x.enclosing.extend(conditionals.concat());
+                }
+                if config.line_markers {
+                    ret.records.includes.push((ret.text.len(), 1));
+                }
                 ret.merge(include);
+                if config.line_markers {
+                    ret.records.includes.push((ret.text.len(), 2));
+                }
             }
             NodeEvent::Enter(RefNode::TextMacroUsage(x)) => {
                 skip_nodes.push(x.into());
@@ -705,13 +2141,19 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
 
                 if let Some((text, origin, new_defines)) = resolve_text_macro_usage(
                     x,
-                    s,
-                    path.as_ref(),
-                    &defines,
-                    include_paths,
-                    strip_comments,
-                    resolve_depth + 1,
+                    MacroContext {
+                        s,
+                        path: path.as_ref(),
+                        defines: &defines,
+                        include_paths,
+                        config,
+                        resolve_depth: resolve_depth + 1,
+                        expansions: &mut ret.expansions,
+                        warnings: &mut ret.warnings,
+                        macros: &mut ret.macros,
+                    },
                 )? {
+                    record_macro_call(ret, x, s, path.as_ref(), text.len(), config);
                     ret.push(&text, origin);
                     defines = new_defines;
                 }
@@ -773,7 +2215,68 @@ pub fn preprocess_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
         }
     }
 
-    Ok((ret, defines))
+    state.defines = defines;
+    state.last_item_line = last_item_line;
+    state.last_include_line = last_include_line;
+    state.keywords = keywords;
+    state.translate_off = translate_off;
+    Ok(())
+}
+
+fn resolve_include_path<U: AsRef<Path>>(
+    path: PathBuf,
+    angle_bracket: bool,
+    including_path: &Path,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+) -> (PathBuf, bool, Vec<PathBuf>) {
+    if path.is_absolute() {
+        return (path.clone(), false, vec![path]);
+    }
+
+    let mut tried = Vec::new();
+    if angle_bracket {
+        for x in &config.system_include_paths {
+            let x = x.join(&path);
+            tried.push(x.clone());
+            if x.exists() {
+                return (x, false, tried);
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for x in &config.include_search {
+        match x {
+            IncludeSearch::WorkingDirectory => candidates.push(path.clone()),
+            IncludeSearch::IncludingFile => {
+                if let Some(x) = including_path.parent() {
+                    candidates.push(x.join(&path));
+                }
+            }
+            IncludeSearch::IncludePaths => {
+                for x in include_paths {
+                    candidates.push(x.as_ref().join(&path));
+                }
+            }
+        }
+    }
+
+    for x in candidates {
+        tried.push(x.clone());
+        if x.exists() {
+            return (x, angle_bracket, tried);
+        }
+    }
+    (path, false, tried)
+}
+
+fn is_same_define(a: &Option<Define>, b: &Define) -> bool {
+    let text = |x: &Option<DefineText>| x.as_ref().map(|x| String::from(x.text.trim()));
+    match a {
+        Some(a) => a.arguments == b.arguments && text(&a.text) == text(&b.text),
+        None => b.arguments.is_empty() && text(&b.text).unwrap_or_default().is_empty(),
+    }
 }
 
 fn identifier(node: RefNode, s: &str) -> Option<String> {
@@ -795,6 +2298,364 @@ fn identifier(node: RefNode, s: &str) -> Option<String> {
     None
 }
 
+fn ifdef_condition(node: &IfdefCondition, s: &str, defines: &Defines) -> bool {
+    match node {
+        IfdefCondition::TextMacroIdentifier(x) => is_defined(x.as_ref().into(), s, defines),
+        IfdefCondition::Paren(x) => {
+            let (_, ref x, _) = x.nodes.0.nodes;
+            ifdef_macro_expression(x, s, defines)
+        }
+    }
+}
+
+fn ifdef_macro_expression(node: &IfdefMacroExpression, s: &str, defines: &Defines) -> bool {
+    match node {
+        IfdefMacroExpression::TextMacroIdentifier(x) => {
+            is_defined(x.as_ref().into(), s, defines)
+        }
+        IfdefMacroExpression::Paren(x) => {
+            let (_, ref x, _) = x.nodes.0.nodes;
+            ifdef_macro_expression(x, s, defines)
+        }
+        IfdefMacroExpression::Not(x) => {
+            let (_, ref x) = x.nodes;
+            !ifdef_macro_expression(x, s, defines)
+        }
+        IfdefMacroExpression::Binary(x) => {
+            let (ref a, ref op, ref b) = x.nodes;
+            let a = ifdef_macro_expression(a, s, defines);
+            let b = ifdef_macro_expression(b, s, defines);
+            let op: Locate = op.nodes.0;
+            match op.str(s) {
+                "&&" => a && b,
+                "||" => a || b,
+                "->" => !a || b,
+                "<->" => a == b,
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+fn record_macro_call(
+    ret: &mut PreprocessedText,
+    node: &TextMacroUsage,
+    s: &str,
+    path: &Path,
+    len: usize,
+    config: &PreprocessConfig,
+) {
+    let begin = ret.text.len();
+    if config.macro_invocations {
+        ret.records.macro_calls.push(macro_call(node, s, begin, len));
+    }
+    if config.line_markers {
+        let locate: Locate = node.try_into().unwrap();
+        let range = Range::new(begin, begin + len);
+        ret.records
+            .macro_ranges
+            .push((range, PathBuf::from(path), locate.offset));
+    }
+}
+
+fn macro_call(node: &TextMacroUsage, s: &str, begin: usize, len: usize) -> MacroCall {
+    // The call text excludes the trailing whitespace
+    let mut call = None;
+    let mut whitespace = 0;
+    for x in RefNode::from(node).into_iter().event() {
+        match x {
+            NodeEvent::Enter(RefNode::WhiteSpace(_)) => whitespace += 1,
+            NodeEvent::Leave(RefNode::WhiteSpace(_)) => whitespace -= 1,
+            NodeEvent::Enter(RefNode::Locate(x)) if whitespace == 0 => {
+                let (start, _) = call.unwrap_or((x.offset, 0));
+                call = Some((start, x.offset + x.len));
+            }
+            _ => (),
+        }
+    }
+    let (start, end) = call.unwrap_or((0, 0));
+    MacroCall {
+        text: String::from(&s[start..end]),
+        begin,
+        end: begin + len,
+    }
+}
+
+fn close_conditional_region(regions: &mut Vec<ConditionalRegion>, top: &mut Vec<ConditionalRegion>) {
+    if let Some(region) = regions.pop() {
+        // Nested regions visited here are in the active branch
+        let parent = regions
+            .last_mut()
+            .and_then(|x| x.branches.iter_mut().find(|x| x.active));
+        match parent {
+            Some(x) => x.regions.push(region),
+            None => top.push(region),
+        }
+    }
+}
+
+fn conditional_region(node: RefNode, s: &str, path: &Path, taken: Option<usize>) -> ConditionalRegion {
+    let (kind, symbol, ifid, ifbody, elsif, elsebody, endif): (_, _, _, RefNode, _, _, _) =
+        match node {
+            RefNode::IfdefDirective(x) => {
+                let (ref a, _, ref b, ref c, ref d, ref e, ref f, _) = x.nodes;
+                (ConditionalBranchKind::Ifdef, a, b, c.into(), d, e, f)
+            }
+            RefNode::IfndefDirective(x) => {
+                let (ref a, _, ref b, ref c, ref d, ref e, ref f, _) = x.nodes;
+                (ConditionalBranchKind::Ifndef, a, b, c.into(), d, e, f)
+            }
+            _ => unreachable!(),
+        };
+
+    let mut branches = vec![(kind, symbol, Some(ifid), ifbody)];
+    for (symbol, _, elsifid, elsifbody) in elsif {
+        branches.push((
+            ConditionalBranchKind::Elsif,
+            symbol,
+            Some(elsifid),
+            elsifbody.into(),
+        ));
+    }
+    if let Some((symbol, _, elsebody)) = elsebody {
+        branches.push((ConditionalBranchKind::Else, symbol, None, elsebody.into()));
+    }
+
+    let end_line = endif.nodes.0.line;
+    let mut ret = ConditionalRegion {
+        path: PathBuf::from(path),
+        line: symbol.nodes.0.line,
+        end_line,
+        branches: Vec::new(),
+    };
+    for (i, (kind, symbol, condition, body)) in branches.iter().enumerate() {
+        let active = taken == Some(i);
+        // Nested regions of the active branch are recorded when visited
+        let mut regions = Vec::new();
+        if !active {
+            let mut depth = 0;
+            for x in body.clone().into_iter().event() {
+                match x {
+                    NodeEvent::Enter(x @ RefNode::IfdefDirective(_))
+                    | NodeEvent::Enter(x @ RefNode::IfndefDirective(_)) => {
+                        if depth == 0 {
+                            regions.push(conditional_region(x, s, path, None));
+                        }
+                        depth += 1;
+                    }
+                    NodeEvent::Leave(RefNode::IfdefDirective(_))
+                    | NodeEvent::Leave(RefNode::IfndefDirective(_)) => depth -= 1,
+                    _ => (),
+                }
+            }
+        }
+        let next = match branches.get(i + 1) {
+            Some((_, x, _, _)) => x.nodes.0.line,
+            None => end_line,
+        };
+        ret.branches.push(ConditionalBranch {
+            kind: *kind,
+            condition: condition.map(|x| String::from(get_str(x.into(), s).trim())),
+            line: symbol.nodes.0.line,
+            end_line: next,
+            active,
+            regions,
+        });
+    }
+    ret
+}
+
+fn condition_macros(conditions: &[&IfdefCondition], s: &str) -> Vec<String> {
+    let mut ret = Vec::new();
+    for x in conditions {
+        for x in *x {
+            if let RefNode::TextMacroIdentifier(x) = x {
+                let id = identifier(x.into(), s).unwrap();
+                if !ret.contains(&id) {
+                    ret.push(id);
+                }
+            }
+        }
+    }
+    ret
+}
+
+fn record_conditions(
+    conditions: &[&IfdefCondition],
+    s: &str,
+    path: &Path,
+    macros: &mut MacroDatabase,
+) {
+    for x in conditions {
+        for x in *x {
+            if let RefNode::TextMacroIdentifier(x) = x {
+                let locate: Locate = x.try_into().unwrap();
+                let id = identifier(x.into(), s).unwrap();
+                macros.record(&id).usages.push(MacroUsage {
+                    site: MacroSite {
+                        path: PathBuf::from(path),
+                        line: locate.line,
+                    },
+                    kind: MacroUsageKind::Condition,
+                    arguments: None,
+                    arity_mismatch: false,
+                });
+            }
+        }
+    }
+}
+
+fn location(path: &Path, s: &str, offset: usize) -> Box<Location> {
+    let head = &s.as_bytes()[..offset.min(s.len())];
+    let line_start = head.iter().rposition(|x| *x == b'\n').map_or(0, |x| x + 1);
+    Box::new(Location {
+        path: PathBuf::from(path),
+        offset,
+        line: head.iter().filter(|x| **x == b'\n').count() + 1,
+        column: head.len() - line_start + 1,
+    })
+}
+
+fn line_starts(s: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(s.match_indices('\n').map(|(x, _)| x + 1))
+        .collect()
+}
+
+/// Convert locations in the decoded text of `path` to the original bytes
+///
+/// Errors in included files are kept because they are converted by the
+/// preprocessing of the files.
+fn locate_original(error: &mut Error, path: &Path, map: &OffsetMap) {
+    let location = match error {
+        Error::Include { site, .. } => Some(&mut site.location),
+        Error::Macro {
+            source, location, ..
+        } => {
+            locate_original(source, path, map);
+            Some(location.as_mut())
+        }
+        Error::Parse(x) | Error::Preprocess(x) => x.as_deref_mut(),
+        Error::DefineArgNotFound { location, .. }
+        | Error::DefineNotFound { location, .. }
+        | Error::DefineNoArgs { location, .. }
+        | Error::ExceedRecursiveLimit { location }
+        | Error::IncludeLine { location }
+        | Error::ExceedIncludeDepth { location } => Some(location.as_mut()),
+        _ => None,
+    };
+    if let Some(x) = location {
+        if x.path == path {
+            map.locate(x);
+        }
+    }
+}
+
+/// Replace locations in an expanded text by the call site
+///
+/// Errors in included files are kept because they are located in the files.
+fn relocate(error: Error, call_site: &Location) -> Error {
+    let location = Box::new(call_site.clone());
+    match error {
+        Error::Include { source, mut site } => {
+            site.location = call_site.clone();
+            Error::Include { source, site }
+        }
+        Error::Macro {
+            source, identifier, ..
+        } => Error::Macro {
+            source: Box::new(relocate(*source, call_site)),
+            identifier,
+            location,
+        },
+        Error::Preprocess(Some(_)) => Error::Preprocess(Some(location)),
+        Error::DefineArgNotFound { identifier, .. } => Error::DefineArgNotFound {
+            identifier,
+            location,
+        },
+        Error::DefineNotFound { identifier, .. } => Error::DefineNotFound {
+            identifier,
+            location,
+        },
+        Error::DefineNoArgs { identifier, .. } => Error::DefineNoArgs {
+            identifier,
+            location,
+        },
+        Error::ExceedRecursiveLimit { .. } => Error::ExceedRecursiveLimit { location },
+        Error::IncludeLine { .. } => Error::IncludeLine { location },
+        x => x,
+    }
+}
+
+fn check_output_size(s: &str, config: &PreprocessConfig) -> Result<(), Error> {
+    match config.limits.max_output_size {
+        Some(max) if s.len() > max => Err(Error::ExceedOutputSize),
+        _ => Ok(()),
+    }
+}
+
+fn parse_preprocessor_text(
+    s: &str,
+    path: &Path,
+    config: &PreprocessConfig,
+) -> Result<PreprocessorText, Error> {
+    let span = Span::new_extra(s, SpanInfo::default());
+    set_limits(&config.limits);
+    let result = all_consuming(pp_parser)(span);
+    if let Some(e) = clear_limits() {
+        return Err(e);
+    }
+    let (_, pp_text) = result.map_err(|x| parse_error(x, path, s))?;
+    Ok(pp_text)
+}
+
+fn parse_error(x: nom::Err<GreedyError<Span, ErrorKind>>, path: &Path, s: &str) -> Error {
+    let pos = match x {
+        nom::Err::Incomplete(_) => None,
+        nom::Err::Error(e) => error_position(&e),
+        nom::Err::Failure(e) => error_position(&e),
+    };
+    Error::Preprocess(pos.map(|pos| location(path, s, pos)))
+}
+
+fn record_metacomment(ret: &mut PreprocessedText, locate: &Locate, s: &str, path: &Path) {
+    if let Some(x) = Metacomment::parse(locate.str(s), path, locate.line) {
+        ret.records.metacomments.push(x);
+    }
+}
+
+// Macros predefined by the profile and overridden by pre_defines.
+// They are seeded only at the top level so that `undef is kept in included files.
+fn profile_defines<V: BuildHasher>(profile: Profile, pre_defines: &Defines<V>) -> Defines {
+    let mut defines = HashMap::new();
+    for (k, v) in profile.predefined_macros() {
+        let define = Define {
+            identifier: k.to_string(),
+            arguments: Vec::new(),
+            text: v.map(|x| DefineText::new(x.to_string(), None)),
+        };
+        defines.insert(k.to_string(), Some(define));
+    }
+    for (k, v) in pre_defines {
+        defines.insert(k.clone(), (*v).clone());
+    }
+    defines
+}
+
+fn is_predefined_condition(node: &IfdefCondition, s: &str) -> bool {
+    match node {
+        IfdefCondition::TextMacroIdentifier(x) => {
+            is_predefined_text_macro(&identifier(x.as_ref().into(), s).unwrap())
+        }
+        IfdefCondition::Paren(_) => false,
+    }
+}
+
+fn is_defined(node: RefNode, s: &str, defines: &Defines) -> bool {
+    let id = identifier(node, s).unwrap();
+    defines.contains_key(&id) || is_predefined_text_macro(&id)
+}
+
 fn get_str(node: RefNode, s: &str) -> String {
     let mut ret = String::from("");
     for x in node {
@@ -903,20 +2764,43 @@ fn split_text(s: &str) -> Vec<String> {
     ret
 }
 
-fn resolve_text_macro_usage<T: AsRef<Path>, U: AsRef<Path>>(
+// Arguments of resolve_text_macro_usage() except for the macro usage itself
+struct MacroContext<'a, U: AsRef<Path>> {
+    s: &'a str,
+    path: &'a Path,
+    defines: &'a Defines,
+    include_paths: &'a [U],
+    config: &'a PreprocessConfig,
+    resolve_depth: usize,
+    expansions: &'a mut Vec<MacroExpansion>,
+    warnings: &'a mut Vec<PreprocessWarning>,
+    macros: &'a mut MacroDatabase,
+}
+
+fn resolve_text_macro_usage<U: AsRef<Path>>(
     x: &TextMacroUsage,
-    s: &str,
-    path: T,
-    defines: &Defines,
-    include_paths: &[U],
-    strip_comments: bool,
-    resolve_depth: usize,
+    cx: MacroContext<U>,
 ) -> Result<Option<(String, Option<(PathBuf, Range)>, Defines)>, Error> {
+    let MacroContext {
+        s,
+        path,
+        defines,
+        include_paths,
+        config,
+        resolve_depth,
+        expansions,
+        warnings,
+        macros,
+    } = cx;
     let (_, ref name, ref args) = x.nodes;
     let id = identifier((&name.nodes.0).into(), &s).unwrap();
+    let locate: Locate = x.try_into().unwrap();
+    let call_site = location(path, s, locate.offset);
 
     if resolve_depth > RECURSIVE_LIMIT {
-        return Err(Error::ExceedRecursiveLimit);
+        return Err(Error::ExceedRecursiveLimit {
+            location: call_site,
+        });
     }
 
     let mut args_str = String::from("");
@@ -941,11 +2825,37 @@ fn resolve_text_macro_usage<T: AsRef<Path>, U: AsRef<Path>>(
     }
 
     let define = defines.get(&id);
+    let site = MacroSite {
+        path: PathBuf::from(path),
+        line: locate.line,
+    };
+
+    if config.macro_database {
+        if let Some(define) = define {
+            let arity_mismatch = match define {
+                Some(define) => {
+                    !define.arguments.is_empty() && actual_args.len() > define.arguments.len()
+                }
+                None => false,
+            };
+            macros.record(&id).usages.push(MacroUsage {
+                site: site.clone(),
+                kind: MacroUsageKind::Expansion,
+                arguments: if no_args { None } else { Some(actual_args.len()) },
+                arity_mismatch,
+            });
+        }
+    }
+
     if let Some(Some(define)) = define {
         let mut arg_map = HashMap::new();
+        let mut arg_list = Vec::new();
 
         if !define.arguments.is_empty() && no_args {
-            return Err(Error::DefineNoArgs(define.identifier.clone()));
+            return Err(Error::DefineNoArgs {
+                identifier: define.identifier.clone(),
+                location: call_site,
+            });
         }
 
         for (i, (arg, default)) in define.arguments.iter().enumerate() {
@@ -962,13 +2872,32 @@ fn resolve_text_macro_usage<T: AsRef<Path>, U: AsRef<Path>>(
                     if let Some(default) = default {
                         default
                     } else {
-                        return Err(Error::DefineArgNotFound(String::from(arg)));
+                        return Err(Error::DefineArgNotFound {
+                            identifier: String::from(arg),
+                            location: call_site,
+                        });
                     }
                 }
             };
             arg_map.insert(String::from(arg), value);
+            arg_list.push((String::from(arg), String::from(value)));
         }
 
+        let mut expansion = if config.expand_trace {
+            Some(MacroExpansion {
+                name: id.clone(),
+                path: PathBuf::from(path),
+                offset: locate.offset,
+                line: locate.line,
+                arguments: arg_list,
+                body: String::new(),
+                nested: Vec::new(),
+                text: String::new(),
+            })
+        } else {
+            None
+        };
+
         // restore () for textmacro without arguments
         let paren = if define.arguments.is_empty() {
             Some(args_str)
@@ -998,28 +2927,57 @@ fn resolve_text_macro_usage<T: AsRef<Path>, U: AsRef<Path>>(
                 replaced.push_str(&paren);
             }
 
-            let (replaced, new_defines) = preprocess_str(
+            let config_inner = PreprocessConfig {
+                ignore_include: false,
+                ..config.clone()
+            };
+            let (mut expanded, new_defines) = preprocess_str_inner(
                 &replaced,
-                path.as_ref(),
+                path,
                 &defines,
                 include_paths,
-                false,
-                strip_comments,
+                &config_inner,
                 resolve_depth,
                 0, // include_depth
-            )?;
+            )
+            .map_err(|x| Error::Macro {
+                source: Box::new(relocate(x, &call_site)),
+                identifier: id.clone(),
+                location: call_site.clone(),
+            })?;
+            // Positions in the expanded text are replaced by the call site.
+            for mut warning in expanded.warnings.drain(..) {
+                warning.origin = Some((PathBuf::from(path), locate.offset));
+                warnings.push(warning);
+            }
+            let mut expanded_macros = std::mem::take(&mut expanded.macros);
+            expanded_macros.relocate(&site);
+            macros.merge(expanded_macros);
+            if let Some(mut expansion) = expansion {
+                expansion.body = replaced;
+                expansion.nested = std::mem::take(&mut expanded.expansions);
+                expansion.text = String::from(expanded.text());
+                expansions.push(expansion);
+            }
+            let replaced = expanded;
             Ok(Some((
                 String::from(replaced.text()),
                 text.origin.clone(),
                 new_defines,
             )))
         } else {
+            if let Some(expansion) = expansion.take() {
+                expansions.push(expansion);
+            }
             Ok(None)
         }
     } else if define.is_some() {
         Ok(None)
     } else {
-        Err(Error::DefineNotFound(id))
+        Err(Error::DefineNotFound {
+            identifier: id,
+            location: call_site,
+        })
     }
 }
 
@@ -1059,6 +3017,26 @@ mod tests {
         )
     }
 
+    // Include and macro expansion stack of an error, and the innermost error
+    fn error_stack(error: &Error) -> (Vec<String>, &Error) {
+        let mut stack = Vec::new();
+        let mut error = error;
+        loop {
+            match error {
+                Error::Include { source, site } => {
+                    let location = &site.location;
+                    stack.push(format!("{} {}:{}", site.path.display(), location.line, location.column));
+                    error = source;
+                }
+                Error::Macro { source, identifier, location } => {
+                    stack.push(format!("`{} {}:{}", identifier, location.line, location.column));
+                    error = source;
+                }
+                _ => return (stack, error),
+            }
+        }
+    }
+
     #[test]
     fn escaped_identifier() { // {{{
         let (ret, _) = preprocess_usualargs("escaped_identifier.sv").unwrap();
@@ -1072,11 +3050,13 @@ mod tests {
     #[allow(non_snake_case)]
     fn err_DefineNoArgs() { // {{{
         match preprocess_usualargs("err_DefineNoArgs.sv").unwrap_err() {
-            Error::DefineNoArgs(identifier) => {
+            Error::DefineNoArgs { identifier, location } => {
                 assert_eq!(
                     identifier,
                     String::from("A")
                 );
+                assert_eq!(location.path, PathBuf::from(testfile_path("err_DefineNoArgs.sv")));
+                assert_eq!((location.line, location.column), (3, 1));
             }
             _ => {
                 panic!("Error::DefineNoArgs not raised.");
@@ -1088,11 +3068,13 @@ mod tests {
     #[allow(non_snake_case)]
     fn err_DefineNotFound() { // {{{
         match preprocess_usualargs("err_DefineNotFound.sv").unwrap_err() {
-            Error::DefineNotFound(identifier) => {
+            Error::DefineNotFound { identifier, location } => {
                 assert_eq!(
                     identifier,
                     String::from("A")
                 );
+                assert_eq!(location.path, PathBuf::from(testfile_path("err_DefineNotFound.sv")));
+                assert_eq!((location.line, location.column), (2, 1));
             }
             _ => {
                 panic!("Error::DefineNotFound not raised.");
@@ -1104,11 +3086,13 @@ mod tests {
     #[allow(non_snake_case)]
     fn err_DefineArgNotFound() { // {{{
         match preprocess_usualargs("err_DefineArgNotFound.sv").unwrap_err() {
-            Error::DefineArgNotFound(identifier) => {
+            Error::DefineArgNotFound { identifier, location } => {
                 assert_eq!(
                     identifier,
                     String::from("c")
                 );
+                assert_eq!(location.path, PathBuf::from(testfile_path("err_DefineArgNotFound.sv")));
+                assert_eq!((location.line, location.column), (4, 1));
             }
             _ => {
                 panic!("Error::DefineArgNotFound not raised.");
@@ -1295,6 +3279,24 @@ mod tests {
         assert_eq!(ret.origin(70).unwrap().1, 124);
     } // }}}
 
+    #[test]
+    fn ifdef_expression() { // {{{
+        let mut defines = HashMap::new();
+        defines.insert(String::from("A"), None);
+        let (ret, _) = preprocess(
+            testfile_path("ifdef_expression.sv"),
+            &defines,
+            &[] as &[String],
+            false, // strip_comments
+            false, // ignore_include
+        )
+        .unwrap();
+        assert_eq!(
+            ret.text(),
+            testfile_contents("expected/ifdef_expression.sv")
+        )
+    } // }}}
+
     #[test]
     fn ifndef_undefined() { // {{{
         let (ret, _) = preprocess_usualargs("ifndef_undefined.sv").unwrap();
@@ -1304,6 +3306,226 @@ mod tests {
         );
     } // }}}
 
+    #[test]
+    fn include_relative() { // {{{
+        let (ret, _) = preprocess_usualargs("include_relative.sv").unwrap();
+        assert_eq!(
+            ret.text(),
+            testfile_contents("expected/include_relative.sv")
+        );
+
+        let config = PreprocessConfig {
+            include_search: vec![IncludeSearch::IncludePaths],
+            ..Default::default()
+        };
+        let ret = preprocess_with_config(
+            testfile_path("include_relative.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        );
+        assert!(ret.is_err());
+    } // }}}
+
+    #[test]
+    fn include_system() { // {{{
+        let config = PreprocessConfig {
+            system_include_paths: vec![PathBuf::from(testfile_path("include_system"))],
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("include_system.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            ret.text(),
+            testfile_contents("expected/include_system.sv")
+        );
+    } // }}}
+
+    #[test]
+    fn warnings() { // {{{
+        let (ret, _) = preprocess_usualargs("warnings.sv").unwrap();
+        let contents = testfile_contents("warnings.sv");
+        let warnings: Vec<_> = ret
+            .warnings()
+            .iter()
+            .map(|x| {
+                let (path, pos) = x.origin.as_ref().unwrap();
+                assert_eq!(path, &PathBuf::from(testfile_path("warnings.sv")));
+                (x.code(), contents[..*pos].matches('\n').count() + 1)
+            })
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                ("PP001", 3),
+                ("PP002", 4),
+                ("PP003", 7),
+                ("PP004", 9),
+                ("PP005", 10),
+            ]
+        );
+        assert_eq!(
+            format!("{}", ret.warnings()[1]),
+            "warning[PP002]: `B` is not defined"
+        );
+    } // }}}
+
+    #[test]
+    fn macro_database() { // {{{
+        let config = PreprocessConfig {
+            macro_database: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("macro_database.sv"),
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        let path = PathBuf::from(testfile_path("macro_database.sv"));
+        let site = |line| MacroSite {
+            path: path.clone(),
+            line,
+        };
+
+        let add = ret.macros().get("ADD").unwrap();
+        let lines = |x: &[MacroSite]| x.iter().map(|x| x.line).collect::<Vec<_>>();
+        assert_eq!(
+            lines(&add.definitions.iter().map(|x| x.site.clone()).collect::<Vec<_>>()),
+            vec![2, 15]
+        );
+        assert_eq!(lines(&add.undefs), vec![14]);
+        assert_eq!(
+            add.usages
+                .iter()
+                .map(|x| (x.site.line, x.arguments))
+                .collect::<Vec<_>>(),
+            vec![(9, Some(2)), (10, Some(3)), (12, Some(2))]
+        );
+        assert_eq!(
+            ret.macros().get("GUARD").unwrap().usages[0].kind,
+            MacroUsageKind::Condition
+        );
+
+        assert_eq!(
+            ret.macros().issues(),
+            vec![
+                MacroIssue::ArityMismatch {
+                    name: String::from("ADD"),
+                    site: site(10),
+                },
+                MacroIssue::UnusedArgument {
+                    name: String::from("IGNORE"),
+                    argument: String::from("b"),
+                    site: site(3),
+                },
+                MacroIssue::Unused {
+                    name: String::from("UNUSED"),
+                    site: site(1),
+                },
+            ]
+        );
+
+        let (ret, _) = preprocess_usualargs("macro_database.sv").unwrap();
+        assert!(ret.macros().get("ADD").is_none());
+    } // }}}
+
+    #[test]
+    fn line_markers() { // {{{
+        let config = PreprocessConfig {
+            line_markers: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("line_markers.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        let top = testfile_path("line_markers.sv");
+        let included = testfile_path("included.svh");
+        assert_eq!(
+            ret.text_with_line_markers(),
+            testfile_contents("expected/line_markers.sv")
+                .replace("TOP", &top)
+                .replace("INCLUDED", &included)
+        );
+    } // }}}
+
+    #[test]
+    fn line_markers_escape() { // {{{
+        let config = PreprocessConfig {
+            line_markers: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_str_with_config(
+            "module a;\nendmodule\n",
+            r#"C:\src\"a".sv"#,
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            ret.text_with_line_markers(),
+            concat!(
+                r#"`line 1 "C:\\src\\\"a\".sv" 0"#,
+                "\nmodule a;\nendmodule\n"
+            )
+        );
+    } // }}}
+
+    #[test]
+    fn conditional_regions() { // {{{
+        let config = PreprocessConfig {
+            conditional_regions: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("conditional_regions.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        let regions = ret.conditional_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].line, regions[0].end_line), (2, 14));
+
+        let branches: Vec<_> = regions[0]
+            .branches
+            .iter()
+            .map(|x| (x.kind, x.condition.as_deref(), x.line, x.end_line, x.active))
+            .collect();
+        assert_eq!(
+            branches,
+            vec![
+                (ConditionalBranchKind::Ifdef, Some("A"), 2, 8, true),
+                (ConditionalBranchKind::Elsif, Some("(C && D)"), 8, 12, false),
+                (ConditionalBranchKind::Else, None, 12, 14, false),
+            ]
+        );
+
+        let guards = |line| -> Vec<_> {
+            ret.conditional_guards(testfile_path("conditional_regions.sv"), line)
+                .iter()
+                .map(|x| (x.line, x.active))
+                .collect()
+        };
+        assert_eq!(guards(1), vec![]);
+        assert_eq!(guards(4), vec![(2, true), (3, true)]);
+        assert_eq!(guards(6), vec![(2, true), (5, false)]);
+        assert_eq!(guards(10), vec![(8, false), (9, false)]);
+        assert_eq!(guards(13), vec![(12, false)]);
+    } // }}}
+
     #[test]
     fn include_ignore() { // {{{
         let include_paths = [testfile_path("")];
@@ -1360,14 +3582,48 @@ mod tests {
 
     #[test]
     fn include_quoted_a() { // {{{
-        let ret = preprocess_usualargs("include_quoted_a.sv");
-        assert_eq!(format!("{:?}", ret), "Err(Include { source: File { source: Os { code: 2, kind: NotFound, message: \"No such file or directory\" }, path: \"`PATH\" } })");
+        match preprocess_usualargs("include_quoted_a.sv").unwrap_err() {
+            Error::Include { source, site } => {
+                assert_eq!(format!("{:?}", source), "File { source: Os { code: 2, kind: NotFound, message: \"No such file or directory\" }, path: \"`PATH\" }");
+                assert_eq!(site.path, PathBuf::from("`PATH"));
+                assert_eq!(site.location.path, PathBuf::from(testfile_path("include_quoted_a.sv")));
+                assert_eq!((site.location.line, site.location.column), (4, 1));
+                assert_eq!(
+                    site.tried,
+                    vec![
+                        PathBuf::from("`PATH"),                    // WorkingDirectory
+                        PathBuf::from(testfile_path("`PATH")),     // IncludingFile
+                        PathBuf::from(testfile_path("`PATH")),     // IncludePaths
+                    ]
+                );
+            }
+            x => {
+                panic!("Error::Include not raised: {:?}", x);
+            }
+        };
     } // }}}
 
     #[test]
     fn include_quoted_b() { // {{{
-        let ret = preprocess_usualargs("include_quoted_b.sv");
-        assert_eq!(format!("{:?}", ret), "Err(Include { source: File { source: Os { code: 2, kind: NotFound, message: \"No such file or directory\" }, path: \"`PATH\" } })");
+        match preprocess_usualargs("include_quoted_b.sv").unwrap_err() {
+            Error::Include { source, site } => {
+                assert_eq!(format!("{:?}", source), "File { source: Os { code: 2, kind: NotFound, message: \"No such file or directory\" }, path: \"`PATH\" }");
+                assert_eq!(site.path, PathBuf::from("`PATH"));
+                assert_eq!(site.location.path, PathBuf::from(testfile_path("include_quoted_b.sv")));
+                assert_eq!((site.location.line, site.location.column), (4, 1));
+                assert_eq!(
+                    site.tried,
+                    vec![
+                        PathBuf::from("`PATH"),                    // WorkingDirectory
+                        PathBuf::from(testfile_path("`PATH")),     // IncludingFile
+                        PathBuf::from(testfile_path("`PATH")),     // IncludePaths
+                    ]
+                );
+            }
+            x => {
+                panic!("Error::Include not raised: {:?}", x);
+            }
+        };
     } // }}}
 
     #[test]
@@ -1390,13 +3646,146 @@ mod tests {
 
     #[test]
     fn include_recursive() { // {{{
-        let ret = preprocess_usualargs("include_recursive.svh");
-        let expected = format!(
-            "Err({}ExceedRecursiveLimit{})",
-            "Include { source: ".repeat(RECURSIVE_LIMIT+1),
-            " }".repeat(RECURSIVE_LIMIT+1),
+        let ret = preprocess_usualargs("include_recursive.svh").unwrap_err();
+        let (stack, error) = error_stack(&ret);
+        assert_eq!(stack, vec!["include_recursive.svh 2:1"; RECURSIVE_LIMIT+1]);
+        match error {
+            Error::ExceedRecursiveLimit { location } => {
+                assert_eq!(location.path, PathBuf::from(testfile_path("include_recursive.svh")));
+                assert_eq!((location.line, location.column), (1, 1));
+            }
+            x => {
+                panic!("Error::ExceedRecursiveLimit not raised: {:?}", x);
+            }
+        }
+    } // }}}
+
+    #[test]
+    fn include_recursive_limits() { // {{{
+        let config = PreprocessConfig {
+            limits: Limits {
+                max_include_depth: Some(3),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ret = preprocess_with_config(
+            testfile_path("include_recursive.svh"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap_err();
+        let (stack, error) = error_stack(&ret);
+        assert_eq!(stack, vec!["include_recursive.svh 2:1"; 4]);
+        assert!(matches!(error, Error::ExceedIncludeDepth { .. }));
+    } // }}}
+
+    #[test]
+    fn limits() { // {{{
+        let src = concat!(
+            "`define A0 xxxxxxxx\n",
+            "`define A1 `A0 `A0 `A0 `A0\n",
+            "`define A2 `A1 `A1 `A1 `A1\n",
+            "`define A3 `A2 `A2 `A2 `A2\n",
+            "`A3\n",
+        );
+        let preprocess_limits = |limits: Limits| {
+            let config = PreprocessConfig {
+                limits,
+                ..Default::default()
+            };
+            preprocess_str_with_config(src, "", &HashMap::new(), &[] as &[String], &config)
+        };
+
+        let (ret, _) = preprocess_limits(Limits::default()).unwrap();
+        assert_eq!(ret.text().len(), 677);
+
+        let ret = preprocess_limits(Limits {
+            max_input_size: Some(100),
+            ..Default::default()
+        });
+        assert_eq!(format!("{:?}", ret), "Err(ExceedInputSize(\"\"))");
+
+        let ret = preprocess_limits(Limits {
+            max_output_size: Some(200),
+            ..Default::default()
+        });
+        let ret = ret.unwrap_err();
+        assert_eq!(error_stack(&ret).0, ["`A3 5:1"]);
+        assert!(matches!(ret.innermost(), Error::ExceedOutputSize));
+
+        let ret = preprocess_limits(Limits {
+            deadline: Some(std::time::Instant::now()),
+            ..Default::default()
+        });
+        assert_eq!(format!("{:?}", ret), "Err(ExceedDeadline)");
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let ret = preprocess_limits(Limits {
+            cancellation: Some(cancellation),
+            ..Default::default()
+        });
+        assert_eq!(format!("{:?}", ret), "Err(Cancelled)");
+
+        let ret = preprocess_with_config(
+            testfile_path("macro_basic.sv"),
+            &HashMap::new(),
+            &[] as &[String],
+            &PreprocessConfig {
+                limits: Limits {
+                    max_input_size: Some(10),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            format!("{:?}", ret),
+            format!("Err(ExceedInputSize({:?}))", PathBuf::from(testfile_path("macro_basic.sv")))
         );
-        assert_eq!(format!("{:?}", ret), expected);
+    } // }}}
+
+    #[test]
+    fn stream() { // {{{
+        let mut src = String::from("`define A(x) x\n");
+        for i in 0..1000 {
+            src.push_str(&format!("// {}\nmodule m{}; `A(wire w); endmodule\n", i, i));
+        }
+        // Items longer than a part
+        src.push_str(&format!("`A({})\n", "wire x;".repeat(10000)));
+        src.push_str(&format!("{}\n", "wire y;".repeat(10000)));
+        src.push_str("`begin_keywords \"1800-2017\"\n");
+
+        let config = PreprocessConfig::default();
+        let (whole, defines) =
+            preprocess_str_with_config(&src, "", &HashMap::new(), &[] as &[String], &config)
+                .unwrap();
+        let mut stream =
+            preprocess_str_stream_with_config(&src, "", &HashMap::new(), &[] as &[String], &config)
+                .unwrap();
+
+        // Parts are merged to the whole text
+        let mut ret = PreprocessedText::new();
+        let mut parts = 0;
+        for x in &mut stream {
+            ret.merge(x.unwrap());
+            parts += 1;
+        }
+        assert!(parts > 2);
+        assert_eq!(ret.text(), whole.text());
+        assert_eq!(stream.defines(), &defines);
+        assert_eq!(ret.warnings().len(), 1);
+
+        let pos = whole.text().find("module m999").unwrap();
+        assert_eq!(ret.location(pos), whole.location(pos));
+
+        // Positions are shifted by drain
+        ret.drain(pos);
+        assert!(ret.text().starts_with("module m999"));
+        assert_eq!(ret.location(0), whole.location(pos));
+        assert_eq!(ret.location(1), whole.location(pos + 1));
     } // }}}
 
     #[test]
@@ -1426,13 +3815,234 @@ mod tests {
     #[test]
     fn include_sameline_include() { // {{{
         let ret = preprocess_usualargs("include_sameline_include.sv");
-        assert_eq!(format!("{:?}", ret), "Err(IncludeLine)");
+        match ret {
+            Err(Error::IncludeLine { location }) => {
+                assert_eq!((location.line, location.column), (2, 27));
+            }
+            x => {
+                panic!("Error::IncludeLine not raised: {:?}", x);
+            }
+        }
     } // }}}
 
     #[test]
     fn include_sameline_keyword() { // {{{
         let ret = preprocess_usualargs("include_sameline_keyword.sv");
-        assert_eq!(format!("{:?}", ret), "Err(IncludeLine)");
+        match ret {
+            Err(Error::IncludeLine { location }) => {
+                assert_eq!((location.line, location.column), (2, 25));
+            }
+            x => {
+                panic!("Error::IncludeLine not raised: {:?}", x);
+            }
+        }
+    } // }}}
+
+    #[test]
+    fn protected_envelope() { // {{{
+        let (ret, _) = preprocess_usualargs("protected_envelope.sv").unwrap();
+        assert_eq!(
+            ret.text(),
+            testfile_contents("protected_envelope.sv")
+        );
+    } // }}}
+
+    #[test]
+    fn translate_off() { // {{{
+        let config = PreprocessConfig {
+            metacomments: true,
+            exclude_translate_off: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("translate_off.sv"),
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            ret.text(),
+            testfile_contents("expected/translate_off.sv")
+        );
+
+        let metacomments: Vec<_> = ret
+            .metacomments()
+            .iter()
+            .map(|x| (x.line, x.namespace.as_str(), x.directive.as_str(), x.arguments.as_str()))
+            .collect();
+        assert_eq!(
+            metacomments,
+            vec![
+                (4, "synopsys", "translate_off", ""),
+                (7, "synopsys", "translate_on", ""),
+                (8, "verilator", "lint_off", "WIDTH"),
+                (10, "verilator", "lint_on", "WIDTH"),
+                (11, "pragma", "translate_off", ""),
+                (13, "synthesis", "translate_on", ""),
+            ]
+        );
+        assert!(ret.metacomments()[0].is_translate_off());
+        assert!(!ret.metacomments()[2].is_translate_off());
+
+        // Without exclusion, the missing include file is an error
+        let config = PreprocessConfig {
+            metacomments: true,
+            ..Default::default()
+        };
+        let ret = preprocess_with_config(
+            testfile_path("translate_off.sv"),
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        );
+        assert!(ret.is_err());
+    } // }}}
+
+    #[test]
+    fn translate_off_ifdef() { // {{{
+        let config = PreprocessConfig {
+            exclude_translate_off: true,
+            ..Default::default()
+        };
+        let mut defines = HashMap::new();
+        defines.insert(String::from("SIM"), None);
+        let (ret, defines) = preprocess_with_config(
+            testfile_path("translate_off_ifdef.sv"),
+            &defines,
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        // Metacomments in inactive branches are not honored, and directives
+        // in excluded text are not processed.
+        assert_eq!(
+            ret.text(),
+            testfile_contents("expected/translate_off_ifdef.sv")
+        );
+        assert!(!defines.contains_key("SIM_ONLY"));
+    } // }}}
+
+    #[test]
+    fn encoding_latin1() { // {{{
+        let path = testfile_path("encoding_latin1.sv");
+        let ret = preprocess_usualargs("encoding_latin1.sv");
+        assert_eq!(format!("{:?}", ret), format!("Err(ReadUtf8({:?}))", path));
+
+        let config = PreprocessConfig {
+            fallback_encoding: Some(Encoding::Latin1),
+            ..Default::default()
+        };
+        let (ret, _) =
+            preprocess_with_config(&path, &HashMap::new(), &[] as &[String], &config).unwrap();
+        assert_eq!(
+            ret.text(),
+            "// Copyright \u{a9} 2001 Vendor\nmodule encoding_latin1;\nendmodule\n"
+        );
+        // `module` is at 27 in the original bytes and at 28 in the text
+        let pos = ret.text().find("module").unwrap();
+        assert_eq!(pos, 28);
+        assert_eq!(ret.origin(pos).unwrap(), (&PathBuf::from(&path), 27));
+        assert_eq!(ret.origin(3).unwrap().1, 3);
+        let location = ret.location(pos).unwrap();
+        assert_eq!((location.offset, location.line, location.column), (27, 2, 1));
+
+        // Errors are located in the original bytes
+        let path = testfile_path("err_DefineNotFound_latin1.sv");
+        let ret = preprocess_with_config(&path, &HashMap::new(), &[] as &[String], &config);
+        match ret {
+            Err(Error::DefineNotFound { location, .. }) => {
+                assert_eq!((location.offset, location.line, location.column), (13, 2, 9));
+            }
+            x => panic!("unexpected {:?}", x),
+        }
+
+        // Warnings are located in the original bytes by warning_location()
+        let path = testfile_path("warning_latin1.sv");
+        let (ret, _) =
+            preprocess_with_config(&path, &HashMap::new(), &[] as &[String], &config).unwrap();
+        let warning = &ret.warnings()[0];
+        assert_eq!(warning.kind, PreprocessWarningKind::UndefineUndefined);
+        assert_eq!(warning.origin, Some((PathBuf::from(&path), 15)));
+        let location = ret.warning_location(warning).unwrap();
+        assert_eq!((location.offset, location.line, location.column), (13, 2, 9));
+    } // }}}
+
+    #[test]
+    fn encoding_utf16le() { // {{{
+        let path = testfile_path("encoding_utf16le.sv");
+        let (ret, _) = preprocess_usualargs("encoding_utf16le.sv").unwrap();
+        assert_eq!(
+            ret.text(),
+            "// \u{e9}t\u{e9}\nmodule encoding_utf16le;\nendmodule\n"
+        );
+        // BOM and 7 characters before `module`
+        let pos = ret.text().find("module").unwrap();
+        assert_eq!(ret.origin(pos).unwrap(), (&PathBuf::from(&path), 16));
+    } // }}}
+
+    #[test]
+    fn include_sameline_profile() { // {{{
+        let config = PreprocessConfig {
+            profile: Profile::Vcs,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("include_sameline_keyword.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        assert!(ret.text().ends_with("endmodule\n"));
+    } // }}}
+
+    #[test]
+    fn profile_predefined_macros() { // {{{
+        let src = "`ifdef VERILATOR\nv\n`endif\n`ifdef SYNTHESIS\ns\n`endif\n`coverage_block_off\n";
+        let config = PreprocessConfig {
+            profile: Profile::Verilator,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_str_with_config(
+            src,
+            "",
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(ret.text(), "v\n\n\n/*verilator coverage_block_off*/\n");
+
+        // pre_defines override the profile
+        let mut defines = HashMap::new();
+        defines.insert(String::from("VERILATOR"), None);
+        let (ret, _) = preprocess_str_with_config(
+            "`VERILATOR",
+            "",
+            &defines,
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(ret.text(), "");
+
+        // `undef of a profile macro is kept in included files
+        let config = PreprocessConfig {
+            profile: Profile::Vcs,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("profile_undef.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(ret.text(), "before\n\n`undef VCS\nnot vcs\n\n\n");
+
+        assert_eq!("synthesis".parse(), Ok(Profile::Synthesis));
+        assert!("unknown".parse::<Profile>().is_err());
     } // }}}
 
     #[test]
@@ -1536,6 +4146,62 @@ mod tests {
         );
     } // }}}
 
+    #[test]
+    fn macro_expand_trace() { // {{{
+        let config = PreprocessConfig {
+            expand_trace: true,
+            ..Default::default()
+        };
+        let (ret, _) = preprocess_with_config(
+            testfile_path("macro_expand_trace.sv"),
+            &HashMap::new(),
+            &[] as &[String],
+            &config,
+        )
+        .unwrap();
+        let expansions = ret.expansions();
+        assert_eq!(expansions.len(), 2);
+
+        let outer = &expansions[0];
+        assert_eq!(outer.name, "OUTER");
+        assert_eq!(outer.line, 5);
+        assert_eq!(
+            outer.arguments,
+            vec![
+                (String::from("a"), String::from("3")),
+                (String::from("b"), String::from("2"))
+            ]
+        );
+        assert_eq!(outer.body, "`INNER(3) * 2");
+        assert_eq!(outer.text, "(3 + 1) * 2");
+        assert_eq!(outer.nested.len(), 1);
+        assert_eq!(outer.nested[0].name, "INNER");
+        assert_eq!(outer.nested[0].body, "(3 + 1)");
+
+        let empty = &expansions[1];
+        assert_eq!(empty.name, "EMPTY");
+        assert_eq!(empty.line, 6);
+        assert_eq!(empty.text, "");
+
+        // Expansions are not recorded by default.
+        let (ret, _) = preprocess_usualargs("macro_expand_trace.sv").unwrap();
+        assert!(ret.expansions().is_empty());
+
+        // The macro giving the path of `include is recorded.
+        let (ret, _) = preprocess_with_config(
+            testfile_path("macro_expand_trace_include.sv"),
+            &HashMap::new(),
+            &[testfile_path("")],
+            &config,
+        )
+        .unwrap();
+        let expansions = ret.expansions();
+        assert_eq!(expansions.len(), 1);
+        assert_eq!(expansions[0].name, "INCLUDED");
+        assert_eq!(expansions[0].line, 3);
+        assert_eq!(expansions[0].text, "\"included.svh\"");
+    } // }}}
+
     #[test]
     #[allow(non_snake_case)]
     fn macro_FILE() { // {{{
@@ -1576,14 +4242,28 @@ mod tests {
 
     #[test]
     fn macro_recursion_direct() { // {{{
-        let ret = preprocess_usualargs("macro_recursion_direct.sv");
-        assert_eq!(format!("{:?}", ret), "Err(ExceedRecursiveLimit)");
+        let ret = preprocess_usualargs("macro_recursion_direct.sv").unwrap_err();
+        let (stack, error) = error_stack(&ret);
+        // Locations in expansions are the call site in the file
+        assert_eq!(stack, vec!["`a 3:1"; RECURSIVE_LIMIT]);
+        match error {
+            Error::ExceedRecursiveLimit { location } => {
+                assert_eq!(location.path, PathBuf::from(testfile_path("macro_recursion_direct.sv")));
+                assert_eq!((location.line, location.column), (3, 1));
+            }
+            x => {
+                panic!("Error::ExceedRecursiveLimit not raised: {:?}", x);
+            }
+        }
     } // }}}
 
     #[test]
     fn macro_recursion_indirect() { // {{{
-        let ret = preprocess_usualargs("macro_recursion_indirect.sv");
-        assert_eq!(format!("{:?}", ret), "Err(ExceedRecursiveLimit)");
+        let ret = preprocess_usualargs("macro_recursion_indirect.sv").unwrap_err();
+        let (stack, error) = error_stack(&ret);
+        assert_eq!(stack.len(), RECURSIVE_LIMIT);
+        assert_eq!(stack[..5], ["`b 6:1", "`c 6:1", "`d 6:1", "`e 6:1", "`b 6:1"]);
+        assert!(matches!(error, Error::ExceedRecursiveLimit { .. }));
     } // }}}
 
     #[test]
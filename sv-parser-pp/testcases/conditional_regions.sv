@@ -0,0 +1,14 @@
+`define A
+`ifdef A
+  `ifndef B
+    a_not_b
+  `else
+    a_b
+  `endif
+`elsif (C && D)
+  `ifdef E
+    c_d_e
+  `endif
+`else
+  none
+`endif
@@ -0,0 +1,3 @@
+// Copyright  2001 Vendor
+module encoding_latin1;
+endmodule
@@ -0,0 +1,9 @@
+module test;
+initial $display("A && !B");
+
+
+initial $display("!(A <-> C)");
+
+initial $display("__LINE__ && !B");
+
+endmodule
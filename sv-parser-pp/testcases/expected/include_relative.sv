@@ -0,0 +1,6 @@
+module and_op (a, b, c);
+output a;
+input b, c;
+
+
+endmodule
@@ -0,0 +1,10 @@
+module and_op (a, b, c);
+// system
+
+output a;
+input b, c;
+
+and a1 (a,b,c);
+
+
+endmodule
@@ -0,0 +1,19 @@
+`line 1 "TOP" 0
+module and_op (a, b, c);
+`line 1 "INCLUDED" 1
+output a;
+input b, c;
+
+`line 7 "INCLUDED" 0
+and a1 (a,b,c);
+
+
+`line 3 "TOP" 2
+`define MULTI \
+  wire m1; \
+  wire m2;
+  wire m1; 
+  wire m2;
+
+`line 10 "TOP" 0
+endmodule
@@ -0,0 +1,14 @@
+module translate_off (input a, output b);
+  assign b = a;
+  initial $display("// synopsys translate_off");
+  // synopsys translate_off
+                                      
+                            
+  // synopsys translate_on
+  /* verilator lint_off WIDTH */
+  wire [1:0] w = a;
+  /* verilator lint_on WIDTH */
+  // pragma translate_off
+                                     
+  // synthesis translate_on
+endmodule
@@ -0,0 +1,16 @@
+module translate_off_ifdef;
+wire a;
+
+  wire b;
+  // synopsys translate_off
+          
+                  
+      
+  // synopsys translate_on
+
+// synopsys translate_off
+         
+      
+  // synopsys translate_on
+  wire e;
+endmodule
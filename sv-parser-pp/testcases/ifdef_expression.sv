@@ -0,0 +1,18 @@
+module test;
+`ifdef (A && !B)
+  initial $display("A && !B");
+`endif
+`ifdef (A && B)
+  initial $display("A && B");
+`elsif (B || (A -> C))
+  initial $display("B || (A -> C)");
+`endif
+`ifndef (A <-> C)
+  initial $display("!(A <-> C)");
+`else
+  initial $display("A <-> C");
+`endif
+`ifdef (__LINE__ && !(B))
+  initial $display("__LINE__ && !B");
+`endif
+endmodule
@@ -0,0 +1,10 @@
+module and_op (a, b, c);
+`include "included.svh"
+`define MULTI \
+  wire m1; \
+  wire m2;
+`MULTI
+`ifdef UNDEFINED
+wire x;
+`endif
+endmodule
@@ -0,0 +1,15 @@
+`define UNUSED 1
+`define ADD(a, b) a + b
+`define IGNORE(a, b) a
+`define GUARD
+`define WRAP `ADD(3, 4)
+`ifndef GUARD
+`endif
+module test;
+  assign x = `ADD(1, 2);
+  assign y = `ADD(1, 2, 3);
+  assign z = `IGNORE(1, 2);
+  assign w = `WRAP;
+endmodule
+`undef ADD
+`define ADD(a, b) (a + b)
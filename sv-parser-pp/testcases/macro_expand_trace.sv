@@ -0,0 +1,7 @@
+`define INNER(x) (x + 1)
+`define OUTER(a, b=2) `INNER(a) * b
+`define EMPTY
+module m;
+  assign y = `OUTER(3);
+  `EMPTY
+endmodule
@@ -0,0 +1,5 @@
+`define INCLUDED "included.svh"
+module m(
+`include `INCLUDED
+);
+endmodule
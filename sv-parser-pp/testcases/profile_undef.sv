@@ -0,0 +1,5 @@
+`ifdef VCS
+before
+`endif
+`undef VCS
+`include "profile_undef.svh"
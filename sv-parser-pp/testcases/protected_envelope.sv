@@ -0,0 +1,15 @@
+module protected_ip (input clk, output q);
+`pragma protect begin_protected
+`pragma protect encrypt_agent = "Example Encryption Tool", encrypt_agent_info = "1.0"
+`pragma protect key_keyowner = "Example Vendor", key_keyname = "EX_KEY_1", key_method = "rsa"
+`pragma protect encoding = (enctype = "base64", line_length = 76, bytes = 64)
+`pragma protect key_block
+//8AbWFjcm8gYHVzYWdlIGlzIG5vdCBleHBhbmRlZCBpbnNpZGUgZGF0YQ==
+qk9Z+/Rt3lLm1aQ5Vh3Gd2x0Yg==
+`pragma protect encoding = (enctype = "base64", line_length = 76, bytes = 48)
+`pragma protect data_method = "aes128-cbc"
+`pragma protect data_block
+U2FsdGVkX1+3bq//k0JmZW5jcnlwdGVkIG1vZHVsZSBib2R5
+ZW5kbW9kdWxl/wire
+`pragma protect end_protected
+endmodule
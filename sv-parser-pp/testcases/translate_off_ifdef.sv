@@ -0,0 +1,22 @@
+module translate_off_ifdef;
+`ifdef SYNTHESIS
+  // synopsys translate_off
+`else
+  wire a;
+`endif
+  wire b;
+  // synopsys translate_off
+`ifdef SIM
+  `define SIM_ONLY
+`endif
+  // synopsys translate_on
+`ifdef SIM_ONLY
+  wire c;
+`endif
+`ifndef SYNTHESIS
+  // synopsys translate_off
+  wire d;
+`endif
+  // synopsys translate_on
+  wire e;
+endmodule
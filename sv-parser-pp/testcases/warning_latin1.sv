@@ -0,0 +1,2 @@
+// 
+/*  */ `undef FOO
@@ -0,0 +1,12 @@
+`define A 1
+`define A 1
+`define A 2
+`undef B
+`undef A
+`ifndef __FILE__
+`elsif UNDEFINED
+`endif
+`include <included.svh>
+`begin_keywords "1800-2017"
+module test;
+endmodule
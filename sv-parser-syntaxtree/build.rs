@@ -51,6 +51,43 @@ static ANY_NODE_DISPLAY_FOOTER: &str = r##"
 }
 "##;
 
+static NODE_KIND_HEADER: &str = r##"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u16)]
+pub enum NodeKind {
+    Locate,
+"##;
+
+static NODE_KIND_FOOTER: &str = r##"
+}
+"##;
+
+static REF_NODE_KIND_HEADER: &str = r##"
+impl<'a> RefNode<'a> {
+    pub fn kind(&self) -> NodeKind {
+        match self {
+            RefNode::Locate(_) => NodeKind::Locate,
+"##;
+
+static REF_NODE_KIND_FOOTER: &str = r##"
+        }
+    }
+}
+"##;
+
+static NODE_KIND_DISPLAY_HEADER: &str = r##"
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Locate => "Locate",
+"##;
+
+static NODE_KIND_DISPLAY_FOOTER: &str = r##"
+        }
+    }
+}
+"##;
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest = Path::new(&out_dir).join("any_node.rs");
@@ -60,11 +97,18 @@ fn main() {
     let mut any_node = String::from(ANY_NODE_HEADER);
     let mut ref_node_display = String::from(REF_NODE_DISPLAY_HEADER);
     let mut any_node_display = String::from(ANY_NODE_DISPLAY_HEADER);
+    let mut node_kind = String::from(NODE_KIND_HEADER);
+    let mut ref_node_kind = String::from(REF_NODE_KIND_HEADER);
+    let mut node_kind_display = String::from(NODE_KIND_DISPLAY_HEADER);
 
     let re_node = Regex::new(r"#\[derive.*Node.*\]").unwrap();
 
     for entry in WalkDir::new("src") {
         let entry = entry.unwrap();
+        // Nodes of Verilog-AMS exist only with `ams` feature
+        if entry.path().starts_with("src/ams") && env::var("CARGO_FEATURE_AMS").is_err() {
+            continue;
+        }
         if entry.path().is_file() {
             let f = File::open(entry.path()).unwrap();
             let f = BufReader::new(f);
@@ -83,6 +127,15 @@ fn main() {
                         "{}            AnyNode::{}(_) => write!(f, \"{}\"),\n",
                         any_node_display, name, name
                     );
+                    node_kind = format!("{}    {},\n", node_kind, name);
+                    ref_node_kind = format!(
+                        "{}            RefNode::{}(_) => NodeKind::{},\n",
+                        ref_node_kind, name, name
+                    );
+                    node_kind_display = format!(
+                        "{}            NodeKind::{} => \"{}\",\n",
+                        node_kind_display, name, name
+                    );
                     hit_node = false;
                 }
                 if re_node.is_match(&line) {
@@ -96,8 +149,14 @@ fn main() {
     any_node = format!("{}{}\n", any_node, ANY_NODE_FOOTER);
     ref_node_display = format!("{}{}\n", ref_node_display, REF_NODE_DISPLAY_FOOTER);
     any_node_display = format!("{}{}\n", any_node_display, ANY_NODE_DISPLAY_FOOTER);
+    node_kind = format!("{}{}\n", node_kind, NODE_KIND_FOOTER);
+    ref_node_kind = format!("{}{}\n", ref_node_kind, REF_NODE_KIND_FOOTER);
+    node_kind_display = format!("{}{}\n", node_kind_display, NODE_KIND_DISPLAY_FOOTER);
     let _ = write!(out, "{}", ref_node);
     let _ = write!(out, "{}", any_node);
     let _ = write!(out, "{}", ref_node_display);
     let _ = write!(out, "{}", any_node_display);
+    let _ = write!(out, "{}", node_kind);
+    let _ = write!(out, "{}", ref_node_kind);
+    let _ = write!(out, "{}", node_kind_display);
 }
@@ -0,0 +1,142 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogConstruct {
+    pub nodes: (Keyword, Option<Keyword>, AnalogStatement),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum AnalogStatementOrNull {
+    Statement(Box<AnalogStatement>),
+    Attribute(Box<StatementOrNullAttribute>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogStatement {
+    pub nodes: (Vec<AttributeInstance>, AnalogStatementItem),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum AnalogStatementItem {
+    ContributionStatement(Box<ContributionStatement>),
+    IndirectContributionStatement(Box<IndirectContributionStatement>),
+    AnalogConditionalStatement(Box<AnalogConditionalStatement>),
+    AnalogCaseStatement(Box<AnalogCaseStatement>),
+    AnalogLoopStatement(Box<AnalogLoopStatement>),
+    AnalogSeqBlock(Box<AnalogSeqBlock>),
+    AnalogEventControlStatement(Box<AnalogEventControlStatement>),
+    AnalogSystemTaskEnable(Box<AnalogSystemTaskEnable>),
+    AnalogProceduralAssignment(Box<AnalogProceduralAssignment>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct ContributionStatement {
+    pub nodes: (AccessFunctionReference, Symbol, Expression, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct IndirectContributionStatement {
+    pub nodes: (AccessFunctionReference, Symbol, Expression, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AccessFunctionReference {
+    pub nodes: (Identifier, Paren<BranchReference>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogConditionalStatement {
+    pub nodes: (
+        Keyword,
+        Paren<Expression>,
+        AnalogStatementOrNull,
+        Option<(Keyword, AnalogStatementOrNull)>,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogCaseStatement {
+    pub nodes: (
+        CaseKeyword,
+        Paren<Expression>,
+        AnalogCaseItem,
+        Vec<AnalogCaseItem>,
+        Keyword,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum AnalogCaseItem {
+    NonDefault(Box<AnalogCaseItemNondefault>),
+    Default(Box<AnalogCaseItemDefault>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogCaseItemNondefault {
+    pub nodes: (List<Symbol, Expression>, Symbol, AnalogStatementOrNull),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogCaseItemDefault {
+    pub nodes: (Keyword, Option<Symbol>, AnalogStatementOrNull),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum AnalogLoopStatement {
+    Repeat(Box<AnalogLoopStatementRepeat>),
+    While(Box<AnalogLoopStatementWhile>),
+    For(Box<AnalogLoopStatementFor>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogLoopStatementRepeat {
+    pub nodes: (Keyword, Paren<Expression>, AnalogStatement),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogLoopStatementWhile {
+    pub nodes: (Keyword, Paren<Expression>, AnalogStatement),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogLoopStatementFor {
+    pub nodes: (
+        Keyword,
+        Paren<(
+            VariableAssignment,
+            Symbol,
+            Expression,
+            Symbol,
+            VariableAssignment,
+        )>,
+        AnalogStatement,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogSeqBlock {
+    pub nodes: (
+        Keyword,
+        Option<(Symbol, BlockIdentifier)>,
+        Vec<BlockItemDeclaration>,
+        Vec<AnalogStatementOrNull>,
+        Keyword,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogEventControlStatement {
+    pub nodes: (EventControl, AnalogStatementOrNull),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogSystemTaskEnable {
+    pub nodes: (SystemTfCall, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct AnalogProceduralAssignment {
+    pub nodes: (VariableAssignment, Symbol),
+}
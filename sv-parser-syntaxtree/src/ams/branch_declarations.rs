@@ -0,0 +1,37 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct BranchDeclaration {
+    pub nodes: (
+        Keyword,
+        Paren<BranchReference>,
+        List<Symbol, BranchIdentifier>,
+        Symbol,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum BranchReference {
+    Terminals(Box<(BranchTerminal, Option<(Symbol, BranchTerminal)>)>),
+    Port(Box<(Symbol, PortIdentifier, Symbol)>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct BranchTerminal {
+    pub nodes: (
+        HierarchicalNetIdentifier,
+        Option<Bracket<ConstantRangeExpression>>,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct GroundDeclaration {
+    pub nodes: (Keyword, List<Symbol, NetIdentifier>, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct BranchIdentifier {
+    pub nodes: (Identifier,),
+}
@@ -0,0 +1,6 @@
+pub mod analog_statements;
+pub mod branch_declarations;
+pub mod natures_and_disciplines;
+pub use analog_statements::*;
+pub use branch_declarations::*;
+pub use natures_and_disciplines::*;
@@ -0,0 +1,92 @@
+use crate::*;
+
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct NatureDeclaration {
+    pub nodes: (
+        Keyword,
+        NatureIdentifier,
+        Option<(Symbol, ParentNature)>,
+        Option<Symbol>,
+        Vec<NatureAttribute>,
+        Keyword,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum ParentNature {
+    Nature(Box<NatureIdentifier>),
+    Discipline(Box<(DisciplineIdentifier, Symbol, PotentialOrFlow)>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct NatureAttribute {
+    pub nodes: (
+        NatureAttributeIdentifier,
+        Symbol,
+        ConstantExpression,
+        Symbol,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum NatureAttributeIdentifier {
+    Keyword(Box<Keyword>),
+    Identifier(Box<Identifier>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct DisciplineDeclaration {
+    pub nodes: (
+        Keyword,
+        DisciplineIdentifier,
+        Option<Symbol>,
+        Vec<DisciplineItem>,
+        Keyword,
+    ),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum DisciplineItem {
+    NatureBinding(Box<NatureBinding>),
+    DisciplineDomainBinding(Box<DisciplineDomainBinding>),
+    NatureAttributeOverride(Box<NatureAttributeOverride>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct NatureBinding {
+    pub nodes: (PotentialOrFlow, NatureIdentifier, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum PotentialOrFlow {
+    Potential(Box<Keyword>),
+    Flow(Box<Keyword>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct DisciplineDomainBinding {
+    pub nodes: (Keyword, DiscreteOrContinuous, Symbol),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum DiscreteOrContinuous {
+    Discrete(Box<Keyword>),
+    Continuous(Box<Keyword>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct NatureAttributeOverride {
+    pub nodes: (PotentialOrFlow, Symbol, NatureAttribute),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct NatureIdentifier {
+    pub nodes: (Identifier,),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct DisciplineIdentifier {
+    pub nodes: (Identifier,),
+}
@@ -44,6 +44,7 @@ pub enum StatementItem {
     RandsequenceStatement(Box<RandsequenceStatement>),
     RandcaseStatement(Box<RandcaseStatement>),
     ExpectPropertyStatement(Box<ExpectPropertyStatement>),
+    MacroInvocation(Box<MacroInvocation>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
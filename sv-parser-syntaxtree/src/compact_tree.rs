@@ -0,0 +1,249 @@
+use crate::*;
+use std::collections::HashMap;
+use std::mem::size_of;
+
+// -----------------------------------------------------------------------------
+
+/// Index of a node in `CompactTree`.
+pub type NodeId = u32;
+
+const NONE: u32 = u32::MAX;
+
+/// Flattened node stored in the arena of `CompactTree`.
+///
+/// Nodes are stored in pre-order, so the descendants of a node are exactly
+/// the nodes in `id + 1..end`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactNode {
+    pub kind: NodeKind,
+    parent: u32,
+    end: u32,
+    token: u32,
+}
+
+/// Position of a `Locate` in the arena of `CompactTree`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CompactToken {
+    pub offset: u32,
+    pub line: u32,
+    pub len: u32,
+    trivia: u32,
+}
+
+/// Compact, arena-allocated representation of a syntax tree.
+///
+/// Every node of the original tree becomes a fixed-size `CompactNode` whose
+/// kind is an interned `NodeKind`. `Locate`s are stored in a separate token
+/// table, and the text of white space and comments is shared between all
+/// tokens with the same spelling.
+#[derive(Clone, Debug, Default)]
+pub struct CompactTree {
+    nodes: Vec<CompactNode>,
+    tokens: Vec<CompactToken>,
+    trivia: Vec<String>,
+}
+
+impl CompactTree {
+    /// Build a `CompactTree` from the specified node.
+    ///
+    /// `text` is the source text the `Locate`s refer to. It is used to share
+    /// the spelling of trivia tokens.
+    pub fn new<'a, T: Into<RefNodes<'a>>>(nodes: T, text: &str) -> Self {
+        let mut ret = CompactTree::default();
+        let mut stack: Vec<u32> = Vec::new();
+        let mut trivia_map: HashMap<&str, u32> = HashMap::new();
+        let mut in_trivia = 0;
+
+        for event in Iter::new(nodes.into()).event() {
+            match event {
+                NodeEvent::Enter(x) => {
+                    let id = ret.nodes.len() as u32;
+                    let parent = stack.last().copied().unwrap_or(NONE);
+                    if let RefNode::WhiteSpace(_) = x {
+                        in_trivia += 1;
+                    }
+                    let token = if let RefNode::Locate(x) = x {
+                        let trivia = if in_trivia > 0 {
+                            let s = text.get(x.offset..x.offset + x.len).unwrap_or("");
+                            let next = ret.trivia.len() as u32;
+                            let id = *trivia_map.entry(s).or_insert(next);
+                            if id == next {
+                                ret.trivia.push(String::from(s));
+                            }
+                            id
+                        } else {
+                            NONE
+                        };
+                        ret.tokens.push(CompactToken {
+                            offset: x.offset as u32,
+                            line: x.line,
+                            len: x.len as u32,
+                            trivia,
+                        });
+                        ret.tokens.len() as u32 - 1
+                    } else {
+                        NONE
+                    };
+                    ret.nodes.push(CompactNode {
+                        kind: x.kind(),
+                        parent,
+                        end: NONE,
+                        token,
+                    });
+                    stack.push(id);
+                }
+                NodeEvent::Leave(x) => {
+                    if let RefNode::WhiteSpace(_) = x {
+                        in_trivia -= 1;
+                    }
+                    if let Some(id) = stack.pop() {
+                        ret.nodes[id as usize].end = ret.nodes.len() as u32;
+                    }
+                }
+            }
+        }
+
+        ret.nodes.shrink_to_fit();
+        ret.tokens.shrink_to_fit();
+        ret.trivia.shrink_to_fit();
+        ret
+    }
+
+    /// Get the number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether the tree has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get the root node
+    pub fn root(&self) -> Option<NodeId> {
+        if self.nodes.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    /// Get the node of the specified id
+    pub fn node(&self, id: NodeId) -> &CompactNode {
+        &self.nodes[id as usize]
+    }
+
+    /// Get the kind of the specified node
+    pub fn kind(&self, id: NodeId) -> NodeKind {
+        self.node(id).kind
+    }
+
+    /// Get the parent of the specified node
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        let parent = self.node(id).parent;
+        if parent == NONE {
+            None
+        } else {
+            Some(parent)
+        }
+    }
+
+    /// Get the direct children of the specified node
+    pub fn children(&self, id: NodeId) -> Children<'_> {
+        Children {
+            tree: self,
+            next: id + 1,
+            end: self.node(id).end,
+        }
+    }
+
+    /// Get all descendants of the specified node in pre-order, including itself
+    pub fn descendants(&self, id: NodeId) -> impl Iterator<Item = NodeId> {
+        id..self.node(id).end
+    }
+
+    /// Get all nodes in pre-order
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> {
+        0..self.nodes.len() as u32
+    }
+
+    /// Get `Locate` of the specified node if it is a token
+    pub fn locate(&self, id: NodeId) -> Option<Locate> {
+        let token = self.node(id).token;
+        if token == NONE {
+            None
+        } else {
+            let x = &self.tokens[token as usize];
+            Some(Locate {
+                offset: x.offset as usize,
+                line: x.line,
+                len: x.len as usize,
+            })
+        }
+    }
+
+    /// Check whether the specified node is white space, comment or a part of them
+    pub fn is_trivia(&self, id: NodeId) -> bool {
+        let node = self.node(id);
+        if node.kind == NodeKind::WhiteSpace {
+            return true;
+        }
+        let mut parent = node.parent;
+        while parent != NONE {
+            let x = &self.nodes[parent as usize];
+            if x.kind == NodeKind::WhiteSpace {
+                return true;
+            }
+            parent = x.parent;
+        }
+        false
+    }
+
+    /// Get the shared text of the specified token if it is trivia
+    pub fn trivia_text(&self, id: NodeId) -> Option<&str> {
+        let token = self.node(id).token;
+        if token == NONE {
+            return None;
+        }
+        let trivia = self.tokens[token as usize].trivia;
+        if trivia == NONE {
+            None
+        } else {
+            Some(&self.trivia[trivia as usize])
+        }
+    }
+
+    /// Get the number of bytes allocated on heap by this tree
+    pub fn heap_size(&self) -> usize {
+        self.nodes.capacity() * size_of::<CompactNode>()
+            + self.tokens.capacity() * size_of::<CompactToken>()
+            + self.trivia.capacity() * size_of::<String>()
+            + self.trivia.iter().map(|x| x.capacity()).sum::<usize>()
+    }
+}
+
+pub struct Children<'a> {
+    tree: &'a CompactTree,
+    next: u32,
+    end: u32,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < self.end {
+            let ret = self.next;
+            self.next = self.tree.node(ret).end;
+            Some(ret)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
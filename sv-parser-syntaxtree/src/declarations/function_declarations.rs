@@ -10,7 +10,12 @@ pub enum FunctionDataTypeOrImplicit {
 
 #[derive(Clone, Debug, PartialEq, Node)]
 pub struct FunctionDeclaration {
-    pub nodes: (Keyword, Option<Lifetime>, FunctionBodyDeclaration),
+    pub nodes: (
+        Keyword,
+        Option<DynamicOverrideSpecifiers>,
+        Option<Lifetime>,
+        FunctionBodyDeclaration,
+    ),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
@@ -58,6 +63,7 @@ pub enum InterfaceIdentifierOrClassScope {
 pub struct FunctionPrototype {
     pub nodes: (
         Keyword,
+        Option<DynamicOverrideSpecifiers>,
         DataTypeOrVoid,
         FunctionIdentifier,
         Option<Paren<Option<TfPortList>>>,
@@ -146,3 +152,19 @@ pub struct DpiFunctionProto {
 pub struct DpiTaskProto {
     pub nodes: (TaskPrototype,),
 }
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct DynamicOverrideSpecifiers {
+    pub nodes: (Option<InitialOrExtendsSpecifier>, Option<FinalSpecifier>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum InitialOrExtendsSpecifier {
+    Initial(Box<(Symbol, Keyword)>),
+    Extends(Box<(Symbol, Keyword)>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct FinalSpecifier {
+    pub nodes: (Symbol, Keyword),
+}
@@ -259,6 +259,7 @@ pub enum StructUnion {
     Struct(Box<Keyword>),
     Union(Box<Keyword>),
     UnionTagged(Box<(Keyword, Keyword)>),
+    UnionSoft(Box<(Keyword, Keyword)>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
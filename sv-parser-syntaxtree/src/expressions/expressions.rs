@@ -134,6 +134,7 @@ pub enum Expression {
     ConditionalExpression(Box<ConditionalExpression>),
     InsideExpression(Box<InsideExpression>),
     TaggedUnionExpression(Box<TaggedUnionExpression>),
+    MacroInvocation(Box<MacroInvocation>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
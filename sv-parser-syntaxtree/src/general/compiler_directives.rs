@@ -18,6 +18,7 @@ pub enum CompilerDirective {
     CelldefineDriveCompilerDirective(Box<CelldefineDriveCompilerDirective>),
     EndcelldefineDriveCompilerDirective(Box<EndcelldefineDriveCompilerDirective>),
     Pragma(Box<Pragma>),
+    ProtectedEnvelope(Box<ProtectedEnvelope>),
     LineCompilerDirective(Box<LineCompilerDirective>),
     PositionCompilerDirective(Box<PositionCompilerDirective>),
     KeywordsDirective(Box<KeywordsDirective>),
@@ -131,9 +132,9 @@ pub struct IfdefDirective {
     pub nodes: (
         Symbol,
         Keyword,
-        TextMacroIdentifier,
+        IfdefCondition,
         IfdefGroupOfLines,
-        Vec<(Symbol, Keyword, TextMacroIdentifier, ElsifGroupOfLines)>,
+        Vec<(Symbol, Keyword, IfdefCondition, ElsifGroupOfLines)>,
         Option<(Symbol, Keyword, ElseGroupOfLines)>,
         Symbol,
         Keyword,
@@ -145,15 +146,49 @@ pub struct IfndefDirective {
     pub nodes: (
         Symbol,
         Keyword,
-        TextMacroIdentifier,
+        IfdefCondition,
         IfndefGroupOfLines,
-        Vec<(Symbol, Keyword, TextMacroIdentifier, ElsifGroupOfLines)>,
+        Vec<(Symbol, Keyword, IfdefCondition, ElsifGroupOfLines)>,
         Option<(Symbol, Keyword, ElseGroupOfLines)>,
         Symbol,
         Keyword,
     ),
 }
 
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum IfdefCondition {
+    TextMacroIdentifier(Box<TextMacroIdentifier>),
+    Paren(Box<IfdefConditionParen>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct IfdefConditionParen {
+    pub nodes: (Paren<IfdefMacroExpression>,),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum IfdefMacroExpression {
+    TextMacroIdentifier(Box<TextMacroIdentifier>),
+    Paren(Box<IfdefMacroExpressionParen>),
+    Not(Box<IfdefMacroExpressionNot>),
+    Binary(Box<IfdefMacroExpressionBinary>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct IfdefMacroExpressionParen {
+    pub nodes: (Paren<IfdefMacroExpression>,),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct IfdefMacroExpressionNot {
+    pub nodes: (Symbol, IfdefMacroExpression),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct IfdefMacroExpressionBinary {
+    pub nodes: (IfdefMacroExpression, Symbol, IfdefMacroExpression),
+}
+
 #[derive(Clone, Debug, PartialEq, Node)]
 pub struct IfdefGroupOfLines {
     pub nodes: (Vec<SourceDescription>,),
@@ -276,6 +311,27 @@ pub struct PragmaKeyword {
     pub nodes: (SimpleIdentifier,),
 }
 
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct ProtectedEnvelope {
+    pub nodes: (Pragma, Vec<ProtectedEnvelopeItem>, Pragma),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum ProtectedEnvelopeItem {
+    Pragma(Box<Pragma>),
+    ProtectedBlock(Box<ProtectedBlock>),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct ProtectedBlock {
+    pub nodes: (Pragma, ProtectedData),
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct ProtectedData {
+    pub nodes: (Locate,),
+}
+
 #[derive(Clone, Debug, PartialEq, Node)]
 pub struct LineCompilerDirective {
     pub nodes: (Symbol, Keyword, Number, StringLiteral, Level),
@@ -305,3 +361,21 @@ pub struct VersionSpecifier {
 pub struct EndkeywordsDirective {
     pub nodes: (Symbol, Keyword),
 }
+
+/// A macro invocation whose expansion is parsed as a whole item
+///
+/// This is produced only when the preprocessor records macro calls.
+#[derive(Clone, Debug, PartialEq, Node)]
+pub struct MacroInvocation {
+    pub nodes: (MacroInvocationItem, Vec<WhiteSpace>),
+    /// Macro call text in the original source
+    pub call: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Node)]
+pub enum MacroInvocationItem {
+    Expression(Box<Expression>),
+    StatementItem(Box<StatementItem>),
+    NonPortModuleItem(Box<NonPortModuleItem>),
+    ClassItem(Box<ClassItem>),
+}
@@ -5,8 +5,11 @@
     clippy::type_complexity
 )]
 
+#[cfg(feature = "ams")]
+pub mod ams;
 pub mod any_node;
 pub mod behavioral_statements;
+pub mod compact_tree;
 pub mod declarations;
 pub mod expressions;
 pub mod general;
@@ -17,8 +20,11 @@ pub mod source_text;
 pub mod special_node;
 pub mod specify_section;
 pub mod udp_declaration_and_instantiation;
+#[cfg(feature = "ams")]
+pub use ams::*;
 pub use any_node::*;
 pub use behavioral_statements::*;
+pub use compact_tree::*;
 pub use declarations::*;
 pub use expressions::*;
 pub use general::*;
@@ -12,6 +12,7 @@ pub enum ClassItem {
     LocalParameterDeclaration(Box<(LocalParameterDeclaration, Symbol)>),
     ParameterDeclaration(Box<(ParameterDeclaration, Symbol)>),
     Empty(Box<Symbol>),
+    MacroInvocation(Box<MacroInvocation>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
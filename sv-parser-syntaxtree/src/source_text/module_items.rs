@@ -56,6 +56,8 @@ pub enum ModuleCommonItem {
     LoopGenerateConstruct(Box<LoopGenerateConstruct>),
     ConditionalGenerateConstruct(Box<ConditionalGenerateConstruct>),
     ElaborationSystemTask(Box<ElaborationSystemTask>),
+    #[cfg(feature = "ams")]
+    AnalogConstruct(Box<AnalogConstruct>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
@@ -105,6 +107,10 @@ pub enum ModuleOrGenerateItemDeclaration {
     ClockingDeclaration(Box<ClockingDeclaration>),
     Clocking(Box<ModuleOrGenerateItemDeclarationClocking>),
     Disable(Box<ModuleOrGenerateItemDeclarationDisable>),
+    #[cfg(feature = "ams")]
+    BranchDeclaration(Box<BranchDeclaration>),
+    #[cfg(feature = "ams")]
+    GroundDeclaration(Box<GroundDeclaration>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
@@ -127,6 +133,7 @@ pub enum NonPortModuleItem {
     ModuleDeclaration(Box<ModuleDeclaration>),
     InterfaceDeclaration(Box<InterfaceDeclaration>),
     TimeunitsDeclaration(Box<TimeunitsDeclaration>),
+    MacroInvocation(Box<MacroInvocation>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
@@ -23,6 +23,10 @@ pub enum Description {
     PackageItem(Box<DescriptionPackageItem>),
     BindDirective(Box<DescriptionBindDirective>),
     ConfigDeclaration(Box<ConfigDeclaration>),
+    #[cfg(feature = "ams")]
+    NatureDeclaration(Box<NatureDeclaration>),
+    #[cfg(feature = "ams")]
+    DisciplineDeclaration(Box<DisciplineDeclaration>),
 }
 
 #[derive(Clone, Debug, PartialEq, Node)]
@@ -313,6 +317,7 @@ pub struct ClassDeclaration {
     pub nodes: (
         Option<Virtual>,
         Keyword,
+        Option<FinalSpecifier>,
         Option<Lifetime>,
         ClassIdentifier,
         Option<ParameterPortList>,
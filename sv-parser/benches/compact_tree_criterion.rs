@@ -0,0 +1,117 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use sv_parser::{parse_sv, CompactTree, RefNode};
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn get_path(s: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/testcases/{}",
+        env::var("CARGO_MANIFEST_DIR").unwrap(),
+        s
+    ))
+}
+
+fn report_memory(s: &str) {
+    let defines = HashMap::new();
+    let includes: Vec<PathBuf> = Vec::new();
+    let path = get_path(s);
+
+    let (syntax_tree, _) = parse_sv(&path, &defines, &includes, false, false).unwrap();
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let (compact, _text) = syntax_tree.into_compact();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+
+    // The original nodes are dropped by into_compact(), so the difference
+    // excluding the new CompactTree is the size of them.
+    let compact_size = compact.heap_size();
+    let tree_size = before + compact_size - after;
+
+    println!(
+        "{}: SyntaxTree {} bytes, CompactTree {} bytes ({} nodes, {:.1}x smaller)",
+        s,
+        tree_size,
+        compact_size,
+        compact.len(),
+        tree_size as f64 / compact_size as f64,
+    );
+}
+
+fn gen_benchmark_group(c: &mut Criterion, s: &str) {
+    report_memory(s);
+
+    let defines = HashMap::new();
+    let includes: Vec<PathBuf> = Vec::new();
+    let path = get_path(s);
+    let mut group = c.benchmark_group(s);
+    group.bench_function("parse", |b| {
+        b.iter_with_large_drop(|| parse_sv(&path, &defines, &includes, false, false))
+    });
+    group.bench_function("parse_compact", |b| {
+        b.iter_with_large_drop(|| {
+            let (syntax_tree, _) = parse_sv(&path, &defines, &includes, false, false).unwrap();
+            syntax_tree.into_compact()
+        })
+    });
+
+    let (syntax_tree, _) = parse_sv(&path, &defines, &includes, false, false).unwrap();
+    let compact: CompactTree = syntax_tree.compact();
+    group.bench_function("traverse", |b| {
+        b.iter(|| {
+            (&syntax_tree)
+                .into_iter()
+                .filter(|x| matches!(x, RefNode::Locate(_)))
+                .count()
+        })
+    });
+    group.bench_function("traverse_compact", |b| {
+        b.iter(|| {
+            compact
+                .iter()
+                .filter(|x| compact.locate(*x).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .sample_size(30)
+        .measurement_time(Duration::new(30, 0))
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    gen_benchmark_group(c, "test1.sv");
+    gen_benchmark_group(c, "test2.sv");
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
@@ -2,12 +2,15 @@ use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{cmp, process};
 use structopt::StructOpt;
-use sv_parser::{parse_sv, Define, DefineText};
+use sv_parser::{
+    parse_sv_pp_with_config, Define, DefineText, Encoding, LanguageVersion, PreprocessConfig,
+    PreprocessedText, Profile,
+};
 use sv_parser_error::Error;
-use sv_parser_pp::preprocess::preprocess;
+use sv_parser_pp::preprocess::preprocess_with_config;
 
 #[derive(StructOpt)]
 struct Opt {
@@ -17,6 +20,10 @@ struct Opt {
     #[structopt(short = "i", long = "include", multiple = true, number_of_values = 1)]
     pub includes: Vec<PathBuf>,
 
+    /// Include path for `include <filename>
+    #[structopt(long = "system-include", multiple = true, number_of_values = 1)]
+    pub system_includes: Vec<PathBuf>,
+
     /// Show syntax tree
     #[structopt(short = "t", long = "tree")]
     pub tree: bool,
@@ -25,6 +32,10 @@ struct Opt {
     #[structopt(short = "p", long = "pp")]
     pub pp: bool,
 
+    /// Insert `line directives to preprocesed text
+    #[structopt(long = "line-markers")]
+    pub line_markers: bool,
+
     /// Allow incomplete source code
     #[structopt(long = "incomplete")]
     pub incomplete: bool,
@@ -36,6 +47,52 @@ struct Opt {
     /// Quiet
     #[structopt(short = "q", long = "quiet")]
     pub quiet: bool,
+
+    /// Show macro expansion trace
+    #[structopt(long = "expand-trace")]
+    pub expand_trace: bool,
+
+    /// Emulated tool [generic|vcs|questa|xcelium|verilator|synthesis]
+    #[structopt(long = "profile", default_value = "generic")]
+    pub profile: Profile,
+
+    /// Exclude translate_off regions
+    #[structopt(long = "exclude-translate-off")]
+    pub exclude_translate_off: bool,
+
+    /// Encoding of files which are not UTF-8 [latin1|windows-1252]
+    #[structopt(long = "encoding")]
+    pub encoding: Option<Encoding>,
+
+    /// Language version such as 1364-2005 or 1800-2017, or <extension>=<version>
+    /// to select it by file extension
+    #[structopt(
+        long = "language",
+        multiple = true,
+        number_of_values = 1,
+        parse(try_from_str = parse_language)
+    )]
+    pub languages: Vec<(Option<String>, LanguageVersion)>,
+}
+
+fn parse_language(s: &str) -> Result<(Option<String>, LanguageVersion), String> {
+    match s.split_once('=') {
+        Some((ext, version)) => Ok((Some(String::from(ext)), version.parse()?)),
+        None => Ok((None, s.parse()?)),
+    }
+}
+
+fn language_version(
+    languages: &[(Option<String>, LanguageVersion)],
+    path: &Path,
+) -> LanguageVersion {
+    let ext = path.extension().map(|x| x.to_string_lossy());
+    let by_ext = languages
+        .iter()
+        .rev()
+        .find(|(x, _)| x.is_some() && x.as_deref() == ext.as_deref());
+    let default = languages.iter().rev().find(|(x, _)| x.is_none());
+    by_ext.or(default).map(|(_, x)| *x).unwrap_or_default()
 }
 
 fn main() {
@@ -55,28 +112,66 @@ fn main() {
         defines.insert(ident, Some(define));
     }
 
+    let config = PreprocessConfig {
+        system_include_paths: opt.system_includes.clone(),
+        line_markers: opt.line_markers,
+        profile: opt.profile,
+        exclude_translate_off: opt.exclude_translate_off,
+        fallback_encoding: opt.encoding,
+        ..Default::default()
+    };
+
     let builder = std::thread::Builder::new().stack_size(20 * 1024 * 1024);
 
     let child = builder
         .spawn(move || {
             let mut exit = 0;
             for path in &opt.files {
+                let language_version = language_version(&opt.languages, path);
+                if opt.expand_trace {
+                    let config = PreprocessConfig {
+                        expand_trace: true,
+                        ..config.clone()
+                    };
+                    match preprocess_with_config(path, &defines, &opt.includes, &config) {
+                        Ok((preprocessed_text, _)) => {
+                            for expansion in preprocessed_text.expansions() {
+                                print!("{}", expansion);
+                            }
+                        }
+                        Err(x) => {
+                            println!("preprocess failed: {:?} ({})", path, x);
+                            exit = 1;
+                            continue;
+                        }
+                    }
+                }
                 if opt.pp {
-                    match preprocess(
-                        &path,
-                        &defines,
-                        &opt.includes,
-                        false, // strip_comments
-                        false, // ignore_include
-                    ) {
+                    match preprocess_with_config(path, &defines, &opt.includes, &config) {
                         Ok((preprocessed_text, new_defines)) => {
-                            println!("{}", preprocessed_text.text());
+                            print_warnings(&preprocessed_text);
+                            if opt.line_markers {
+                                println!("{}", preprocessed_text.text_with_line_markers());
+                            } else {
+                                println!("{}", preprocessed_text.text());
+                            }
                             defines = new_defines;
                         }
                         _ => (),
                     }
                 } else {
-                    match parse_sv(&path, &defines, &opt.includes, false, opt.incomplete) {
+                    let result = preprocess_with_config(path, &defines, &opt.includes, &config)
+                        .and_then(|(text, defines)| {
+                            print_warnings(&text);
+                            parse_sv_pp_with_config(
+                                text,
+                                defines,
+                                &config,
+                                language_version,
+                                opt.incomplete,
+                            )
+                        });
+                    match result {
                         Ok((syntax_tree, new_defines)) => {
                             if opt.tree {
                                 println!("{}", syntax_tree);
@@ -88,9 +183,9 @@ fn main() {
                         }
                         Err(x) => {
                             match x {
-                                Error::Parse(Some((origin_path, origin_pos))) => {
+                                Error::Parse(Some(location)) => {
                                     println!("parse failed: {:?}", path);
-                                    print_parse_error(&origin_path, &origin_pos);
+                                    print_parse_error(&location.path, &location.offset);
                                 }
                                 x => {
                                     println!("parse failed: {:?} ({:?})", path, x);
@@ -113,6 +208,22 @@ fn main() {
     let _ = child.join();
 }
 
+fn print_warnings(text: &PreprocessedText) {
+    for warning in text.warnings() {
+        if let Some(location) = text.warning_location(warning) {
+            eprintln!(
+                "{}:{}:{}: {}",
+                location.path.to_string_lossy(),
+                location.line,
+                location.column,
+                warning
+            );
+        } else {
+            eprintln!("{}", warning);
+        }
+    }
+}
+
 static CHAR_CR: u8 = 0x0d;
 static CHAR_LF: u8 = 0x0a;
 
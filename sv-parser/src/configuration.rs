@@ -0,0 +1,392 @@
+use crate::{parse_sv_pp, unwrap_node, Define, Defines, Error, NodeKind, RefNode, SyntaxTree};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::BuildHasher;
+use std::path::Path;
+use sv_parser_pp::preprocess::{preprocess_with_config, PreprocessConfig};
+
+// -----------------------------------------------------------------------------
+
+/// Limits of `explore_configurations`
+#[derive(Clone, Debug)]
+pub struct ExploreConfig {
+    /// The maximum number of configurations to be preprocessed
+    pub max_configurations: usize,
+    /// Macros in a larger group of interacting macros are toggled one by one
+    /// instead of all combinations
+    pub max_group_size: usize,
+    /// Passed to `parse_sv_pp` to accept an incomplete source in each configuration
+    pub allow_incomplete: bool,
+    /// `conditional_directives` is always enabled
+    pub preprocess: PreprocessConfig,
+}
+
+impl Default for ExploreConfig {
+    fn default() -> Self {
+        ExploreConfig {
+            max_configurations: 256,
+            max_group_size: 8,
+            allow_incomplete: false,
+            preprocess: PreprocessConfig::default(),
+        }
+    }
+}
+
+/// A combination of macros used in conditions and the parse result under it
+#[derive(Debug)]
+pub struct Configuration {
+    /// Whether each macro used in conditions is defined
+    pub macros: BTreeMap<String, bool>,
+    /// Index of the first configuration which produces the same preprocessed
+    /// text. `error` and `descriptions` are copied from it.
+    pub same_as: Option<usize>,
+    pub error: Option<Error>,
+    pub descriptions: Vec<(NodeKind, String)>,
+}
+
+/// Configurations in which a `Description` exists
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DescriptionConfigurations {
+    pub kind: NodeKind,
+    pub name: String,
+    /// Indices of `ConfigurationReport::configurations`
+    pub configurations: Vec<usize>,
+    /// Macros whose value decides whether the description exists
+    ///
+    /// Each group of interacting macros is checked in the configurations
+    /// where the other macros have their default values.
+    pub requires: BTreeMap<String, bool>,
+}
+
+#[derive(Debug)]
+pub struct ConfigurationReport {
+    pub configurations: Vec<Configuration>,
+    pub descriptions: Vec<DescriptionConfigurations>,
+    /// Some configurations are not explored because of `max_configurations`
+    pub truncated: bool,
+}
+
+impl ConfigurationReport {
+    /// Get the error of the specified configuration, following `same_as`
+    pub fn error(&self, index: usize) -> Option<&Error> {
+        let x = &self.configurations[index];
+        match x.same_as {
+            Some(x) => self.configurations[x].error.as_ref(),
+            None => x.error.as_ref(),
+        }
+    }
+
+    /// Get configurations which fail to preprocess or parse
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &Configuration)> {
+        self.configurations
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| self.error(*i).is_some())
+    }
+}
+
+/// Preprocess and parse the specified file under combinations of macros used
+/// in `ifdef/`ifndef/`elsif
+///
+/// Macros are discovered from conditions evaluated in each configuration, so
+/// conditions inside inactive branches are explored in later configurations.
+/// Macros appearing in the same directive or in nested directives are grouped,
+/// and all combinations of each group are explored while the other macros are
+/// kept as `pre_defines`.
+pub fn explore_configurations<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &ExploreConfig,
+) -> ConfigurationReport {
+    let preprocess_config = PreprocessConfig {
+        conditional_directives: true,
+        ..config.preprocess.clone()
+    };
+
+    let mut base = HashMap::new();
+    for (k, v) in pre_defines {
+        base.insert(k.clone(), v.clone());
+    }
+
+    let mut groups = Groups::default();
+    let mut queue: VecDeque<BTreeMap<String, bool>> = VecDeque::new();
+    let mut tried = HashSet::new();
+    let mut texts = HashMap::new();
+    let mut results = Vec::new();
+    let mut truncated = false;
+
+    queue.push_back(BTreeMap::new());
+    while let Some(overrides) = queue.pop_front() {
+        if tried.contains(&overrides) {
+            continue;
+        }
+        if results.len() >= config.max_configurations {
+            truncated = true;
+            break;
+        }
+        tried.insert(overrides.clone());
+
+        let mut defines = base.clone();
+        for (name, defined) in &overrides {
+            if *defined {
+                let define = Define::new(name.clone(), vec![], None);
+                defines.insert(name.clone(), Some(define));
+            } else {
+                defines.remove(name);
+            }
+        }
+
+        let result = match preprocess_with_config(
+            path.as_ref(),
+            &defines,
+            include_paths,
+            &preprocess_config,
+        ) {
+            Ok((text, new_defines)) => {
+                for x in text.conditional_directives() {
+                    groups.add(&x.macros, &x.enclosing);
+                }
+                if let Some(i) = texts.get(text.text()) {
+                    (Some(*i), None, Vec::new())
+                } else {
+                    texts.insert(String::from(text.text()), results.len());
+                    match parse_sv_pp(text, new_defines, config.allow_incomplete) {
+                        Ok((syntax_tree, _)) => (None, None, descriptions(&syntax_tree)),
+                        Err(x) => (None, Some(x), Vec::new()),
+                    }
+                }
+            }
+            Err(x) => (None, Some(x), Vec::new()),
+        };
+        results.push((overrides, result));
+
+        for group in groups.groups() {
+            let assignments: Vec<Vec<(&String, bool)>> = if group.len() <= config.max_group_size {
+                (0..1usize << group.len())
+                    .map(|mask| {
+                        group
+                            .iter()
+                            .enumerate()
+                            .map(|(i, x)| (*x, mask & (1 << i) != 0))
+                            .collect()
+                    })
+                    .collect()
+            } else {
+                group
+                    .iter()
+                    .map(|x| vec![(*x, !base.contains_key(*x))])
+                    .collect()
+            };
+            for assignment in assignments {
+                let overrides: BTreeMap<_, _> = assignment
+                    .into_iter()
+                    .filter(|(x, defined)| base.contains_key(*x) != *defined)
+                    .map(|(x, defined)| (x.clone(), defined))
+                    .collect();
+                if !tried.contains(&overrides) {
+                    queue.push_back(overrides);
+                }
+            }
+        }
+    }
+    if queue.iter().any(|x| !tried.contains(x)) {
+        truncated = true;
+    }
+
+    let mut configurations: Vec<Configuration> = Vec::new();
+    for (overrides, (same_as, error, descriptions)) in results {
+        let macros = groups
+            .macros
+            .keys()
+            .map(|x| {
+                let defined = overrides
+                    .get(x)
+                    .copied()
+                    .unwrap_or_else(|| base.contains_key(x));
+                (x.clone(), defined)
+            })
+            .collect();
+        let descriptions = match same_as {
+            Some(x) => configurations[x].descriptions.clone(),
+            None => descriptions,
+        };
+        configurations.push(Configuration {
+            macros,
+            same_as,
+            error,
+            descriptions,
+        });
+    }
+
+    let descriptions = description_configurations(&configurations, &groups.groups());
+    ConfigurationReport {
+        configurations,
+        descriptions,
+        truncated,
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// Union-find of macros used in conditions
+#[derive(Default)]
+struct Groups {
+    macros: BTreeMap<String, String>,
+}
+
+impl Groups {
+    fn find(&self, x: &str) -> String {
+        let mut x = x;
+        while let Some(parent) = self.macros.get(x) {
+            if parent == x {
+                break;
+            }
+            x = parent;
+        }
+        String::from(x)
+    }
+
+    fn union(&mut self, a: &str, b: &str) {
+        for x in &[a, b] {
+            if !self.macros.contains_key(*x) {
+                self.macros.insert(String::from(*x), String::from(*x));
+            }
+        }
+        let a = self.find(a);
+        let b = self.find(b);
+        if a != b {
+            self.macros.insert(a, b);
+        }
+    }
+
+    fn add(&mut self, macros: &[String], enclosing: &[String]) {
+        for x in macros.iter().chain(enclosing) {
+            self.union(&macros[0], x);
+        }
+    }
+
+    fn groups(&self) -> Vec<Vec<&String>> {
+        let mut ret: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+        for x in self.macros.keys() {
+            ret.entry(self.find(x)).or_default().push(x);
+        }
+        ret.into_values().collect()
+    }
+}
+
+fn descriptions(syntax_tree: &SyntaxTree) -> Vec<(NodeKind, String)> {
+    let mut ret = Vec::new();
+    for node in syntax_tree {
+        if let RefNode::Description(x) = node {
+            let (kind, id) = match RefNode::from(x).into_iter().nth(1) {
+                Some(RefNode::ModuleDeclaration(x)) => (
+                    NodeKind::ModuleDeclaration,
+                    unwrap_node!(x, ModuleIdentifier),
+                ),
+                Some(RefNode::UdpDeclaration(x)) => {
+                    (NodeKind::UdpDeclaration, unwrap_node!(x, UdpIdentifier))
+                }
+                Some(RefNode::InterfaceDeclaration(x)) => (
+                    NodeKind::InterfaceDeclaration,
+                    unwrap_node!(x, InterfaceIdentifier),
+                ),
+                Some(RefNode::InterfaceClassDeclaration(x)) => (
+                    NodeKind::InterfaceClassDeclaration,
+                    unwrap_node!(x, ClassIdentifier),
+                ),
+                Some(RefNode::ProgramDeclaration(x)) => (
+                    NodeKind::ProgramDeclaration,
+                    unwrap_node!(x, ProgramIdentifier),
+                ),
+                Some(RefNode::PackageDeclaration(x)) => (
+                    NodeKind::PackageDeclaration,
+                    unwrap_node!(x, PackageIdentifier),
+                ),
+                Some(RefNode::ConfigDeclaration(x)) => (
+                    NodeKind::ConfigDeclaration,
+                    unwrap_node!(x, ConfigIdentifier),
+                ),
+                Some(RefNode::DescriptionPackageItem(x)) => match unwrap_node!(x, ClassDeclaration)
+                {
+                    Some(x) => (NodeKind::ClassDeclaration, unwrap_node!(x, ClassIdentifier)),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if let Some(name) = id.and_then(|x| syntax_tree.get_str_trim(vec![x])) {
+                ret.push((kind, String::from(name)));
+            }
+        }
+    }
+    ret
+}
+
+fn description_configurations(
+    configurations: &[Configuration],
+    groups: &[Vec<&String>],
+) -> Vec<DescriptionConfigurations> {
+    let failed = |i: usize| match configurations[i].same_as {
+        Some(x) => configurations[x].error.is_some(),
+        None => configurations[i].error.is_some(),
+    };
+
+    let mut ret: Vec<DescriptionConfigurations> = Vec::new();
+    for (i, x) in configurations.iter().enumerate() {
+        for (kind, name) in &x.descriptions {
+            let pos = ret.iter().position(|x| x.kind == *kind && x.name == *name);
+            match pos {
+                Some(pos) => ret[pos].configurations.push(i),
+                None => ret.push(DescriptionConfigurations {
+                    kind: *kind,
+                    name: name.clone(),
+                    configurations: vec![i],
+                    requires: BTreeMap::new(),
+                }),
+            }
+        }
+    }
+
+    let base = match configurations.first() {
+        Some(x) => &x.macros,
+        None => return ret,
+    };
+    for group in groups {
+        // Configurations which differ from the first one only in this group
+        let relevant: Vec<_> = (0..configurations.len())
+            .filter(|i| {
+                configurations[*i]
+                    .macros
+                    .iter()
+                    .all(|(k, v)| group.contains(&k) || base.get(k) == Some(v))
+            })
+            .collect();
+
+        for x in &mut ret {
+            let present: Vec<_> = relevant
+                .iter()
+                .filter(|i| x.configurations.contains(i))
+                .collect();
+            if present.is_empty() {
+                continue;
+            }
+            for name in group {
+                let defined = configurations[*present[0]].macros[*name];
+                if present
+                    .iter()
+                    .any(|i| configurations[**i].macros[*name] != defined)
+                {
+                    continue;
+                }
+                // Failed configurations may contain the description
+                let mut others = relevant
+                    .iter()
+                    .filter(|i| configurations[**i].macros[*name] != defined)
+                    .peekable();
+                if others.peek().is_some() && others.all(|i| !present.contains(&i) && !failed(*i)) {
+                    x.requires.insert((*name).clone(), defined);
+                }
+            }
+        }
+    }
+    ret
+}
@@ -0,0 +1,196 @@
+use crate::{unwrap_node, NodeEvent, NodeKind, RefNode, SyntaxTree, TimeunitsDeclaration};
+
+// -----------------------------------------------------------------------------
+
+/// Compiler directives in force at the beginning of a design element
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirectiveState {
+    /// Time unit such as `1ns` given by `` `timescale `` or overridden by
+    /// `timeunit` in the design element
+    pub time_unit: Option<String>,
+    /// Time precision such as `1ps` given by `` `timescale `` or overridden by
+    /// `timeprecision` in the design element
+    pub time_precision: Option<String>,
+    /// Net type of implicit nets. `wire` unless `` `default_nettype `` is given.
+    pub default_nettype: String,
+    pub celldefine: bool,
+    /// `pull0` or `pull1` given by `` `unconnected_drive ``
+    pub unconnected_drive: Option<String>,
+    /// Version specifier of the innermost `` `begin_keywords `` such as `1800-2017`
+    pub keywords: Option<String>,
+}
+
+impl Default for DirectiveState {
+    fn default() -> Self {
+        DirectiveState {
+            time_unit: None,
+            time_precision: None,
+            default_nettype: String::from("wire"),
+            celldefine: false,
+            unconnected_drive: None,
+            keywords: None,
+        }
+    }
+}
+
+/// Directive state of a module, interface, program or package
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DesignElementDirectives {
+    pub kind: NodeKind,
+    pub name: String,
+    pub state: DirectiveState,
+}
+
+impl SyntaxTree {
+    /// Get compiler directive state of each module, interface, program and package
+    ///
+    /// Directives are applied in source order. `` `resetall `` restores the
+    /// defaults except `` `begin_keywords ``. Nested design elements inherit the
+    /// time unit and precision of the enclosing one.
+    pub fn directive_states(&self) -> Vec<DesignElementDirectives> {
+        let mut ret: Vec<DesignElementDirectives> = Vec::new();
+        let mut state = DirectiveState::default();
+        let mut keywords: Vec<String> = Vec::new();
+        // Indices of `ret` for enclosing design elements
+        let mut stack: Vec<Option<usize>> = Vec::new();
+
+        for event in self.into_iter().event() {
+            match event {
+                NodeEvent::Enter(RefNode::ResetallCompilerDirective(_)) => {
+                    state = DirectiveState {
+                        keywords: state.keywords,
+                        ..Default::default()
+                    };
+                }
+                NodeEvent::Enter(RefNode::TimescaleCompilerDirective(x)) => {
+                    let (_, _, a, b, _, c, d) = &x.nodes;
+                    state.time_unit = self.time(a.into(), b.into());
+                    state.time_precision = self.time(c.into(), d.into());
+                }
+                NodeEvent::Enter(RefNode::DefaultNettypeCompilerDirective(x)) => {
+                    if let Some(x) = self.get_str_trim(vec![(&x.nodes.2).into()]) {
+                        state.default_nettype = String::from(x);
+                    }
+                }
+                NodeEvent::Enter(RefNode::CelldefineDriveCompilerDirective(_)) => {
+                    state.celldefine = true;
+                }
+                NodeEvent::Enter(RefNode::EndcelldefineDriveCompilerDirective(_)) => {
+                    state.celldefine = false;
+                }
+                NodeEvent::Enter(RefNode::UnconnectedDriveCompilerDirective(x)) => {
+                    state.unconnected_drive = self
+                        .get_str_trim(vec![(&x.nodes.2).into()])
+                        .map(String::from);
+                }
+                NodeEvent::Enter(RefNode::NounconnectedDriveCompilerDirective(_)) => {
+                    state.unconnected_drive = None;
+                }
+                NodeEvent::Enter(RefNode::KeywordsDirective(x)) => {
+                    if let Some(x) = self.get_str_trim(vec![(&x.nodes.3).into()]) {
+                        keywords.push(String::from(x));
+                    }
+                    state.keywords = keywords.last().cloned();
+                }
+                NodeEvent::Enter(RefNode::EndkeywordsDirective(_)) => {
+                    keywords.pop();
+                    state.keywords = keywords.last().cloned();
+                }
+                NodeEvent::Enter(RefNode::TimeunitsDeclaration(x)) => {
+                    let (unit, precision) = self.timeunits(x);
+                    let target = match stack.iter().rev().find_map(|x| *x) {
+                        Some(i) => &mut ret[i].state,
+                        None => &mut state,
+                    };
+                    if unit.is_some() {
+                        target.time_unit = unit;
+                    }
+                    if precision.is_some() {
+                        target.time_precision = precision;
+                    }
+                }
+                NodeEvent::Enter(x) => {
+                    if let Some((kind, name)) = self.design_element(&x) {
+                        let mut element_state = state.clone();
+                        if let Some(i) = stack.iter().rev().find_map(|x| *x) {
+                            element_state.time_unit = ret[i].state.time_unit.clone();
+                            element_state.time_precision = ret[i].state.time_precision.clone();
+                        }
+                        stack.push(Some(ret.len()));
+                        ret.push(DesignElementDirectives {
+                            kind,
+                            name,
+                            state: element_state,
+                        });
+                    } else if is_design_element(&x) {
+                        stack.push(None);
+                    }
+                }
+                NodeEvent::Leave(x) => {
+                    if is_design_element(&x) {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn time(&self, number: RefNode, unit: RefNode) -> Option<String> {
+        let number = self.get_str_trim(vec![number])?;
+        let unit = self.get_str_trim(vec![unit])?;
+        Some(format!("{}{}", number, unit))
+    }
+
+    fn timeunits(&self, x: &TimeunitsDeclaration) -> (Option<String>, Option<String>) {
+        let str = |x: RefNode| self.get_str_trim(vec![x]).map(String::from);
+        match x {
+            TimeunitsDeclaration::Timeunit(x) => {
+                let (_, unit, precision, _) = &x.nodes;
+                let precision = precision.as_ref().and_then(|(_, x)| str(x.into()));
+                (str(unit.into()), precision)
+            }
+            TimeunitsDeclaration::Timeprecision(x) => (None, str((&x.nodes.1).into())),
+            TimeunitsDeclaration::TimeunitTimeprecision(x) => {
+                (str((&x.nodes.1).into()), str((&x.nodes.4).into()))
+            }
+            TimeunitsDeclaration::TimeprecisionTimeunit(x) => {
+                (str((&x.nodes.4).into()), str((&x.nodes.1).into()))
+            }
+        }
+    }
+
+    pub(crate) fn design_element(&self, node: &RefNode) -> Option<(NodeKind, String)> {
+        let (kind, id) = match *node {
+            RefNode::ModuleDeclaration(x) => (
+                NodeKind::ModuleDeclaration,
+                unwrap_node!(x, ModuleIdentifier),
+            ),
+            RefNode::InterfaceDeclaration(x) => (
+                NodeKind::InterfaceDeclaration,
+                unwrap_node!(x, InterfaceIdentifier),
+            ),
+            RefNode::ProgramDeclaration(x) => (
+                NodeKind::ProgramDeclaration,
+                unwrap_node!(x, ProgramIdentifier),
+            ),
+            RefNode::PackageDeclaration(x) => (
+                NodeKind::PackageDeclaration,
+                unwrap_node!(x, PackageIdentifier),
+            ),
+            _ => return None,
+        };
+        let name = self.get_str_trim(vec![id?])?;
+        Some((kind, String::from(name)))
+    }
+}
+
+pub(crate) fn is_design_element(node: &RefNode) -> bool {
+    matches!(
+        node,
+        RefNode::ModuleDeclaration(_)
+            | RefNode::InterfaceDeclaration(_)
+            | RefNode::ProgramDeclaration(_)
+            | RefNode::PackageDeclaration(_)
+    )
+}
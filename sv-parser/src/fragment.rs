@@ -0,0 +1,139 @@
+use crate::{
+    AnyNode, ClassItem, ConstantExpression, DataType, Defines, Description, Error, Expression,
+    InterfaceItem, Locate, ModuleItem, PackageItem, PreprocessConfig, PreprocessedText, Statement,
+    SyntaxTree,
+};
+use nom_greedyerror::error_position;
+use std::path::PathBuf;
+use sv_parser_parser::{
+    class_item_parser, constant_expression_parser, data_type_parser, description_parser,
+    expression_parser, interface_item_parser, module_item_parser, package_item_parser,
+    statement_parser, IResult, Span, SpanInfo,
+};
+use sv_parser_pp::preprocess::preprocess_str_with_config;
+
+// -----------------------------------------------------------------------------
+
+/// Subtree parsed from a fragment of source code by `parse_*_str`
+///
+/// Leading white spaces of the fragment are not included in the node. Convert
+/// it into `SyntaxTree` to get `&str` of nodes or to traverse it.
+///
+/// The `parse_*_str` functions are the stable entry points to parse a part of
+/// source code, and follow semantic versioning as other public items: a
+/// function is neither removed nor changes the type of its node except in a
+/// breaking release. The grammar functions of `sv-parser-parser` are not part
+/// of them.
+#[derive(Debug)]
+pub struct Fragment<T> {
+    node: T,
+    text: PreprocessedText,
+}
+
+impl<T> Fragment<T> {
+    /// Get the parsed node
+    pub fn node(&self) -> &T {
+        &self.node
+    }
+
+    /// Get the parsed node and drop the text
+    pub fn into_node(self) -> T {
+        self.node
+    }
+
+    /// Get the parsed node and the preprocessed text
+    pub fn into_parts(self) -> (T, PreprocessedText) {
+        (self.node, self.text)
+    }
+
+    /// Get the preprocessed text
+    pub fn text(&self) -> &PreprocessedText {
+        &self.text
+    }
+
+    /// Get source code location of the specified `Locate`
+    pub fn get_origin(&self, locate: &Locate) -> Option<(&PathBuf, usize)> {
+        self.text.origin(locate.offset)
+    }
+}
+
+impl<T: Into<AnyNode>> From<Fragment<T>> for SyntaxTree {
+    fn from(x: Fragment<T>) -> Self {
+        SyntaxTree {
+            node: x.node.into(),
+            text: x.text,
+        }
+    }
+}
+
+// Macros in the fragment are expanded, and `include is ignored because the
+// fragment has no path.
+fn parse_fragment<T, F>(s: &str, parser: F) -> Result<Fragment<T>, Error>
+where
+    F: Fn(Span) -> IResult<Span, T>,
+{
+    let config = PreprocessConfig {
+        ignore_include: true,
+        ..PreprocessConfig::default()
+    };
+    let (text, _) = preprocess_str_with_config(s, "", &Defines::new(), &[] as &[PathBuf], &config)?;
+    let span = Span::new_extra(text.text(), SpanInfo::default());
+    let result = parser(span).map(|(_, x)| x);
+    match result {
+        Ok(node) => Ok(Fragment { node, text }),
+        Err(x) => {
+            let pos = match x {
+                nom::Err::Incomplete(_) => None,
+                nom::Err::Error(e) => error_position(&e),
+                nom::Err::Failure(e) => error_position(&e),
+            };
+            let location = pos.and_then(|pos| text.location(pos)).map(Box::new);
+            Err(Error::Parse(location))
+        }
+    }
+}
+
+/// Parse an `Expression` from a string
+pub fn parse_expression_str(s: &str) -> Result<Fragment<Expression>, Error> {
+    parse_fragment(s, expression_parser)
+}
+
+/// Parse a `ConstantExpression` from a string
+pub fn parse_constant_expression_str(s: &str) -> Result<Fragment<ConstantExpression>, Error> {
+    parse_fragment(s, constant_expression_parser)
+}
+
+/// Parse a `Statement` from a string
+pub fn parse_statement_str(s: &str) -> Result<Fragment<Statement>, Error> {
+    parse_fragment(s, statement_parser)
+}
+
+/// Parse a `DataType` from a string
+pub fn parse_data_type_str(s: &str) -> Result<Fragment<DataType>, Error> {
+    parse_fragment(s, data_type_parser)
+}
+
+/// Parse a `ModuleItem` from a string
+pub fn parse_module_item_str(s: &str) -> Result<Fragment<ModuleItem>, Error> {
+    parse_fragment(s, module_item_parser)
+}
+
+/// Parse an `InterfaceItem` from a string
+pub fn parse_interface_item_str(s: &str) -> Result<Fragment<InterfaceItem>, Error> {
+    parse_fragment(s, interface_item_parser)
+}
+
+/// Parse a `PackageItem` from a string
+pub fn parse_package_item_str(s: &str) -> Result<Fragment<PackageItem>, Error> {
+    parse_fragment(s, package_item_parser)
+}
+
+/// Parse a `ClassItem` from a string
+pub fn parse_class_item_str(s: &str) -> Result<Fragment<ClassItem>, Error> {
+    parse_fragment(s, class_item_parser)
+}
+
+/// Parse a `Description` from a string
+pub fn parse_description_str(s: &str) -> Result<Fragment<Description>, Error> {
+    parse_fragment(s, description_parser)
+}
@@ -0,0 +1,144 @@
+use crate::{
+    get_str_trim, DataType, Error, LanguageVersion, NodeEvent, PreprocessedText, RefNode,
+    SyntaxTree, UnpackedDimension, VariableDimension,
+};
+
+// -----------------------------------------------------------------------------
+
+/// Reject SystemVerilog-only constructs in a syntax tree parsed as Verilog
+///
+/// Keywords which are not reserved in `version`, and the constructs which the
+/// grammar accepts without SystemVerilog keywords are rejected. Regions in
+/// `` `begin_keywords "1800-*" `` are not checked.
+pub(crate) fn check_verilog(tree: &SyntaxTree, version: LanguageVersion) -> Result<(), Error> {
+    VerilogCheck::new(version).check((&tree.node).into(), &tree.text, 0)
+}
+
+/// `check_verilog` over nodes parsed one at a time
+///
+/// `` `begin_keywords `` regions are kept across the checked nodes.
+pub(crate) struct VerilogCheck {
+    version: LanguageVersion,
+    keywords: Vec<LanguageVersion>,
+}
+
+impl VerilogCheck {
+    pub(crate) fn new(version: LanguageVersion) -> Self {
+        VerilogCheck {
+            version,
+            keywords: Vec::new(),
+        }
+    }
+
+    /// `text` begins at `base` of the offsets of `node`
+    pub(crate) fn check(
+        &mut self,
+        node: RefNode,
+        text: &PreprocessedText,
+        base: usize,
+    ) -> Result<(), Error> {
+        let keywords = &mut self.keywords;
+        let version = self.version;
+        let s = text.text();
+        // Keywords of compiler directives such as `end_keywords are not checked
+        let mut directive = 0;
+
+        for event in node.into_iter().event() {
+            let node = match event {
+                NodeEvent::Enter(RefNode::CompilerDirective(_)) => {
+                    directive += 1;
+                    continue;
+                }
+                NodeEvent::Leave(RefNode::CompilerDirective(_)) => {
+                    directive -= 1;
+                    continue;
+                }
+                NodeEvent::Enter(RefNode::KeywordsDirective(x)) => {
+                    let specifier = get_str_trim(s, base, vec![(&x.nodes.3).into()]);
+                    let specifier = specifier.map(|x| x.trim_matches('"'));
+                    keywords.push(specifier.and_then(|x| x.parse().ok()).unwrap_or(version));
+                    continue;
+                }
+                NodeEvent::Enter(RefNode::EndkeywordsDirective(_)) => {
+                    keywords.pop();
+                    continue;
+                }
+                NodeEvent::Enter(_) if directive > 0 => continue,
+                NodeEvent::Enter(x) => x,
+                NodeEvent::Leave(_) => continue,
+            };
+            let version = keywords.last().copied().unwrap_or(version);
+            if !version.is_verilog() {
+                continue;
+            }
+            let construct = match node {
+                RefNode::Keyword(x) => get_str_trim(s, base, x)
+                    .filter(|x| !version.is_keyword(x))
+                    .map(|x| format!("keyword `{}`", x)),
+                _ => systemverilog_construct(s, base, &node).map(String::from),
+            };
+            if let Some(construct) = construct {
+                let location = node
+                    .clone()
+                    .into_iter()
+                    .find_map(|x| match x {
+                        RefNode::Locate(x) => Some(x),
+                        _ => None,
+                    })
+                    .and_then(|x| text.location(x.offset - base))
+                    .map(Box::new);
+                return Err(Error::SystemVerilogConstruct {
+                    construct,
+                    location,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn systemverilog_construct(text: &str, base: usize, node: &RefNode) -> Option<&'static str> {
+    match node {
+        // `logic`, `bit` and `int` are user-defined types with Verilog keywords
+        RefNode::DataType(
+            DataType::Type(_)
+            | DataType::ClassType(_)
+            | DataType::PsCovergroupIdentifier(_)
+            | DataType::TypeReference(_),
+        ) => Some("user-defined type"),
+        RefNode::NetDeclarationNetTypeIdentifier(_) => Some("user-defined nettype"),
+        RefNode::ForVariableDeclaration(_) => Some("loop variable declaration"),
+        RefNode::AssignmentOperator(x) if get_str_trim(text, base, *x) != Some("=") => {
+            Some("assignment operator")
+        }
+        RefNode::IncOrDecOperator(_) => Some("increment or decrement operator"),
+        RefNode::BinaryOperator(x)
+            if matches!(
+                get_str_trim(text, base, *x),
+                Some("==?" | "!=?" | "->" | "<->")
+            ) =>
+        {
+            Some("binary operator")
+        }
+        RefNode::UnbasedUnsizedLiteral(_) => Some("unbased unsized literal"),
+        RefNode::AssignmentPattern(_) => Some("assignment pattern"),
+        RefNode::Cast(_) => Some("cast"),
+        RefNode::PackageScope(_) | RefNode::ClassScope(_) => Some("scope resolution"),
+        RefNode::NamedPortConnectionAsterisk(_) => Some(".* port connection"),
+        RefNode::NamedPortConnectionIdentifier(x) if x.nodes.3.is_none() => {
+            Some(".name port connection")
+        }
+        RefNode::TimeLiteral(_) => Some("time literal"),
+        RefNode::UnpackedDimension(UnpackedDimension::Expression(_)) => {
+            Some("unpacked dimension by size")
+        }
+        RefNode::VariableDimension(
+            VariableDimension::UnsizedDimension(_)
+            | VariableDimension::AssociativeDimension(_)
+            | VariableDimension::QueueDimension(_),
+        ) => Some("dynamic array"),
+        RefNode::SeqBlock(x) if x.nodes.5.is_some() => Some("block end label"),
+        RefNode::ParBlock(x) if x.nodes.5.is_some() => Some("block end label"),
+        _ => None,
+    }
+}
@@ -1,18 +1,40 @@
 #![recursion_limit = "256"]
 
-use nom_greedyerror::error_position;
+use nom::error::ErrorKind;
+use nom_greedyerror::{error_position, GreedyError};
 use std::fmt;
 use std::hash::BuildHasher;
 use std::path::{Path, PathBuf};
-pub use sv_parser_error::Error;
+pub use sv_parser_error::{Error, IncludeSite, Location};
+pub use sv_parser_parser::lexer::{tokenize, Lexer, Token, TokenKind};
+use sv_parser_parser::limits::{clear_limits, set_limits};
+pub use sv_parser_parser::utils::LanguageVersion;
+use sv_parser_parser::utils::{clear_macro_calls, set_language_version, set_macro_calls};
 use sv_parser_parser::{
-    lib_parser, lib_parser_incomplete, sv_parser, sv_parser_incomplete, Span, SpanInfo,
+    lib_parser, lib_parser_incomplete, sv_parser, sv_parser_incomplete, sv_parser_stream_begin,
+    sv_parser_stream_next, Span, SpanInfo,
 };
+pub use sv_parser_pp::encoding::Encoding;
 pub use sv_parser_pp::preprocess::{
-    preprocess, preprocess_str, Define, DefineText, Defines, PreprocessedText,
+    preprocess, preprocess_str, preprocess_str_stream_with_config, preprocess_str_with_config,
+    preprocess_stream_with_config, preprocess_with_config, CancellationToken, ConditionalBranch,
+    ConditionalBranchKind, ConditionalDirective, ConditionalRegion, Define, DefineText, Defines,
+    IncludeSearch, Limits, MacroCall, MacroDatabase, MacroDefinition, MacroExpansion, MacroIssue,
+    MacroRecord, MacroSite, MacroUsage, MacroUsageKind, Metacomment, PreprocessConfig,
+    PreprocessStream, PreprocessWarning, PreprocessWarningKind, PreprocessedText, Profile,
 };
 pub use sv_parser_syntaxtree::*;
 
+mod configuration;
+mod directive_state;
+mod fragment;
+mod language_version;
+mod protected;
+pub use configuration::*;
+pub use directive_state::*;
+pub use fragment::*;
+pub use protected::*;
+
 pub struct SyntaxTree {
     node: AnyNode,
     text: PreprocessedText,
@@ -41,38 +63,30 @@ impl SyntaxTree {
 
     /// Get `&str` without trailing `WhiteSpace` from the specified node
     pub fn get_str_trim<'a, T: Into<RefNodes<'a>>>(&self, nodes: T) -> Option<&str> {
-        let mut beg = None;
-        let mut end = 0;
-        let mut skip = false;
-        for n in Iter::new(nodes.into()).event() {
-            match n {
-                NodeEvent::Enter(RefNode::WhiteSpace(_)) => {
-                    skip = true;
-                }
-                NodeEvent::Leave(RefNode::WhiteSpace(_)) => {
-                    skip = false;
-                }
-                NodeEvent::Enter(RefNode::Locate(x)) if !skip => {
-                    if beg.is_none() {
-                        beg = Some(x.offset);
-                    }
-                    end = x.offset + x.len;
-                }
-                _ => (),
-            }
-        }
-        if let Some(beg) = beg {
-            let ret = unsafe { self.text.text().get_unchecked(beg..end) };
-            Some(ret)
-        } else {
-            None
-        }
+        get_str_trim(self.text.text(), 0, nodes)
     }
 
     /// Get source code location of the specified `Locate`
     pub fn get_origin(&self, locate: &Locate) -> Option<(&PathBuf, usize)> {
         self.text.origin(locate.offset)
     }
+
+    /// Get source code location of the specified `Locate` with line and column
+    pub fn get_location(&self, locate: &Locate) -> Option<Location> {
+        self.text.location(locate.offset)
+    }
+
+    /// Convert to `CompactTree` keeping this tree
+    pub fn compact(&self) -> CompactTree {
+        let node: RefNode = (&self.node).into();
+        CompactTree::new(vec![node], self.text.text())
+    }
+
+    /// Convert to `CompactTree` and drop the original nodes
+    pub fn into_compact(self) -> (CompactTree, PreprocessedText) {
+        let tree = self.compact();
+        (tree, self.text)
+    }
 }
 
 impl fmt::Display for SyntaxTree {
@@ -181,6 +195,36 @@ impl<'a> IntoIterator for &'a SyntaxTree {
     }
 }
 
+/// Get `&str` without trailing `WhiteSpace` from the specified node in `text`
+// `text` begins at `base` of the offsets of `nodes`
+pub(crate) fn get_str_trim<'a, 'b, T: Into<RefNodes<'b>>>(
+    text: &'a str,
+    base: usize,
+    nodes: T,
+) -> Option<&'a str> {
+    let mut beg = None;
+    let mut end = 0;
+    let mut skip = false;
+    for n in Iter::new(nodes.into()).event() {
+        match n {
+            NodeEvent::Enter(RefNode::WhiteSpace(_)) => {
+                skip = true;
+            }
+            NodeEvent::Leave(RefNode::WhiteSpace(_)) => {
+                skip = false;
+            }
+            NodeEvent::Enter(RefNode::Locate(x)) if !skip => {
+                if beg.is_none() {
+                    beg = Some(x.offset);
+                }
+                end = x.offset + x.len;
+            }
+            _ => (),
+        }
+    }
+    text.get(beg?.checked_sub(base)?..end - base)
+}
+
 pub fn parse_sv<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     path: T,
     pre_defines: &Defines<V>,
@@ -198,41 +242,121 @@ pub fn parse_sv<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     parse_sv_pp(text, defines, allow_incomplete)
 }
 
+/// Parse with `PreprocessConfig` and `LanguageVersion`
+///
+/// `config.limits` is applied to both preprocessing and parsing, and
+/// `language_version` selects the keywords outside of `` `begin_keywords ``.
+pub fn parse_sv_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    language_version: LanguageVersion,
+    allow_incomplete: bool,
+) -> Result<(SyntaxTree, Defines), Error> {
+    let (text, defines) = preprocess_with_config(path, pre_defines, include_paths, config)?;
+    parse_sv_pp_with_config(text, defines, config, language_version, allow_incomplete)
+}
+
 pub fn parse_sv_pp(
     text: PreprocessedText,
     defines: Defines,
     allow_incomplete: bool,
 ) -> Result<(SyntaxTree, Defines), Error> {
+    parse_sv_pp_with_limits(text, defines, &Limits::default(), allow_incomplete)
+}
+
+pub fn parse_sv_pp_with_limits(
+    text: PreprocessedText,
+    defines: Defines,
+    limits: &Limits,
+    allow_incomplete: bool,
+) -> Result<(SyntaxTree, Defines), Error> {
+    parse_sv_pp_inner(
+        text,
+        defines,
+        limits,
+        LanguageVersion::default(),
+        allow_incomplete,
+    )
+}
+
+/// Parse `PreprocessedText` with `PreprocessConfig` and `LanguageVersion`
+///
+/// `config.limits` is applied. If `language_version` is Verilog,
+/// SystemVerilog-only constructs outside of `` `begin_keywords "1800-*" ``
+/// cause `Error::SystemVerilogConstruct`.
+pub fn parse_sv_pp_with_config(
+    text: PreprocessedText,
+    defines: Defines,
+    config: &PreprocessConfig,
+    language_version: LanguageVersion,
+    allow_incomplete: bool,
+) -> Result<(SyntaxTree, Defines), Error> {
+    parse_sv_pp_inner(
+        text,
+        defines,
+        &config.limits,
+        language_version,
+        allow_incomplete,
+    )
+}
+
+fn parse_sv_pp_inner(
+    text: PreprocessedText,
+    defines: Defines,
+    limits: &Limits,
+    language_version: LanguageVersion,
+    allow_incomplete: bool,
+) -> Result<(SyntaxTree, Defines), Error> {
+    limits.check()?;
+
+    // Expansions are matched from the first token
+    let calls = text
+        .macro_calls()
+        .iter()
+        .map(|x| {
+            let expansion = &text.text()[x.begin..x.end];
+            let begin = x.end - expansion.trim_start().len();
+            (begin, x.end, x.text.clone())
+        })
+        .filter(|(begin, end, _)| begin < end)
+        .collect();
+    set_macro_calls(calls);
+
     let span = Span::new_extra(text.text(), SpanInfo::default());
-    let result = if allow_incomplete {
-        sv_parser_incomplete(span)
-    } else {
-        sv_parser(span)
+    set_limits(limits);
+    let result = {
+        let _version = set_language_version(language_version);
+        if allow_incomplete {
+            sv_parser_incomplete(span)
+        } else {
+            sv_parser(span)
+        }
     };
+    clear_macro_calls();
+    if let Some(e) = clear_limits() {
+        return Err(e);
+    }
     match result {
-        Ok((_, x)) => Ok((
-            SyntaxTree {
+        Ok((_, x)) => {
+            let syntax_tree = SyntaxTree {
                 node: x.into(),
                 text,
-            },
-            defines,
-        )),
+            };
+            if language_version.is_verilog() {
+                language_version::check_verilog(&syntax_tree, language_version)?;
+            }
+            Ok((syntax_tree, defines))
+        }
         Err(x) => {
             let pos = match x {
                 nom::Err::Incomplete(_) => None,
                 nom::Err::Error(e) => error_position(&e),
                 nom::Err::Failure(e) => error_position(&e),
             };
-            let origin = if let Some(pos) = pos {
-                if let Some(origin) = text.origin(pos) {
-                    Some((origin.0.clone(), origin.1))
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            Err(Error::Parse(origin))
+            let location = pos.and_then(|pos| text.location(pos)).map(Box::new);
+            Err(Error::Parse(location))
         }
     }
 }
@@ -258,6 +382,362 @@ pub fn parse_sv_str<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     parse_sv_pp(text, defines, allow_incomplete)
 }
 
+/// Parse a string with `PreprocessConfig` and `LanguageVersion`
+///
+/// `config.limits` is applied to both preprocessing and parsing, and
+/// `language_version` selects the keywords outside of `` `begin_keywords ``.
+pub fn parse_sv_str_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    language_version: LanguageVersion,
+    allow_incomplete: bool,
+) -> Result<(SyntaxTree, Defines), Error> {
+    let (text, defines) = preprocess_str_with_config(s, path, pre_defines, include_paths, config)?;
+    parse_sv_pp_with_config(text, defines, config, language_version, allow_incomplete)
+}
+
+// Size of the text following a `Description` to be sure that it is complete
+const STREAM_LOOKAHEAD: usize = 0x1000;
+
+/// Iterator parsing `Description` one at a time
+///
+/// The text is preprocessed part by part as `Description` is parsed when the
+/// stream is created from `PreprocessStream`, and the text before the last
+/// yielded `Description` is dropped at the next call. Each `Description` is
+/// owned by the caller, so the memory is bounded by the largest `Description`
+/// rather than the whole preprocessed text and syntax tree. `Locate` is an
+/// offset in the whole preprocessed text, and can be resolved by `get_str`
+/// and `get_origin` until the next `Description` is parsed.
+///
+/// `Limits` is applied to each `Description`, and the language version to
+/// the whole stream. The parser state is thread local, so only one stream
+/// should be consumed at a time per thread.
+pub struct DescriptionStream {
+    source: Option<PreprocessStream>,
+    // Whether all parts of `source` are merged to `text`
+    preprocessed: bool,
+    text: PreprocessedText,
+    // Offset of `text` in the whole preprocessed text
+    base: usize,
+    limits: Limits,
+    language_version: LanguageVersion,
+    verilog_check: Option<language_version::VerilogCheck>,
+    offset: usize,
+    line: u32,
+    started: bool,
+    finished: bool,
+}
+
+impl DescriptionStream {
+    pub fn new(text: PreprocessedText) -> Self {
+        Self::with_config(
+            text,
+            &PreprocessConfig::default(),
+            LanguageVersion::default(),
+        )
+    }
+
+    /// Create with `config.limits` and `language_version`
+    pub fn with_config(
+        text: PreprocessedText,
+        config: &PreprocessConfig,
+        language_version: LanguageVersion,
+    ) -> Self {
+        let verilog_check = if language_version.is_verilog() {
+            Some(language_version::VerilogCheck::new(language_version))
+        } else {
+            None
+        };
+        DescriptionStream {
+            source: None,
+            preprocessed: true,
+            text,
+            base: 0,
+            limits: config.limits.clone(),
+            language_version,
+            verilog_check,
+            offset: 0,
+            line: 1,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Create from `PreprocessStream` with `config.limits` and
+    /// `language_version`
+    pub fn with_source(
+        source: PreprocessStream,
+        config: &PreprocessConfig,
+        language_version: LanguageVersion,
+    ) -> Self {
+        DescriptionStream {
+            source: Some(source),
+            preprocessed: false,
+            ..Self::with_config(PreprocessedText::default(), config, language_version)
+        }
+    }
+
+    /// Get `&str` from the specified node
+    pub fn get_str<'a, T: Into<RefNodes<'a>>>(&self, nodes: T) -> Option<&str> {
+        let mut beg = None;
+        let mut end = 0;
+        for n in Iter::new(nodes.into()) {
+            if let RefNode::Locate(x) = n {
+                if beg.is_none() {
+                    beg = Some(x.offset);
+                }
+                end = x.offset + x.len;
+            }
+        }
+        let beg = beg?.checked_sub(self.base)?;
+        self.text.text().get(beg..end - self.base)
+    }
+
+    /// Get source code location of the specified `Locate`
+    pub fn get_origin(&self, locate: &Locate) -> Option<(&PathBuf, usize)> {
+        self.text.origin(locate.offset.checked_sub(self.base)?)
+    }
+
+    /// Get source code location of the specified `Locate` with line and column
+    pub fn get_location(&self, locate: &Locate) -> Option<Location> {
+        self.text.location(locate.offset.checked_sub(self.base)?)
+    }
+
+    /// Get the preprocessed text kept in the stream
+    ///
+    /// The text begins at `base` in the whole preprocessed text.
+    pub fn text(&self) -> &PreprocessedText {
+        &self.text
+    }
+
+    /// Get the offset of `text` in the whole preprocessed text
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// Get macros defined by the preprocessed part of the source
+    ///
+    /// This is `None` if the stream is not created from `PreprocessStream`.
+    pub fn defines(&self) -> Option<&Defines> {
+        self.source.as_ref().map(|x| x.defines())
+    }
+
+    fn span(&self) -> Span<'_> {
+        // `offset` and `line` always point to the end of the last parsed item,
+        // so the span is the same as if the whole text had been parsed from
+        // the beginning, and `Locate` of each `Description` is an offset in
+        // the whole text.
+        unsafe {
+            Span::new_from_raw_offset(
+                self.offset,
+                self.line,
+                &self.text.text()[self.offset - self.base..],
+                SpanInfo::default(),
+            )
+        }
+    }
+
+    fn parse<T>(
+        &mut self,
+        parser: impl Fn(Span) -> nom::IResult<Span, T, GreedyError<Span, ErrorKind>>,
+    ) -> Result<(usize, u32, T), Error> {
+        loop {
+            self.limits.check()?;
+            set_limits(&self.limits);
+            let result = {
+                let _version = set_language_version(self.language_version);
+                parser(self.span())
+            };
+            if let Some(e) = clear_limits() {
+                return Err(e);
+            }
+
+            // The end of text near the parsed position may change the result,
+            // e.g. an end label of `Description` may follow.
+            let preprocessed = self.preprocessed;
+            let end = self.base + self.text.text().len();
+            match result {
+                Ok((s, x)) if preprocessed || s.location_offset() + STREAM_LOOKAHEAD <= end => {
+                    return Ok((s.location_offset(), s.location_line(), x));
+                }
+                Ok(_) => (),
+                Err(x) => {
+                    let pos = match x {
+                        nom::Err::Incomplete(_) => None,
+                        nom::Err::Error(e) => error_position(&e),
+                        nom::Err::Failure(e) => error_position(&e),
+                    };
+                    if preprocessed || pos.is_some_and(|pos| pos + STREAM_LOOKAHEAD <= end) {
+                        let location = pos.and_then(|pos| self.text.location(pos - self.base));
+                        return Err(Error::Parse(location.map(Box::new)));
+                    }
+                }
+            }
+            self.fill()?;
+        }
+    }
+
+    // Preprocess parts until the text after `offset` is doubled
+    fn fill(&mut self) -> Result<(), Error> {
+        let len = self.text.text().len() - (self.offset - self.base);
+        if let Some(ref mut source) = self.source {
+            for x in source {
+                self.text.merge(x?);
+                let remaining = self.text.text().len() - (self.offset - self.base);
+                if remaining >= 2 * len + STREAM_LOOKAHEAD {
+                    return Ok(());
+                }
+            }
+        }
+        self.preprocessed = true;
+        Ok(())
+    }
+}
+
+impl Iterator for DescriptionStream {
+    type Item = Result<Description, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // The text of the last yielded `Description` is no longer resolved.
+        self.text.drain(self.offset - self.base);
+        self.base = self.offset;
+
+        if !self.started {
+            self.started = true;
+            match self.parse(sv_parser_stream_begin) {
+                Ok((offset, line, _)) => {
+                    self.offset = offset;
+                    self.line = line;
+                }
+                Err(x) => {
+                    self.finished = true;
+                    return Some(Err(x));
+                }
+            }
+        }
+
+        // At least `STREAM_LOOKAHEAD` follows the parsed position unless the
+        // whole source is preprocessed.
+        if self.offset >= self.base + self.text.text().len() {
+            self.finished = true;
+            return None;
+        }
+
+        let ret = self
+            .parse(sv_parser_stream_next)
+            .and_then(|(offset, line, x)| {
+                if let Some(ref mut check) = self.verilog_check {
+                    check.check((&x).into(), &self.text, self.base)?;
+                }
+                Ok((offset, line, x))
+            });
+        match ret {
+            Ok((offset, line, x)) => {
+                self.offset = offset;
+                self.line = line;
+                Some(Ok(x))
+            }
+            Err(x) => {
+                self.finished = true;
+                Some(Err(x))
+            }
+        }
+    }
+}
+
+/// Parse one `Description` at a time
+///
+/// The source is preprocessed as `Description` is parsed, so an error of
+/// preprocessing is returned by the stream.
+pub fn parse_sv_stream<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    ignore_include: bool,
+) -> Result<DescriptionStream, Error> {
+    let config = PreprocessConfig {
+        ignore_include,
+        ..PreprocessConfig::default()
+    };
+    parse_sv_stream_with_config(
+        path,
+        pre_defines,
+        include_paths,
+        &config,
+        LanguageVersion::default(),
+    )
+}
+
+/// Parse with `PreprocessConfig` and `LanguageVersion` one `Description` at a
+/// time
+///
+/// `config.limits` is applied to both preprocessing and parsing, and
+/// `language_version` selects the keywords outside of `` `begin_keywords ``.
+pub fn parse_sv_stream_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    language_version: LanguageVersion,
+) -> Result<DescriptionStream, Error> {
+    let source = preprocess_stream_with_config(path, pre_defines, include_paths, config)?;
+    Ok(DescriptionStream::with_source(
+        source,
+        config,
+        language_version,
+    ))
+}
+
+/// Parse a string one `Description` at a time
+pub fn parse_sv_str_stream<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    ignore_include: bool,
+) -> Result<DescriptionStream, Error> {
+    let config = PreprocessConfig {
+        ignore_include,
+        ..PreprocessConfig::default()
+    };
+    parse_sv_str_stream_with_config(
+        s,
+        path,
+        pre_defines,
+        include_paths,
+        &config,
+        LanguageVersion::default(),
+    )
+}
+
+/// Parse a string with `PreprocessConfig` and `LanguageVersion` one
+/// `Description` at a time
+///
+/// `config.limits` is applied to both preprocessing and parsing, and
+/// `language_version` selects the keywords outside of `` `begin_keywords ``.
+pub fn parse_sv_str_stream_with_config<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
+    s: &str,
+    path: T,
+    pre_defines: &Defines<V>,
+    include_paths: &[U],
+    config: &PreprocessConfig,
+    language_version: LanguageVersion,
+) -> Result<DescriptionStream, Error> {
+    let source = preprocess_str_stream_with_config(s, path, pre_defines, include_paths, config)?;
+    Ok(DescriptionStream::with_source(
+        source,
+        config,
+        language_version,
+    ))
+}
+
 pub fn parse_lib<T: AsRef<Path>, U: AsRef<Path>, V: BuildHasher>(
     path: T,
     pre_defines: &Defines<V>,
@@ -321,16 +801,8 @@ pub fn parse_lib_pp(
                 nom::Err::Error(e) => error_position(&e),
                 nom::Err::Failure(e) => error_position(&e),
             };
-            let origin = if let Some(pos) = pos {
-                if let Some(origin) = text.origin(pos) {
-                    Some((origin.0.clone(), origin.1))
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            Err(Error::Parse(origin))
+            let location = pos.and_then(|pos| text.location(pos)).map(Box::new);
+            Err(Error::Parse(location))
         }
     }
 }
@@ -410,6 +882,600 @@ mod test {
         assert!(comment.is_some());
     }
 
+    #[test]
+    fn test_compact() {
+        let src =
+            "module A; wire a; endmodule // comment\nmodule B; wire b; endmodule // comment\n";
+        let (syntax_tree, _) =
+            parse_sv_str(src, PathBuf::from(""), &HashMap::new(), &[""], false, false).unwrap();
+        let compact = syntax_tree.compact();
+        assert_eq!(compact.len(), syntax_tree.into_iter().count());
+
+        let root = compact.root().unwrap();
+        assert_eq!(compact.kind(root), NodeKind::SourceText);
+        let modules: Vec<_> = compact
+            .iter()
+            .filter(|x| compact.kind(*x) == NodeKind::ModuleDeclarationAnsi)
+            .collect();
+        assert_eq!(modules.len(), 2);
+        assert_eq!(
+            compact.parent(modules[0]).map(|x| compact.kind(x)),
+            Some(NodeKind::ModuleDeclaration)
+        );
+
+        let tokens: Vec<_> = compact
+            .descendants(modules[1])
+            .filter(|x| !compact.is_trivia(*x))
+            .filter_map(|x| compact.locate(x))
+            .map(|x| x.str(src))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec!["module", "B", ";", "wire", "b", ";", "endmodule"]
+        );
+
+        let comments: Vec<_> = compact
+            .iter()
+            .filter_map(|x| compact.trivia_text(x))
+            .filter(|x| x.starts_with("//"))
+            .collect();
+        assert_eq!(comments, vec!["// comment\n", "// comment\n"]);
+    }
+
+    #[test]
+    fn test_explore_configurations() {
+        let path = format!("{}/testcases/configurations.sv", env!("CARGO_MANIFEST_DIR"));
+        let report = explore_configurations(
+            path,
+            &HashMap::new(),
+            &[] as &[String],
+            &ExploreConfig::default(),
+        );
+        assert!(!report.truncated);
+        // combinations of A and B, and C toggled independently of them
+        assert_eq!(report.configurations.len(), 5);
+
+        // `wire` without a name is a syntax error only when both A and B are defined
+        let failures: Vec<_> = report.failures().map(|(_, x)| x.macros.clone()).collect();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0]["A"] && failures[0]["B"] && !failures[0]["C"]);
+
+        let descriptions: Vec<_> = report
+            .descriptions
+            .iter()
+            .map(|x| (x.name.as_str(), x.requires.clone()))
+            .collect();
+        let requires = |x: &[(&str, bool)]| {
+            x.iter()
+                .map(|(k, v)| (String::from(*k), *v))
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+        assert_eq!(
+            descriptions,
+            vec![
+                ("top", requires(&[])),
+                ("mod_a", requires(&[("A", true)])),
+                ("pkg_b", requires(&[("B", true)])),
+                ("mod_c", requires(&[("C", true)])),
+            ]
+        );
+        assert_eq!(report.descriptions[2].kind, NodeKind::PackageDeclaration);
+    }
+
+    #[test]
+    fn test_directive_states() {
+        let src = r##"`timescale 1ns/1ps
+`default_nettype none
+`celldefine
+module A;
+  timeunit 100ps;
+  module B; endmodule
+endmodule
+`endcelldefine
+`unconnected_drive pull1
+`begin_keywords "1800-2012"
+interface C; endinterface
+`resetall
+package D; endpackage
+`end_keywords
+program E; endprogram
+"##;
+        let (syntax_tree, _) =
+            parse_sv_str(src, PathBuf::from(""), &HashMap::new(), &[""], false, false).unwrap();
+        let states = syntax_tree.directive_states();
+        let names: Vec<_> = states.iter().map(|x| (x.kind, x.name.as_str())).collect();
+        assert_eq!(
+            names,
+            vec![
+                (NodeKind::ModuleDeclaration, "A"),
+                (NodeKind::ModuleDeclaration, "B"),
+                (NodeKind::InterfaceDeclaration, "C"),
+                (NodeKind::PackageDeclaration, "D"),
+                (NodeKind::ProgramDeclaration, "E"),
+            ]
+        );
+
+        let a = &states[0].state;
+        assert_eq!(a.time_unit.as_deref(), Some("100ps"));
+        assert_eq!(a.time_precision.as_deref(), Some("1ps"));
+        assert_eq!(a.default_nettype, "none");
+        assert!(a.celldefine);
+        assert_eq!(a.unconnected_drive, None);
+        assert_eq!(a.keywords, None);
+
+        // nested module inherits the time unit of the enclosing one
+        assert_eq!(states[1].state.time_unit.as_deref(), Some("100ps"));
+
+        let c = &states[2].state;
+        assert_eq!(c.time_unit.as_deref(), Some("1ns"));
+        assert!(!c.celldefine);
+        assert_eq!(c.unconnected_drive.as_deref(), Some("pull1"));
+        assert_eq!(c.keywords.as_deref(), Some("1800-2012"));
+
+        // `resetall keeps `begin_keywords
+        let d = &states[3].state;
+        assert_eq!(
+            *d,
+            DirectiveState {
+                keywords: Some(String::from("1800-2012")),
+                ..DirectiveState::default()
+            }
+        );
+        assert_eq!(states[4].state, DirectiveState::default());
+    }
+
+    #[test]
+    fn test_protected_envelope() {
+        let src = r##"module A (input clk);
+  wire a;
+`pragma protect begin_protected
+`pragma protect key_keyowner = "Vendor", key_method = "rsa"
+`pragma protect encoding = (enctype = "base64", line_length = 76, bytes = 32)
+`pragma protect key_block
+//8Ac2Vzc2lvbiBrZXk=
+`pragma protect data_method = "aes128-cbc"
+`pragma protect data_block
+ZW5kbW9kdWxl/+wire/*
+`pragma protect end_protected
+endmodule
+"##;
+        let (syntax_tree, _) =
+            parse_sv_str(src, PathBuf::from(""), &HashMap::new(), &[""], false, false).unwrap();
+        let data: Vec<_> = syntax_tree
+            .into_iter()
+            .filter_map(|x| match x {
+                RefNode::ProtectedData(x) => syntax_tree.get_str(x),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            data,
+            vec!["//8Ac2Vzc2lvbiBrZXk=\n", "ZW5kbW9kdWxl/+wire/*\n"]
+        );
+
+        let contents = syntax_tree.protected_contents();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(
+            contents[0].element,
+            Some((NodeKind::ModuleDeclaration, String::from("A")))
+        );
+        assert_eq!(contents[0].locate.line, 3);
+        let metadata: Vec<_> = contents[0]
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(
+            metadata,
+            vec![
+                ("key_keyowner", "Vendor"),
+                ("key_method", "rsa"),
+                (
+                    "encoding",
+                    "(enctype = \"base64\", line_length = 76, bytes = 32)"
+                ),
+                ("data_method", "aes128-cbc"),
+            ]
+        );
+
+        // IEEE1800-2017 Clause 34.3 puts begin_protected and data_block at the end
+        let src = r##"module secret (a, b);
+`pragma protect data_method="x-caesar", data_keyname="rot13",
+begin_protected
+`pragma protect encoding=(enctype="raw", bytes=190), data_block
+//`centzn cebgrpg ehagvzr_yvprafr=(yvoenel="yvp.fb",srngher="ehaFrperg",
+`pragma protect end_protected
+`pragma reset protect
+endmodule
+"##;
+        let (syntax_tree, _) =
+            parse_sv_str(src, PathBuf::from(""), &HashMap::new(), &[""], false, false).unwrap();
+        let data = unwrap_node!(&syntax_tree, ProtectedData);
+        let data = match data {
+            Some(RefNode::ProtectedData(x)) => syntax_tree.get_str(x),
+            _ => None,
+        };
+        assert_eq!(
+            data,
+            Some("//`centzn cebgrpg ehagvzr_yvprafr=(yvoenel=\"yvp.fb\",srngher=\"ehaFrperg\",\n")
+        );
+        let contents = syntax_tree.protected_contents();
+        assert_eq!(contents[0].metadata.len(), 3);
+        assert_eq!(contents[0].metadata[2].0, "encoding");
+    }
+
+    #[test]
+    fn test_macro_invocation() {
+        let src = r##"`define ADD(a, b) (a + b)
+`define INFO(msg) $display(msg);
+`define WIRE(name) wire name;
+`define WIDTH 8
+`define FIELD(name) int name;
+module A;
+  `WIRE(w)
+  assign x = `ADD(y, 1);
+  assign z = `WIDTH'd0;
+  initial begin
+    `INFO("hello")
+  end
+endmodule
+class B;
+  `FIELD(v)
+endclass
+"##;
+        let config = PreprocessConfig {
+            macro_invocations: true,
+            ..PreprocessConfig::default()
+        };
+        let (text, defines) =
+            preprocess_str_with_config(src, "", &HashMap::new(), &[""], &config).unwrap();
+        assert_eq!(text.macro_calls().len(), 5);
+        let (syntax_tree, _) = parse_sv_pp(text, defines, false).unwrap();
+
+        let mut ret = Vec::new();
+        for node in &syntax_tree {
+            if let RefNode::MacroInvocation(x) = node {
+                let item = match &x.nodes.0 {
+                    MacroInvocationItem::Expression(_) => "expression",
+                    MacroInvocationItem::StatementItem(_) => "statement",
+                    MacroInvocationItem::NonPortModuleItem(_) => "module_item",
+                    MacroInvocationItem::ClassItem(_) => "class_item",
+                };
+                let expansion = syntax_tree.get_str_trim(&x.nodes.0).unwrap();
+                ret.push((item, x.call.as_str(), expansion));
+            }
+        }
+        // `WIDTH'd0 is not a complete expression
+        assert_eq!(
+            ret,
+            vec![
+                ("module_item", "`WIRE(w)", "wire w;"),
+                ("expression", "`ADD(y, 1)", "(y + 1)"),
+                ("statement", "`INFO(\"hello\")", "$display(\"hello\");"),
+                ("class_item", "`FIELD(v)", "int v;"),
+            ]
+        );
+
+        // Without the configuration, the expansion is parsed as usual
+        let (syntax_tree, _) =
+            parse_sv_str(src, PathBuf::from(""), &HashMap::new(), &[""], false, false).unwrap();
+        assert!(unwrap_node!(&syntax_tree, MacroInvocation).is_none());
+    }
+
+    #[test]
+    fn test_limits() {
+        let src = "module a; assign x = y + z; endmodule";
+        let parse_limits = |limits: Limits| {
+            let config = PreprocessConfig {
+                limits,
+                ..PreprocessConfig::default()
+            };
+            parse_sv_str_with_config(
+                src,
+                "",
+                &HashMap::new(),
+                &[""],
+                &config,
+                LanguageVersion::default(),
+                false,
+            )
+        };
+
+        assert!(parse_limits(Limits::default()).is_ok());
+        assert!(parse_limits(Limits {
+            max_token_count: Some(1000),
+            ..Limits::default()
+        })
+        .is_ok());
+
+        let ret = parse_limits(Limits {
+            max_token_count: Some(5),
+            ..Limits::default()
+        });
+        assert!(matches!(ret, Err(Error::ExceedTokenCount)));
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let ret = parse_limits(Limits {
+            cancellation: Some(cancellation),
+            ..Limits::default()
+        });
+        assert!(matches!(ret, Err(Error::Cancelled)));
+
+        // Limits are cleared after the parse
+        assert!(parse_sv_str(src, "", &HashMap::new(), &[""], false, false).is_ok());
+    }
+
+    #[test]
+    fn test_language_version() {
+        let parse_version = |src: &str, language_version: LanguageVersion| {
+            let config = PreprocessConfig::default();
+            parse_sv_str_with_config(
+                src,
+                "",
+                &HashMap::new(),
+                &[""],
+                &config,
+                language_version,
+                false,
+            )
+        };
+        let verilog = |src: &str| parse_version(src, LanguageVersion::Verilog2005);
+
+        // SystemVerilog keywords are identifiers in Verilog
+        let src = "module a; reg [7:0] logic, bit; always @* logic = bit + 1; endmodule";
+        assert!(verilog(src).is_ok());
+        assert!(parse_version(src, LanguageVersion::SystemVerilog2017).is_err());
+
+        for src in [
+            "module a(input logic x); endmodule",
+            "module a; reg [7:0] x; initial x++; endmodule",
+            "module a; reg [7:0] x; initial x = '0; endmodule",
+            "module a; b u (.*); endmodule",
+            "module a; initial begin : b end : b endmodule",
+        ] {
+            assert!(
+                matches!(verilog(src), Err(Error::SystemVerilogConstruct { .. })),
+                "{}",
+                src
+            );
+            assert!(parse_version(src, LanguageVersion::default()).is_ok());
+        }
+
+        match verilog("module a;\n  logic x;\nendmodule") {
+            Err(Error::SystemVerilogConstruct { location, .. }) => {
+                let location = location.unwrap();
+                assert_eq!((location.line, location.column), (2, 3));
+            }
+            x => panic!("unexpected {:?}", x),
+        }
+
+        // `begin_keywords selects SystemVerilog in the region
+        let src = r#"`begin_keywords "1800-2017"
+module a(input logic x); endmodule
+`end_keywords"#;
+        assert!(verilog(src).is_ok());
+
+        // The language version is reset after the parse
+        let src = "module a; logic x; endmodule";
+        assert!(parse_sv_str(src, "", &HashMap::new(), &[""], false, false).is_ok());
+
+        // and even if the parse panics
+        let ret = std::panic::catch_unwind(|| {
+            let _version = set_language_version(LanguageVersion::Verilog2005);
+            panic!();
+        });
+        assert!(ret.is_err());
+        assert!(parse_expression_str("logic + 1").is_err());
+    }
+
+    #[cfg(feature = "ams")]
+    #[test]
+    fn test_ams() {
+        let src = r#"discipline electrical
+  potential Voltage;
+  flow Current;
+enddiscipline
+module a(p, n);
+  inout p, n;
+  electrical p, n;
+  analog I(p, n) <+ V(p, n) / 1e3;
+endmodule"#;
+        let (syntax_tree, _) = parse_sv_str_with_config(
+            src,
+            "",
+            &HashMap::new(),
+            &[""],
+            &PreprocessConfig::default(),
+            LanguageVersion::VerilogAms,
+            false,
+        )
+        .unwrap();
+        assert!(unwrap_node!(&syntax_tree, DisciplineDeclaration).is_some());
+        assert!(unwrap_node!(&syntax_tree, ContributionStatement).is_some());
+    }
+
+    #[test]
+    fn test_fragment() {
+        let x = parse_expression_str(" a + b * 2").unwrap();
+        assert!(matches!(x.node(), Expression::Binary(_)));
+        let tree: SyntaxTree = x.into();
+        let node = unwrap_node!(&tree, Expression).unwrap();
+        assert_eq!(tree.get_str(vec![node]), Some("a + b * 2"));
+
+        let tree: SyntaxTree = parse_statement_str("x <= y;").unwrap().into();
+        if let Some(RefNode::SimpleIdentifier(id)) = unwrap_node!(&tree, SimpleIdentifier) {
+            assert_eq!(tree.get_str(&id.nodes.0), Some("x"));
+        } else {
+            unreachable!();
+        }
+
+        let x = parse_data_type_str("logic [7:0]").unwrap();
+        assert!(matches!(x.node(), DataType::Vector(_)));
+
+        let tree: SyntaxTree = parse_module_item_str("`define W 8\nwire [`W-1:0] w;")
+            .unwrap()
+            .into();
+        let node = unwrap_node!(&tree, ModuleItem).unwrap();
+        assert_eq!(tree.get_str(vec![node]), Some("wire [8-1:0] w;"));
+
+        let x = parse_package_item_str("typedef int t;").unwrap();
+        let tree: SyntaxTree = x.into();
+        assert!(unwrap_node!(&tree, TypeDeclaration).is_some());
+
+        assert!(parse_constant_expression_str("1 + 2").is_ok());
+        assert!(parse_class_item_str("int x;").is_ok());
+        assert!(parse_interface_item_str("modport mp (input a);").is_ok());
+        assert!(parse_description_str("module a; endmodule").is_ok());
+
+        // The whole string must be consumed
+        assert!(matches!(
+            parse_expression_str("a + b;"),
+            Err(Error::Parse(Some(_)))
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let src = "module a;\n  wire w\nendmodule\n";
+        match parse_sv_str(src, "test.sv", &HashMap::new(), &[""], false, false) {
+            Err(Error::Parse(Some(x))) => {
+                assert_eq!(x.path, PathBuf::from("test.sv"));
+                assert_eq!((x.offset, x.line, x.column), (19, 3, 1));
+            }
+            x => panic!("unexpected {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let src = r##"`timescale 1ns/1ps
+module A; endmodule
+package B; endpackage
+timeunit 1ns;
+bind A C c();
+module D; endmodule
+"##;
+        let mut stream =
+            parse_sv_str_stream(src, PathBuf::from(""), &HashMap::new(), &[""], false).unwrap();
+        let mut names = vec![];
+        while let Some(x) = stream.next() {
+            let x = x.unwrap();
+            let name = match &x {
+                Description::ModuleDeclaration(_) => "module",
+                Description::PackageDeclaration(_) => "package",
+                Description::PackageItem(_) => "package_item",
+                Description::BindDirective(_) => "bind",
+                _ => "other",
+            };
+            names.push((name, String::from(stream.get_str(&x).unwrap().trim())));
+        }
+        assert_eq!(
+            names,
+            vec![
+                ("module", String::from("module A; endmodule")),
+                ("package", String::from("package B; endpackage")),
+                ("package_item", String::from("timeunit 1ns;")),
+                ("bind", String::from("bind A C c();")),
+                ("module", String::from("module D; endmodule")),
+            ]
+        );
+
+        let src_broken = "module A; endmodule\nmodule B; wire endmodule\nmodule C; endmodule\n";
+        let stream =
+            parse_sv_str_stream(src_broken, PathBuf::from(""), &HashMap::new(), &[""], false)
+                .unwrap();
+        let ret: Vec<_> = stream.collect();
+        assert_eq!(ret.len(), 2);
+        assert!(ret[0].is_ok());
+        assert!(ret[1].is_err());
+    }
+
+    #[test]
+    fn test_stream_bounded() {
+        let comment = format!("// {}\n", "-".repeat(1000));
+        let mut src = String::from("`define W 8\n");
+        for i in 0..300 {
+            if i == 150 {
+                src.push_str("`define X\n");
+            }
+            src.push_str(&comment);
+            src.push_str(&format!(
+                "module m{} (input [`W-1:0] a);\nendmodule : m{}\n",
+                i, i
+            ));
+        }
+        let mut stream =
+            parse_sv_str_stream(&src, PathBuf::from(""), &HashMap::new(), &[""], false).unwrap();
+        let mut count = 0;
+        let mut max_len = 0;
+        while let Some(x) = stream.next() {
+            let x = x.unwrap();
+            let name = format!(
+                "module m{} (input [8-1:0] a);\nendmodule : m{}",
+                count, count
+            );
+            assert!(stream.get_str(&x).unwrap().starts_with(&name));
+            let locate = RefNode::from(&x).into_iter().find_map(|x| match x {
+                RefNode::Locate(x) => Some(*x),
+                _ => None,
+            });
+            let location = stream.get_location(&locate.unwrap()).unwrap();
+            let line = count * 3 + if count < 150 { 3 } else { 4 };
+            assert_eq!((location.line, location.column), (line, 1));
+            max_len = max_len.max(stream.text().text().len());
+            count += 1;
+        }
+        assert_eq!(count, 300);
+        // The text is preprocessed and dropped part by part
+        assert!(max_len < src.len() / 2);
+        assert!(stream.defines().unwrap().contains_key("X"));
+    }
+
+    #[test]
+    fn test_stream_with_config() {
+        let src = "module A; endmodule\nmodule B(input logic a); endmodule\n";
+        let stream = parse_sv_str_stream_with_config(
+            src,
+            "test.v",
+            &HashMap::new(),
+            &[""],
+            &PreprocessConfig::default(),
+            LanguageVersion::Verilog2005,
+        )
+        .unwrap();
+        let ret: Vec<_> = stream.collect();
+        assert_eq!(ret.len(), 2);
+        assert!(ret[0].is_ok());
+        match &ret[1] {
+            Err(Error::SystemVerilogConstruct { location, .. }) => {
+                let location = location.as_ref().unwrap();
+                assert_eq!((location.line, location.column), (2, 16));
+            }
+            x => panic!("unexpected {:?}", x),
+        }
+
+        let config = PreprocessConfig {
+            limits: Limits {
+                max_token_count: Some(1),
+                ..Limits::default()
+            },
+            ..PreprocessConfig::default()
+        };
+        let stream = parse_sv_str_stream_with_config(
+            src,
+            "test.v",
+            &HashMap::new(),
+            &[""],
+            &config,
+            LanguageVersion::default(),
+        )
+        .unwrap();
+        let ret: Vec<_> = stream.collect();
+        assert_eq!(ret.len(), 1);
+        assert!(matches!(ret[0], Err(Error::ExceedTokenCount)));
+    }
+
     #[test]
     fn test_continuous() {
         let src = r##"`ifdef A
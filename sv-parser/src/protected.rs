@@ -0,0 +1,80 @@
+use crate::directive_state::is_design_element;
+use crate::{
+    Locate, NodeEvent, NodeKind, PragmaExpression, PragmaValue, ProtectedEnvelope,
+    ProtectedEnvelopeItem, RefNode, SyntaxTree,
+};
+use std::convert::TryInto;
+
+// -----------------------------------------------------------------------------
+
+/// A `pragma protect envelope with encrypted content
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProtectedContent {
+    /// The innermost module, interface, program or package containing the envelope
+    pub element: Option<(NodeKind, String)>,
+    /// Location of `pragma protect begin_protected
+    pub locate: Locate,
+    /// `keyword = value` pairs of `pragma protect in the envelope such as
+    /// `key_method` and `encoding`. Quotes of string values are removed.
+    pub metadata: Vec<(String, String)>,
+}
+
+impl SyntaxTree {
+    /// Get `pragma protect envelopes with the design elements containing them
+    pub fn protected_contents(&self) -> Vec<ProtectedContent> {
+        let mut ret = Vec::new();
+        let mut stack: Vec<Option<(NodeKind, String)>> = Vec::new();
+        for event in self.into_iter().event() {
+            match event {
+                NodeEvent::Enter(RefNode::ProtectedEnvelope(x)) => {
+                    ret.push(ProtectedContent {
+                        element: stack.iter().rev().find_map(|x| x.clone()),
+                        locate: x.try_into().unwrap(),
+                        metadata: self.protected_metadata(x),
+                    });
+                }
+                NodeEvent::Enter(x) => {
+                    if is_design_element(&x) {
+                        stack.push(self.design_element(&x));
+                    }
+                }
+                NodeEvent::Leave(x) => {
+                    if is_design_element(&x) {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    fn protected_metadata(&self, x: &ProtectedEnvelope) -> Vec<(String, String)> {
+        let mut ret = Vec::new();
+        let (ref begin, ref items, _) = x.nodes;
+        let pragmas = std::iter::once(begin).chain(items.iter().map(|x| match x {
+            ProtectedEnvelopeItem::Pragma(x) => x,
+            ProtectedEnvelopeItem::ProtectedBlock(x) => &x.nodes.0,
+        }));
+        for pragma in pragmas {
+            let expressions = match &pragma.nodes.3 {
+                Some(x) => x.contents(),
+                None => continue,
+            };
+            for x in expressions {
+                if let PragmaExpression::Assignment(x) = x {
+                    let (keyword, _, value) = &x.nodes;
+                    let keyword = self.get_str_trim(vec![keyword.into()]);
+                    let text = self.get_str_trim(vec![value.into()]);
+                    if let (Some(keyword), Some(text)) = (keyword, text) {
+                        let text = match value {
+                            PragmaValue::StringLiteral(_) => text.trim_matches('"'),
+                            _ => text,
+                        };
+                        ret.push((String::from(keyword), String::from(text)));
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
@@ -0,0 +1,18 @@
+module top;
+endmodule
+
+`ifdef A
+module mod_a;
+`ifdef B
+  wire
+`endif
+endmodule
+`elsif B
+package pkg_b;
+endpackage
+`endif
+
+`ifdef C
+module mod_c;
+endmodule
+`endif